@@ -0,0 +1,13 @@
+-- 价格数据质量标记：live 为真实行情，synthetic 为后备模拟数据（仅在启用后备价格时写入）
+ALTER TABLE price_history ADD COLUMN data_quality TEXT NOT NULL DEFAULT 'live' CHECK (data_quality IN ('live', 'synthetic'));
+
+CREATE INDEX IF NOT EXISTS idx_price_history_symbol_quality ON price_history(symbol, data_quality);
+
+-- 行情状态表 - 记录每个股票最近一次成功/失败的获取，用于判断行情是否陈旧
+CREATE TABLE IF NOT EXISTS price_feed_status (
+    symbol TEXT PRIMARY KEY,
+    last_success_at DATETIME,              -- 最近一次成功获取真实行情的时间
+    last_failure_at DATETIME,              -- 最近一次获取失败的时间
+    last_error TEXT,                       -- 最近一次失败的错误信息
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,25 @@
+-- 加密货币交易对映射表 - 本系统交易对代码与交易所交易对名称的对应关系
+CREATE TABLE IF NOT EXISTS crypto_symbol_mappings (
+    symbol TEXT NOT NULL,                  -- 本系统交易对代码 (如 BTC-USD)
+    venue TEXT NOT NULL,                   -- 交易所 (binance/okx)
+    venue_symbol TEXT NOT NULL,            -- 交易所交易对名称 (如 BTCUSDT / BTC-USDT)
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (symbol, venue)
+);
+
+-- 交易所没有美元现货对，USD报价映射到USDT交易对；稳定币本身不映射，继续使用Yahoo
+INSERT OR IGNORE INTO crypto_symbol_mappings (symbol, venue, venue_symbol)
+SELECT symbol, 'binance',
+       base_symbol || CASE WHEN quote_symbol = 'USD' THEN 'USDT' ELSE quote_symbol END
+FROM crypto_stocks
+WHERE category IS NOT 'Stablecoin';
+
+INSERT OR IGNORE INTO crypto_symbol_mappings (symbol, venue, venue_symbol)
+SELECT symbol, 'okx',
+       base_symbol || '-' || CASE WHEN quote_symbol = 'USD' THEN 'USDT' ELSE quote_symbol END
+FROM crypto_stocks
+WHERE category IS NOT 'Stablecoin';
+
+-- MATIC 已在交易所更名为 POL
+UPDATE crypto_symbol_mappings SET venue_symbol = 'POLUSDT' WHERE symbol = 'MATIC-USD' AND venue = 'binance';
+UPDATE crypto_symbol_mappings SET venue_symbol = 'POL-USDT' WHERE symbol = 'MATIC-USD' AND venue = 'okx';
@@ -0,0 +1,15 @@
+-- 价格隔离表 - 校验未通过的行情不写入价格历史、不触发预警，保存在此供排查
+CREATE TABLE IF NOT EXISTS price_quarantine (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    price REAL NOT NULL,
+    source TEXT NOT NULL,                  -- 产生该行情的数据源
+    reference_price REAL,                  -- 上一次有效价格
+    deviation_percent REAL,                -- 相对上一次有效价格的偏离
+    confirm_source TEXT,                   -- 用于确认的第二个数据源
+    confirm_price REAL,                    -- 第二个数据源给出的价格
+    reason TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_quarantine_symbol_created ON price_quarantine(symbol, created_at);
@@ -0,0 +1,42 @@
+-- 创建港股股票信息表
+CREATE TABLE IF NOT EXISTS hk_stocks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL UNIQUE,           -- 股票代码 (如 0700.HK)
+    code TEXT NOT NULL,                    -- 五位纯代码 (如 00700)
+    name_cn TEXT NOT NULL,                 -- 中文名称 (如 腾讯控股)
+    name_en TEXT,                          -- 英文名称 (如 Tencent)
+    pinyin TEXT NOT NULL,                  -- 拼音 (如 tengxunkonggu)
+    pinyin_short TEXT NOT NULL,            -- 拼音简写 (如 txkg)
+    industry TEXT,                         -- 行业
+    lot_size INTEGER NOT NULL DEFAULT 100, -- 每手股数，港股各股票不同
+    market_cap REAL,                       -- 市值
+    status TEXT DEFAULT 'active',          -- 状态 (active/suspended/delisted)
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 创建搜索索引
+CREATE INDEX IF NOT EXISTS idx_hk_stocks_symbol ON hk_stocks(symbol);
+CREATE INDEX IF NOT EXISTS idx_hk_stocks_code ON hk_stocks(code);
+CREATE INDEX IF NOT EXISTS idx_hk_stocks_name_cn ON hk_stocks(name_cn);
+CREATE INDEX IF NOT EXISTS idx_hk_stocks_pinyin_short ON hk_stocks(pinyin_short);
+
+-- 插入常见港股数据
+INSERT OR IGNORE INTO hk_stocks (symbol, code, name_cn, name_en, pinyin, pinyin_short, industry, lot_size) VALUES
+('0700.HK', '00700', '腾讯控股', 'Tencent', 'tengxunkonggu', 'txkg', '互联网', 100),
+('9988.HK', '09988', '阿里巴巴-W', 'Alibaba Group', 'alibaba', 'albb', '互联网', 100),
+('3690.HK', '03690', '美团-W', 'Meituan', 'meituan', 'mt', '互联网', 100),
+('1810.HK', '01810', '小米集团-W', 'Xiaomi', 'xiaomijituan', 'xmjt', '电子', 200),
+('0005.HK', '00005', '汇丰控股', 'HSBC Holdings', 'huifengkonggu', 'hfkg', '银行', 400),
+('0939.HK', '00939', '建设银行', 'China Construction Bank', 'jiansheyinhang', 'jsyh', '银行', 1000),
+('1299.HK', '01299', '友邦保险', 'AIA Group', 'youbangbaoxian', 'ybbx', '保险', 200),
+('0388.HK', '00388', '香港交易所', 'HKEX', 'xianggangjiaoyisuo', 'xgjys', '金融服务', 100),
+('0941.HK', '00941', '中国移动', 'China Mobile', 'zhongguoyidong', 'zgyd', '电信', 500),
+('1211.HK', '01211', '比亚迪股份', 'BYD Company', 'biyadigufen', 'bydgf', '汽车', 500);
+
+-- 补充北交所股票
+INSERT OR IGNORE INTO cn_stocks (symbol, code, exchange, name_cn, name_en, pinyin, pinyin_short, industry) VALUES
+('430047.BJ', '430047', 'BJ', '诺思兰德', 'NovoRock Biotherapeutics', 'nuosilande', 'nsld', '医药'),
+('832566.BJ', '832566', 'BJ', '梓橦宫', 'Zitonggong Pharmaceutical', 'zitonggong', 'ztg', '医药'),
+('835185.BJ', '835185', 'BJ', '贝特瑞', 'BTR New Material', 'beiterui', 'btr', '电池材料'),
+('830799.BJ', '830799', 'BJ', '艾融软件', 'iAsset Software', 'airongruanjian', 'arrj', '软件服务');
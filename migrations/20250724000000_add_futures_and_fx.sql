@@ -0,0 +1,61 @@
+-- 创建期货合约信息表（国际期货使用 Yahoo 代码如 GC=F，国内期货如 AU2512.SHF，AU0.SHF 为主力连续）
+CREATE TABLE IF NOT EXISTS futures_contracts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL UNIQUE,           -- 合约代码 (如 GC=F / AU0.SHF)
+    code TEXT NOT NULL,                    -- 品种代码 (如 GC / AU)
+    exchange TEXT NOT NULL,                -- 交易所 (COMEX/NYMEX/SHFE)
+    name_cn TEXT NOT NULL,                 -- 中文名称 (如 COMEX黄金)
+    name_en TEXT NOT NULL,                 -- 英文名称 (如 Gold Futures)
+    pinyin TEXT NOT NULL,                  -- 拼音 (如 huangjin)
+    pinyin_short TEXT NOT NULL,            -- 拼音简写 (如 hj)
+    category TEXT,                         -- 分类 (贵金属/能源/有色金属/黑色金属)
+    currency TEXT NOT NULL,                -- 计价货币 (USD/CNY)
+    unit TEXT,                             -- 报价单位 (如 美元/盎司)
+    multiplier REAL,                       -- 合约乘数
+    status TEXT DEFAULT 'active',          -- 状态 (active/expired)
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_futures_contracts_symbol ON futures_contracts(symbol);
+CREATE INDEX IF NOT EXISTS idx_futures_contracts_code ON futures_contracts(code);
+CREATE INDEX IF NOT EXISTS idx_futures_contracts_name_cn ON futures_contracts(name_cn);
+CREATE INDEX IF NOT EXISTS idx_futures_contracts_pinyin_short ON futures_contracts(pinyin_short);
+
+INSERT OR IGNORE INTO futures_contracts (symbol, code, exchange, name_cn, name_en, pinyin, pinyin_short, category, currency, unit, multiplier) VALUES
+('GC=F', 'GC', 'COMEX', 'COMEX黄金', 'Gold Futures', 'huangjin', 'hj', '贵金属', 'USD', '美元/盎司', 100),
+('SI=F', 'SI', 'COMEX', 'COMEX白银', 'Silver Futures', 'baiyin', 'by', '贵金属', 'USD', '美元/盎司', 5000),
+('HG=F', 'HG', 'COMEX', 'COMEX铜', 'Copper Futures', 'tong', 't', '有色金属', 'USD', '美元/磅', 25000),
+('CL=F', 'CL', 'NYMEX', 'WTI原油', 'Crude Oil Futures', 'yuanyou', 'yy', '能源', 'USD', '美元/桶', 1000),
+('NG=F', 'NG', 'NYMEX', '天然气', 'Natural Gas Futures', 'tianranqi', 'trq', '能源', 'USD', '美元/百万英热', 10000),
+('AU0.SHF', 'AU', 'SHFE', '沪金主力', 'SHFE Gold', 'hujin', 'hj', '贵金属', 'CNY', '元/克', 1000),
+('AG0.SHF', 'AG', 'SHFE', '沪银主力', 'SHFE Silver', 'huyin', 'hy', '贵金属', 'CNY', '元/千克', 15),
+('CU0.SHF', 'CU', 'SHFE', '沪铜主力', 'SHFE Copper', 'hutong', 'ht', '有色金属', 'CNY', '元/吨', 5),
+('AL0.SHF', 'AL', 'SHFE', '沪铝主力', 'SHFE Aluminium', 'hulv', 'hl', '有色金属', 'CNY', '元/吨', 5),
+('RB0.SHF', 'RB', 'SHFE', '螺纹钢主力', 'SHFE Rebar', 'luowengang', 'lwg', '黑色金属', 'CNY', '元/吨', 10);
+
+-- 创建外汇货币对信息表（使用 Yahoo 代码，如 USDCNH=X）
+CREATE TABLE IF NOT EXISTS fx_pairs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL UNIQUE,           -- 货币对代码 (如 USDCNH=X)
+    base_currency TEXT NOT NULL,           -- 基础货币 (如 USD)
+    quote_currency TEXT NOT NULL,          -- 报价货币 (如 CNH)
+    name_cn TEXT NOT NULL,                 -- 中文名称 (如 美元/离岸人民币)
+    name_en TEXT NOT NULL,                 -- 英文名称 (如 USD/CNH)
+    status TEXT DEFAULT 'active',
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_fx_pairs_symbol ON fx_pairs(symbol);
+CREATE INDEX IF NOT EXISTS idx_fx_pairs_name_cn ON fx_pairs(name_cn);
+
+INSERT OR IGNORE INTO fx_pairs (symbol, base_currency, quote_currency, name_cn, name_en) VALUES
+('USDCNH=X', 'USD', 'CNH', '美元/离岸人民币', 'USD/CNH'),
+('USDCNY=X', 'USD', 'CNY', '美元/人民币', 'USD/CNY'),
+('USDHKD=X', 'USD', 'HKD', '美元/港元', 'USD/HKD'),
+('EURUSD=X', 'EUR', 'USD', '欧元/美元', 'EUR/USD'),
+('USDJPY=X', 'USD', 'JPY', '美元/日元', 'USD/JPY'),
+('GBPUSD=X', 'GBP', 'USD', '英镑/美元', 'GBP/USD'),
+('AUDUSD=X', 'AUD', 'USD', '澳元/美元', 'AUD/USD'),
+('EURCNY=X', 'EUR', 'CNY', '欧元/人民币', 'EUR/CNY');
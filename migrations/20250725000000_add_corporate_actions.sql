@@ -0,0 +1,14 @@
+-- 公司行动表 - 拆股、现金分红以及A股送转/除权除息，用于复权价格历史与调整预警价位
+CREATE TABLE IF NOT EXISTS corporate_actions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    ex_date DATE NOT NULL,                 -- 除权除息日，该日及之后的行情为除权后价格
+    action_type TEXT NOT NULL CHECK (action_type IN ('split', 'dividend', 'bonus')),
+    split_ratio REAL NOT NULL DEFAULT 1.0, -- 每股变为多少股：10拆1为10，10送5为1.5，合股小于1
+    cash_dividend REAL NOT NULL DEFAULT 0, -- 每股现金分红（税前）
+    description TEXT,
+    alerts_rescaled_at DATETIME,           -- 已按拆股比例调整活跃预警价位的时间
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_corporate_actions_symbol_date ON corporate_actions(symbol, ex_date);
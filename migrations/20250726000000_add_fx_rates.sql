@@ -0,0 +1,15 @@
+-- 汇率表 - 由数据源定期写入或手工录入，保留历史用于回溯换算
+CREATE TABLE IF NOT EXISTS fx_rates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    base_currency TEXT NOT NULL,           -- 1单位基础货币
+    quote_currency TEXT NOT NULL,          -- 折合多少报价货币
+    rate REAL NOT NULL CHECK (rate > 0),
+    source TEXT NOT NULL,                  -- 数据源名称，手工录入为 manual
+    as_of DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_fx_rates_pair_as_of ON fx_rates(base_currency, quote_currency, as_of);
+
+-- 预警阈值的计价货币，为空表示与股票本身的计价货币相同
+ALTER TABLE alerts ADD COLUMN threshold_currency TEXT;
@@ -0,0 +1,6 @@
+-- 价格来源追溯：每次写入价格时记录数据源、获取方式、获取时间、交易所时间与原始响应摘要
+ALTER TABLE price_history ADD COLUMN source TEXT;              -- 数据源：sina、tencent、yahoo、binance、okx、synthetic
+ALTER TABLE price_history ADD COLUMN fetch_origin TEXT;        -- 获取方式：poller、stream、on_demand、backfill
+ALTER TABLE price_history ADD COLUMN fetched_at DATETIME;      -- 本服务获取行情的时间（UTC）
+ALTER TABLE price_history ADD COLUMN exchange_time DATETIME;   -- 数据源给出的行情时间（UTC），无法解析时为空
+ALTER TABLE price_history ADD COLUMN raw_hash TEXT;            -- 原始响应的 SHA-256 摘要
@@ -0,0 +1,26 @@
+-- 日内原始行情 - 获取到的每一笔真实行情，超过保留天数后降采样到 price_bars 并删除
+CREATE TABLE IF NOT EXISTS price_ticks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    price REAL NOT NULL,
+    volume INTEGER NOT NULL,               -- 数据源报告的成交量
+    source TEXT NOT NULL,                  -- 产生该行情的数据源
+    fetched_at DATETIME NOT NULL           -- 本服务获取行情的时间（UTC）
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_ticks_symbol_fetched ON price_ticks(symbol, fetched_at);
+CREATE INDEX IF NOT EXISTS idx_price_ticks_fetched ON price_ticks(fetched_at);
+
+-- 降采样K线 - 由过期的原始行情合成的 5m/1h/1d K线
+CREATE TABLE IF NOT EXISTS price_bars (
+    symbol TEXT NOT NULL,
+    interval TEXT NOT NULL CHECK (interval IN ('5m', '1h', '1d')),
+    bucket_start DATETIME NOT NULL,        -- 周期起始时间（UTC）
+    open_price REAL NOT NULL,
+    high_price REAL NOT NULL,
+    low_price REAL NOT NULL,
+    close_price REAL NOT NULL,
+    volume INTEGER NOT NULL,               -- 周期结束时数据源报告的成交量
+    tick_count INTEGER NOT NULL,
+    PRIMARY KEY (symbol, interval, bucket_start)
+);
@@ -0,0 +1,3 @@
+-- 美股盘前盘后行情：预警可选择在盘前盘后也参与判断，日内行情记录所属交易时段
+ALTER TABLE alerts ADD COLUMN extended_hours BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE price_ticks ADD COLUMN session TEXT NOT NULL DEFAULT 'regular' CHECK (session IN ('regular', 'pre_market', 'after_hours'));
@@ -0,0 +1,7 @@
+-- A股流通股本（股），用于计算换手率，缺失或过期时从腾讯行情更新
+ALTER TABLE cn_stocks ADD COLUMN float_shares REAL;
+ALTER TABLE cn_stocks ADD COLUMN float_shares_updated_at DATETIME;
+
+-- 按技术指标设定阈值的预警，为空时按价格判断
+ALTER TABLE alerts ADD COLUMN indicator TEXT
+    CHECK (indicator IN ('kdj_k', 'kdj_d', 'kdj_j', 'boll_width', 'volume_ratio', 'turnover_rate'));
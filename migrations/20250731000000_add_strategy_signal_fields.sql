@@ -0,0 +1,14 @@
+-- 策略信号按类型保存完整字段，读取时还原为对应的信号结构
+ALTER TABLE strategy_signals ADD COLUMN limit_up_date TEXT;        -- 涨停回踩：涨停日期
+ALTER TABLE strategy_signals ADD COLUMN limit_up_price REAL;       -- 涨停回踩：涨停价格
+ALTER TABLE strategy_signals ADD COLUMN volume_ratio REAL;         -- 涨停回踩为缩量比例，底部突破为放量倍数
+ALTER TABLE strategy_signals ADD COLUMN support_level REAL;        -- 涨停回踩：支撑位
+ALTER TABLE strategy_signals ADD COLUMN resistance_level REAL;     -- 底部突破：突破的阻力位
+ALTER TABLE strategy_signals ADD COLUMN ma_position REAL;          -- 底部突破：相对年线位置（%）
+ALTER TABLE strategy_signals ADD COLUMN indicator_name TEXT;       -- 技术指标：信号名称
+ALTER TABLE strategy_signals ADD COLUMN indicator_value REAL;      -- 技术指标：指标值
+
+-- 旧记录缺少还原所需的字段且 symbol 可能为日期，全部置为失效
+UPDATE strategy_signals SET is_active = 0;
+
+CREATE INDEX IF NOT EXISTS idx_strategy_signals_expires ON strategy_signals(is_active, expires_at);
@@ -0,0 +1,28 @@
+-- 收盘后策略扫描的运行记录，每个市场每次收盘只扫描一次
+CREATE TABLE IF NOT EXISTS strategy_scan_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    market TEXT NOT NULL,                          -- 市场 (cn/us/hk...)
+    universe TEXT NOT NULL,                        -- 扫描范围 (cn_stocks/watchlist/index)
+    trigger_type TEXT NOT NULL DEFAULT 'scheduled' CHECK (trigger_type IN ('scheduled', 'manual')),
+    session_close DATETIME,                        -- 对应的收盘时间，手动扫描为空
+    status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed')),
+    symbols_total INTEGER NOT NULL DEFAULT 0,      -- 扫描的股票数
+    symbols_failed INTEGER NOT NULL DEFAULT 0,     -- 分析失败的股票数
+    signals_new INTEGER NOT NULL DEFAULT 0,        -- 新保存的信号数
+    signals_duplicate INTEGER NOT NULL DEFAULT 0,  -- 已存在而跳过的信号数
+    signals_expired INTEGER NOT NULL DEFAULT 0,    -- 本次置为失效的过期信号数
+    error TEXT,
+    started_at DATETIME NOT NULL,
+    finished_at DATETIME
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_strategy_scan_runs_close ON strategy_scan_runs(market, session_close);
+CREATE INDEX IF NOT EXISTS idx_strategy_scan_runs_started ON strategy_scan_runs(started_at);
+
+-- 指数成分股，供按指数扫描使用
+CREATE TABLE IF NOT EXISTS index_constituents (
+    index_symbol TEXT NOT NULL,                    -- 指数代码 (如 000300.SH)
+    symbol TEXT NOT NULL,                          -- 成分股代码
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (index_symbol, symbol)
+);
@@ -0,0 +1,2 @@
+-- 收盘扫描同时计算综合评分，记录保存了评分的股票数
+ALTER TABLE strategy_scan_runs ADD COLUMN symbols_scored INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- 运行时通过 API 修改的形态策略参数，覆盖配置文件中的同一范围
+CREATE TABLE IF NOT EXISTS strategy_param_overrides (
+    scope TEXT PRIMARY KEY,        -- default、市场 (us/cn/crypto...) 或A股板块 (main/chinext/star/bse)
+    params TEXT NOT NULL,          -- JSON: 覆盖的参数
+    updated_at DATETIME NOT NULL
+);
+
+-- 记录产生信号的参数组
+ALTER TABLE strategy_signals ADD COLUMN param_set TEXT;  -- 参数组名称，如 cn/chinext、us；技术指标信号为 indicators
+ALTER TABLE strategy_signals ADD COLUMN params TEXT;     -- JSON: 产生信号时生效的参数
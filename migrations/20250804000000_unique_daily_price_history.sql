@@ -0,0 +1,32 @@
+-- 价格历史每个股票每天只保留一根日线：此前每次轮询都新增一行，同一天会有多行
+-- 先把同一天、同一数据质量的多行合并到最后写入的一行：开盘取第一笔，最高/最低取极值，收盘与成交量取最后一笔
+UPDATE price_history
+SET open_price = (
+        SELECT f.open_price FROM price_history f
+        WHERE f.symbol = price_history.symbol AND f.date = price_history.date
+          AND f.data_quality = price_history.data_quality
+        ORDER BY f.id
+        LIMIT 1
+    ),
+    high_price = (
+        SELECT MAX(f.high_price) FROM price_history f
+        WHERE f.symbol = price_history.symbol AND f.date = price_history.date
+          AND f.data_quality = price_history.data_quality
+    ),
+    low_price = (
+        SELECT MIN(f.low_price) FROM price_history f
+        WHERE f.symbol = price_history.symbol AND f.date = price_history.date
+          AND f.data_quality = price_history.data_quality
+    )
+WHERE id IN (SELECT MAX(id) FROM price_history GROUP BY symbol, date, data_quality);
+
+-- 每天保留合并后的一行，有真实行情时丢弃模拟数据
+DELETE FROM price_history
+WHERE id NOT IN (
+    SELECT COALESCE(MAX(CASE WHEN data_quality = 'live' THEN id END), MAX(id))
+    FROM price_history
+    GROUP BY symbol, date
+);
+
+DROP INDEX IF EXISTS idx_price_history_symbol_date;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_price_history_symbol_date ON price_history(symbol, date);
@@ -1,4 +1,6 @@
-use serde::Deserialize;
+use crate::services::CryptoVenue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::net::SocketAddr;
 
 #[derive(Debug, Deserialize)]
@@ -32,6 +34,224 @@ pub struct PriceFetcherConfig {
     pub max_requests_per_hour: u64,
     pub request_timeout_secs: u64,
     pub pool_idle_timeout_secs: u64,
+    #[serde(default = "default_cycle_timeout_secs")]
+    pub cycle_timeout_secs: u64,
+    /// 获取失败时是否写入模拟的后备价格（标记为 synthetic，不触发预警）
+    #[serde(default)]
+    pub fallback_prices_enabled: bool,
+    /// 按数据源配置的令牌桶，未配置的数据源使用 max_requests_per_hour
+    #[serde(default)]
+    pub rate_limits: HashMap<String, ProviderRateLimitConfig>,
+    /// 加密货币交易所数据源
+    #[serde(default)]
+    pub crypto: CryptoProviderConfig,
+    /// 加密货币行情推送（WebSocket），推送不健康时由轮询兜底
+    #[serde(default)]
+    pub stream: StreamConfig,
+    /// 数据源熔断
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// 写入前的价格校验
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    /// 汇率数据源
+    #[serde(default)]
+    pub fx: FxProviderConfig,
+    /// 行情缓存，报价在 cache_ttl_secs 内直接复用
+    #[serde(default)]
+    pub quote_cache: QuoteCacheConfig,
+    /// 日内行情存储与保留策略
+    #[serde(default)]
+    pub intraday: IntradayConfig,
+    /// 美股盘前盘后为开启了 extended_hours 的预警获取行情
+    #[serde(default = "default_extended_hours_enabled")]
+    pub extended_hours_enabled: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct IntradayConfig {
+    /// 是否保存每一笔真实行情
+    #[serde(default = "default_intraday_enabled")]
+    pub enabled: bool,
+    /// 原始行情保留天数，之前的行情降采样为 5m/1h/1d K线后删除
+    #[serde(default = "default_intraday_retention_days")]
+    pub retention_days: i64,
+    #[serde(default = "default_intraday_downsample_interval_secs")]
+    pub downsample_interval_secs: u64,
+}
+
+impl Default for IntradayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_intraday_enabled(),
+            retention_days: default_intraday_retention_days(),
+            downsample_interval_secs: default_intraday_downsample_interval_secs(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct QuoteCacheConfig {
+    /// 多实例部署时共享报价的 Redis 地址，如 redis://127.0.0.1:6379/0，未配置时只使用本地缓存
+    #[serde(default)]
+    pub redis_url: Option<String>,
+    #[serde(default = "default_quote_cache_key_prefix")]
+    pub key_prefix: String,
+}
+
+impl Default for QuoteCacheConfig {
+    fn default() -> Self {
+        Self {
+            redis_url: None,
+            key_prefix: default_quote_cache_key_prefix(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct FxProviderConfig {
+    #[serde(default = "default_fx_enabled")]
+    pub enabled: bool,
+    /// 定期从 Yahoo 获取的货币对（基础货币+报价货币），其他货币对经 USD 交叉换算
+    #[serde(default = "default_fx_pairs")]
+    pub pairs: Vec<String>,
+    #[serde(default = "default_fx_update_interval_secs")]
+    pub update_interval_secs: u64,
+    /// 超过该时长的汇率不再用于换算（手工录入的汇率同样适用）
+    #[serde(default = "default_fx_max_rate_age_hours")]
+    pub max_rate_age_hours: i64,
+}
+
+impl Default for FxProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_fx_enabled(),
+            pairs: default_fx_pairs(),
+            update_interval_secs: default_fx_update_interval_secs(),
+            max_rate_age_hours: default_fx_max_rate_age_hours(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ValidationConfig {
+    #[serde(default = "default_validation_enabled")]
+    pub enabled: bool,
+    /// 相对上一次有效价格偏离超过该百分比时，需要第二个数据源确认
+    #[serde(default = "default_max_deviation_percent")]
+    pub max_deviation_percent: f64,
+    /// 两个数据源价格相差在该百分比内视为一致
+    #[serde(default = "default_confirm_tolerance_percent")]
+    pub confirm_tolerance_percent: f64,
+    /// A股每次都用新浪与腾讯交叉核对（请求量翻倍）
+    #[serde(default)]
+    pub cn_cross_check: bool,
+    /// 没有第二个数据源时，同一数据源10分钟内重复给出一致价格也视为确认。
+    /// 卡住的数据源会重复同一个错误价格，默认关闭
+    #[serde(default)]
+    pub repeat_confirm: bool,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_validation_enabled(),
+            max_deviation_percent: default_max_deviation_percent(),
+            confirm_tolerance_percent: default_confirm_tolerance_percent(),
+            cn_cross_check: false,
+            repeat_confirm: false,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CircuitBreakerConfig {
+    /// 连续失败多少次后熔断
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// 熔断冷却时间，结束后放行一个探测请求
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// 统计成功率与延迟的最近请求数
+    #[serde(default = "default_health_window")]
+    pub window: usize,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_failure_threshold(),
+            cooldown_secs: default_cooldown_secs(),
+            window: default_health_window(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ProviderRateLimitConfig {
+    pub requests_per_hour: u64,
+    pub burst: u32,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CryptoProviderConfig {
+    #[serde(default = "default_crypto_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_crypto_venue")]
+    pub venue: CryptoVenue,
+    /// 未配置时使用交易所官方地址
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// 加密货币24小时交易，使用独立于股票的更新间隔
+    #[serde(default = "default_crypto_update_interval_secs")]
+    pub update_interval_secs: u64,
+    #[serde(default = "default_crypto_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// 首次监控某交易对时回填的日K线数量，0 表示不回填
+    #[serde(default = "default_kline_backfill_days")]
+    pub kline_backfill_days: u32,
+}
+
+impl Default for CryptoProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_crypto_enabled(),
+            venue: default_crypto_venue(),
+            base_url: None,
+            update_interval_secs: default_crypto_update_interval_secs(),
+            request_timeout_secs: default_crypto_request_timeout_secs(),
+            kline_backfill_days: default_kline_backfill_days(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StreamConfig {
+    #[serde(default = "default_stream_enabled")]
+    pub enabled: bool,
+    /// 未配置时使用交易所官方推送地址
+    #[serde(default)]
+    pub url: Option<String>,
+    /// 超过该时间未收到某交易对的推送即视为不健康，恢复轮询
+    #[serde(default = "default_stale_after_secs")]
+    pub stale_after_secs: u64,
+    /// 推送行情写入价格历史的最小间隔，每笔推送仍会检查预警
+    #[serde(default = "default_persist_interval_secs")]
+    pub persist_interval_secs: u64,
+    #[serde(default = "default_reconnect_max_backoff_secs")]
+    pub reconnect_max_backoff_secs: u64,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_stream_enabled(),
+            url: None,
+            stale_after_secs: default_stale_after_secs(),
+            persist_interval_secs: default_persist_interval_secs(),
+            reconnect_max_backoff_secs: default_reconnect_max_backoff_secs(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -75,6 +295,624 @@ pub struct Config {
     pub email: EmailConfig,
     #[serde(default = "default_demo_config")]
     pub demo: DemoConfig,
+    #[serde(default)]
+    pub calendar: CalendarConfig,
+    #[serde(default)]
+    pub corporate_actions: CorporateActionsConfig,
+    #[serde(default)]
+    pub strategy: StrategyConfig,
+}
+
+/// 交易日历配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct CalendarConfig {
+    /// 日历数据目录（us.toml、cn.toml、crypto.toml），缺失的文件使用内置数据
+    #[serde(default = "default_calendar_data_dir")]
+    pub data_dir: String,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            data_dir: default_calendar_data_dir(),
+        }
+    }
+}
+
+fn default_calendar_data_dir() -> String {
+    "config/calendars".to_string()
+}
+
+/// 公司行动配置
+#[derive(Debug, Deserialize, Clone)]
+pub struct CorporateActionsConfig {
+    /// 拆股/送转到达除权日后，自动按比例调整除权日前创建的活跃预警价位并通知预警所有者
+    #[serde(default = "default_rescale_alerts")]
+    pub rescale_alerts: bool,
+    /// 检查到期公司行动的间隔
+    #[serde(default = "default_rescale_check_interval_secs")]
+    pub check_interval_secs: u64,
+}
+
+impl Default for CorporateActionsConfig {
+    fn default() -> Self {
+        Self {
+            rescale_alerts: default_rescale_alerts(),
+            check_interval_secs: default_rescale_check_interval_secs(),
+        }
+    }
+}
+
+/// 策略分析配置
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct StrategyConfig {
+    #[serde(default)]
+    pub indicators: IndicatorConfig,
+    #[serde(default)]
+    pub backtest: BacktestConfig,
+    #[serde(default)]
+    pub scanner: ScannerConfig,
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+    #[serde(default)]
+    pub params: StrategyParams,
+}
+
+/// 形态策略参数：默认值，按市场、A股板块依次覆盖
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StrategyParams {
+    #[serde(default)]
+    pub default: PatternParams,
+    /// 按市场覆盖，键为 us、cn、hk、futures、cn_futures、fx、crypto
+    #[serde(default)]
+    pub markets: HashMap<String, PatternParamsOverride>,
+    /// 按A股板块覆盖，键为 main、chinext、star、bse，优先于市场覆盖
+    #[serde(default = "default_board_params")]
+    pub boards: HashMap<String, PatternParamsOverride>,
+}
+
+impl Default for StrategyParams {
+    fn default() -> Self {
+        Self {
+            default: PatternParams::default(),
+            markets: HashMap::new(),
+            boards: default_board_params(),
+        }
+    }
+}
+
+/// 涨停回踩与底部突破的判定阈值
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PatternParams {
+    /// 单日涨幅（%）不低于该值视为涨停
+    #[serde(default = "default_limit_up_percent")]
+    pub limit_up_percent: f64,
+    /// 在最近多少根K线内寻找涨停
+    #[serde(default = "default_limit_up_lookback_days")]
+    pub limit_up_lookback_days: usize,
+    /// 回踩当日成交量不高于涨停日的该比例才算缩量
+    #[serde(default = "default_pullback_volume_ratio")]
+    pub pullback_volume_ratio: f64,
+    /// 低于该比例为深度缩量，信号强度加一
+    #[serde(default = "default_deep_shrink_ratio")]
+    pub deep_shrink_ratio: f64,
+    /// 收盘价与 MA120、MA250 的偏离（%）都在该范围内才视为底部
+    #[serde(default = "default_ma_proximity_percent")]
+    pub ma_proximity_percent: f64,
+    /// 计算突破量比的平均成交量K线数
+    #[serde(default = "default_volume_average_days")]
+    pub volume_average_days: usize,
+    /// 突破当日量比不低于该值
+    #[serde(default = "default_breakout_volume_ratio")]
+    pub breakout_volume_ratio: f64,
+    /// 量比高于该值时信号强度加一
+    #[serde(default = "default_strong_volume_ratio")]
+    pub strong_volume_ratio: f64,
+    /// 阻力位取之前多少根K线的最高价
+    #[serde(default = "default_resistance_lookback_days")]
+    pub resistance_lookback_days: usize,
+    /// 最高价需高出阻力位的幅度（%）
+    #[serde(default = "default_breakout_percent")]
+    pub breakout_percent: f64,
+}
+
+impl Default for PatternParams {
+    fn default() -> Self {
+        Self {
+            limit_up_percent: default_limit_up_percent(),
+            limit_up_lookback_days: default_limit_up_lookback_days(),
+            pullback_volume_ratio: default_pullback_volume_ratio(),
+            deep_shrink_ratio: default_deep_shrink_ratio(),
+            ma_proximity_percent: default_ma_proximity_percent(),
+            volume_average_days: default_volume_average_days(),
+            breakout_volume_ratio: default_breakout_volume_ratio(),
+            strong_volume_ratio: default_strong_volume_ratio(),
+            resistance_lookback_days: default_resistance_lookback_days(),
+            breakout_percent: default_breakout_percent(),
+        }
+    }
+}
+
+/// 部分覆盖形态参数，未设置的字段沿用上一级
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PatternParamsOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_up_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_up_lookback_days: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pullback_volume_ratio: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deep_shrink_ratio: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ma_proximity_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_average_days: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakout_volume_ratio: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strong_volume_ratio: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resistance_lookback_days: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakout_percent: Option<f64>,
+}
+
+/// 综合评分的建议阈值与波动率风险分级
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScoringConfig {
+    /// 评分不低于该值时建议买入（高风险时改为观望）
+    #[serde(default = "default_buy_score")]
+    pub buy_score: f64,
+    /// 评分不高于该值时建议卖出
+    #[serde(default = "default_sell_score")]
+    pub sell_score: f64,
+    /// 计算波动率与平均成交量的K线数
+    #[serde(default = "default_lookback_period")]
+    pub lookback_period: usize,
+    /// 日收益率标准差（%）低于该值为低风险
+    #[serde(default = "default_low_volatility_percent")]
+    pub low_volatility_percent: f64,
+    /// 日收益率标准差（%）高于该值为高风险
+    #[serde(default = "default_high_volatility_percent")]
+    pub high_volatility_percent: f64,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            buy_score: default_buy_score(),
+            sell_score: default_sell_score(),
+            lookback_period: default_lookback_period(),
+            low_volatility_percent: default_low_volatility_percent(),
+            high_volatility_percent: default_high_volatility_percent(),
+        }
+    }
+}
+
+/// 收盘后策略扫描的股票范围
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanUniverse {
+    /// cn_stocks 表中全部 active 状态的A股
+    #[default]
+    CnStocks,
+    /// scanner.watchlist 中列出的股票
+    Watchlist,
+    /// index_constituents 表中 scanner.index 指数的成分股
+    Index,
+}
+
+impl ScanUniverse {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanUniverse::CnStocks => "cn_stocks",
+            ScanUniverse::Watchlist => "watchlist",
+            ScanUniverse::Index => "index",
+        }
+    }
+}
+
+/// 收盘后策略扫描配置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScannerConfig {
+    #[serde(default = "default_scanner_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub universe: ScanUniverse,
+    #[serde(default)]
+    pub watchlist: Vec<String>,
+    /// universe 为 index 时使用的指数代码
+    #[serde(default = "default_scanner_index")]
+    pub index: String,
+    /// 收盘后等待多少分钟再扫描，留出收盘行情入库的时间
+    #[serde(default = "default_scanner_delay_minutes")]
+    pub delay_minutes: i64,
+    /// 同时分析的股票数
+    #[serde(default = "default_scanner_max_concurrency")]
+    pub max_concurrency: usize,
+    /// 检查是否到达扫描时间的间隔（秒）
+    #[serde(default = "default_scanner_check_interval_secs")]
+    pub check_interval_secs: u64,
+}
+
+impl Default for ScannerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_scanner_enabled(),
+            universe: ScanUniverse::default(),
+            watchlist: Vec::new(),
+            index: default_scanner_index(),
+            delay_minutes: default_scanner_delay_minutes(),
+            max_concurrency: default_scanner_max_concurrency(),
+            check_interval_secs: default_scanner_check_interval_secs(),
+        }
+    }
+}
+
+/// 回测的资金、交易成本与离场规则
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BacktestConfig {
+    #[serde(default = "default_initial_capital")]
+    pub initial_capital: f64,
+    /// 单边滑点（%），买入价上浮、卖出价下调
+    #[serde(default = "default_slippage_percent")]
+    pub slippage_percent: f64,
+    /// 佣金费率（%），买卖双向收取
+    #[serde(default = "default_commission_percent")]
+    pub commission_percent: f64,
+    /// 单笔最低佣金
+    #[serde(default = "default_min_commission")]
+    pub min_commission: f64,
+    /// A股印花税（%），仅卖出收取
+    #[serde(default = "default_stamp_duty_percent")]
+    pub stamp_duty_percent: f64,
+    /// 止盈（%），0 表示不止盈
+    #[serde(default = "default_take_profit_percent")]
+    pub take_profit_percent: f64,
+    /// 止损（%），0 表示不止损
+    #[serde(default = "default_stop_loss_percent")]
+    pub stop_loss_percent: f64,
+    /// 最长持有交易日数，0 表示不限
+    #[serde(default = "default_max_holding_days")]
+    pub max_holding_days: usize,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            initial_capital: default_initial_capital(),
+            slippage_percent: default_slippage_percent(),
+            commission_percent: default_commission_percent(),
+            min_commission: default_min_commission(),
+            stamp_duty_percent: default_stamp_duty_percent(),
+            take_profit_percent: default_take_profit_percent(),
+            stop_loss_percent: default_stop_loss_percent(),
+            max_holding_days: default_max_holding_days(),
+        }
+    }
+}
+
+/// 技术指标参数
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndicatorConfig {
+    #[serde(default = "default_rsi_period")]
+    pub rsi_period: usize,
+    /// RSI 高于该值视为超买
+    #[serde(default = "default_rsi_overbought")]
+    pub rsi_overbought: f64,
+    /// RSI 低于该值视为超卖
+    #[serde(default = "default_rsi_oversold")]
+    pub rsi_oversold: f64,
+    #[serde(default = "default_macd_fast")]
+    pub macd_fast: usize,
+    #[serde(default = "default_macd_slow")]
+    pub macd_slow: usize,
+    #[serde(default = "default_macd_signal")]
+    pub macd_signal: usize,
+    /// 背离比较最近两段各多少根K线的高低点
+    #[serde(default = "default_macd_divergence_lookback")]
+    pub macd_divergence_lookback: usize,
+    #[serde(default = "default_boll_period")]
+    pub boll_period: usize,
+    /// 上下轨距中轨的标准差倍数
+    #[serde(default = "default_boll_std_dev")]
+    pub boll_std_dev: f64,
+    /// 带宽降到最近多少根K线的最低值时视为收口
+    #[serde(default = "default_boll_squeeze_lookback")]
+    pub boll_squeeze_lookback: usize,
+    /// 收口后5根K线内带宽扩大到收口时的多少倍视为开口
+    #[serde(default = "default_boll_expand_ratio")]
+    pub boll_expand_ratio: f64,
+    #[serde(default = "default_kdj_period")]
+    pub kdj_period: usize,
+    #[serde(default = "default_kdj_k_smooth")]
+    pub kdj_k_smooth: usize,
+    #[serde(default = "default_kdj_d_smooth")]
+    pub kdj_d_smooth: usize,
+    /// K、D 高于该值视为高位，J 超过100为超买
+    #[serde(default = "default_kdj_overbought")]
+    pub kdj_overbought: f64,
+    /// K、D 低于该值视为低位，J 低于0为超卖
+    #[serde(default = "default_kdj_oversold")]
+    pub kdj_oversold: f64,
+    /// 平均真实波幅（ATR）的周期
+    #[serde(default = "default_atr_period")]
+    pub atr_period: usize,
+    /// 量比的比较天数
+    #[serde(default = "default_volume_ratio_period")]
+    pub volume_ratio_period: usize,
+    /// 量比高于该值视为放量
+    #[serde(default = "default_volume_ratio_high")]
+    pub volume_ratio_high: f64,
+    /// 量比低于该值视为缩量
+    #[serde(default = "default_volume_ratio_low")]
+    pub volume_ratio_low: f64,
+    /// 换手率（%）高于该值视为交投活跃
+    #[serde(default = "default_turnover_high")]
+    pub turnover_high: f64,
+    /// 换手率（%）低于该值视为交投清淡
+    #[serde(default = "default_turnover_low")]
+    pub turnover_low: f64,
+}
+
+impl Default for IndicatorConfig {
+    fn default() -> Self {
+        Self {
+            rsi_period: default_rsi_period(),
+            rsi_overbought: default_rsi_overbought(),
+            rsi_oversold: default_rsi_oversold(),
+            macd_fast: default_macd_fast(),
+            macd_slow: default_macd_slow(),
+            macd_signal: default_macd_signal(),
+            macd_divergence_lookback: default_macd_divergence_lookback(),
+            boll_period: default_boll_period(),
+            boll_std_dev: default_boll_std_dev(),
+            boll_squeeze_lookback: default_boll_squeeze_lookback(),
+            boll_expand_ratio: default_boll_expand_ratio(),
+            kdj_period: default_kdj_period(),
+            kdj_k_smooth: default_kdj_k_smooth(),
+            kdj_d_smooth: default_kdj_d_smooth(),
+            kdj_overbought: default_kdj_overbought(),
+            kdj_oversold: default_kdj_oversold(),
+            atr_period: default_atr_period(),
+            volume_ratio_period: default_volume_ratio_period(),
+            volume_ratio_high: default_volume_ratio_high(),
+            volume_ratio_low: default_volume_ratio_low(),
+            turnover_high: default_turnover_high(),
+            turnover_low: default_turnover_low(),
+        }
+    }
+}
+
+fn default_rescale_alerts() -> bool {
+    true
+}
+
+fn default_rescale_check_interval_secs() -> u64 {
+    3600
+}
+
+// IndicatorConfig默认值函数
+fn default_rsi_period() -> usize {
+    14
+}
+
+fn default_rsi_overbought() -> f64 {
+    70.0
+}
+
+fn default_rsi_oversold() -> f64 {
+    30.0
+}
+
+fn default_macd_fast() -> usize {
+    12
+}
+
+fn default_macd_slow() -> usize {
+    26
+}
+
+fn default_macd_signal() -> usize {
+    9
+}
+
+fn default_macd_divergence_lookback() -> usize {
+    20
+}
+
+fn default_boll_period() -> usize {
+    20
+}
+
+fn default_boll_std_dev() -> f64 {
+    2.0
+}
+
+fn default_boll_squeeze_lookback() -> usize {
+    120
+}
+
+fn default_boll_expand_ratio() -> f64 {
+    1.5
+}
+
+fn default_kdj_period() -> usize {
+    9
+}
+
+fn default_kdj_k_smooth() -> usize {
+    3
+}
+
+fn default_kdj_d_smooth() -> usize {
+    3
+}
+
+fn default_kdj_overbought() -> f64 {
+    80.0
+}
+
+fn default_kdj_oversold() -> f64 {
+    20.0
+}
+
+fn default_atr_period() -> usize {
+    14
+}
+
+fn default_volume_ratio_period() -> usize {
+    5
+}
+
+fn default_volume_ratio_high() -> f64 {
+    2.0
+}
+
+fn default_volume_ratio_low() -> f64 {
+    0.5
+}
+
+fn default_turnover_high() -> f64 {
+    10.0
+}
+
+fn default_turnover_low() -> f64 {
+    1.0
+}
+
+// BacktestConfig默认值函数
+fn default_initial_capital() -> f64 {
+    100_000.0
+}
+
+fn default_slippage_percent() -> f64 {
+    0.1
+}
+
+fn default_commission_percent() -> f64 {
+    0.025
+}
+
+fn default_min_commission() -> f64 {
+    5.0
+}
+
+fn default_stamp_duty_percent() -> f64 {
+    0.05
+}
+
+fn default_take_profit_percent() -> f64 {
+    10.0
+}
+
+fn default_stop_loss_percent() -> f64 {
+    5.0
+}
+
+fn default_max_holding_days() -> usize {
+    10
+}
+
+// ScannerConfig默认值函数
+fn default_scanner_enabled() -> bool {
+    true
+}
+
+fn default_scanner_index() -> String {
+    "000300.SH".to_string()
+}
+
+fn default_scanner_delay_minutes() -> i64 {
+    30
+}
+
+fn default_scanner_max_concurrency() -> usize {
+    4
+}
+
+fn default_scanner_check_interval_secs() -> u64 {
+    300
+}
+
+// StrategyParams默认值函数
+fn default_board_params() -> HashMap<String, PatternParamsOverride> {
+    // 创业板、科创板涨跌幅限制20%，北交所30%
+    [("chinext", 19.8), ("star", 19.8), ("bse", 29.8)]
+        .into_iter()
+        .map(|(board, limit_up_percent)| {
+            (
+                board.to_string(),
+                PatternParamsOverride {
+                    limit_up_percent: Some(limit_up_percent),
+                    ..PatternParamsOverride::default()
+                },
+            )
+        })
+        .collect()
+}
+
+fn default_limit_up_percent() -> f64 {
+    9.8
+}
+
+fn default_limit_up_lookback_days() -> usize {
+    10
+}
+
+fn default_pullback_volume_ratio() -> f64 {
+    0.5
+}
+
+fn default_deep_shrink_ratio() -> f64 {
+    0.3
+}
+
+fn default_ma_proximity_percent() -> f64 {
+    15.0
+}
+
+fn default_volume_average_days() -> usize {
+    30
+}
+
+fn default_breakout_volume_ratio() -> f64 {
+    1.5
+}
+
+fn default_strong_volume_ratio() -> f64 {
+    2.0
+}
+
+fn default_resistance_lookback_days() -> usize {
+    60
+}
+
+fn default_breakout_percent() -> f64 {
+    2.0
+}
+
+// ScoringConfig默认值函数
+fn default_buy_score() -> f64 {
+    70.0
+}
+
+fn default_sell_score() -> f64 {
+    35.0
+}
+
+fn default_lookback_period() -> usize {
+    20
+}
+
+fn default_low_volatility_percent() -> f64 {
+    2.0
+}
+
+fn default_high_volatility_percent() -> f64 {
+    4.0
 }
 
 fn default_demo_config() -> DemoConfig {
@@ -88,6 +926,116 @@ fn default_demo_config() -> DemoConfig {
     }
 }
 
+// PriceFetcherConfig默认值函数
+fn default_cycle_timeout_secs() -> u64 {
+    25
+}
+
+// CryptoProviderConfig默认值函数
+fn default_crypto_enabled() -> bool {
+    true
+}
+
+fn default_crypto_venue() -> CryptoVenue {
+    CryptoVenue::Binance
+}
+
+fn default_crypto_update_interval_secs() -> u64 {
+    10
+}
+
+fn default_crypto_request_timeout_secs() -> u64 {
+    5
+}
+
+fn default_kline_backfill_days() -> u32 {
+    250
+}
+
+fn default_quote_cache_key_prefix() -> String {
+    "trade_alert:quote:".to_string()
+}
+
+fn default_extended_hours_enabled() -> bool {
+    true
+}
+
+fn default_intraday_enabled() -> bool {
+    true
+}
+
+fn default_intraday_retention_days() -> i64 {
+    7
+}
+
+fn default_intraday_downsample_interval_secs() -> u64 {
+    3600
+}
+
+// FxProviderConfig默认值函数
+fn default_fx_enabled() -> bool {
+    true
+}
+
+fn default_fx_pairs() -> Vec<String> {
+    vec![
+        "USDCNY".to_string(),
+        "USDHKD".to_string(),
+        "USDCNH".to_string(),
+    ]
+}
+
+fn default_fx_update_interval_secs() -> u64 {
+    3600
+}
+
+fn default_fx_max_rate_age_hours() -> i64 {
+    72
+}
+
+// ValidationConfig默认值函数
+fn default_validation_enabled() -> bool {
+    true
+}
+
+fn default_max_deviation_percent() -> f64 {
+    20.0
+}
+
+fn default_confirm_tolerance_percent() -> f64 {
+    1.0
+}
+
+// CircuitBreakerConfig默认值函数
+fn default_failure_threshold() -> u32 {
+    5
+}
+
+fn default_cooldown_secs() -> u64 {
+    60
+}
+
+fn default_health_window() -> usize {
+    50
+}
+
+// StreamConfig默认值函数
+fn default_stream_enabled() -> bool {
+    true
+}
+
+fn default_stale_after_secs() -> u64 {
+    30
+}
+
+fn default_persist_interval_secs() -> u64 {
+    60
+}
+
+fn default_reconnect_max_backoff_secs() -> u64 {
+    60
+}
+
 // EmailConfig默认值函数
 fn default_smtp_server() -> String {
     "smtp.gmail.com".to_string()
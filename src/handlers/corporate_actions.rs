@@ -0,0 +1,69 @@
+use crate::handlers::market::AppState;
+use crate::models::CreateCorporateActionRequest;
+use crate::services::CorporateActionService;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+/// 获取股票的公司行动列表API
+pub async fn list_corporate_actions(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> impl IntoResponse {
+    let service = CorporateActionService::new(state.db.pool().clone());
+    match service.list(&symbol).await {
+        Ok(actions) => Json(serde_json::json!({
+            "symbol": symbol,
+            "actions": actions
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list corporate actions for {}: {}", symbol, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to list corporate actions",
+            )
+                .into_response()
+        }
+    }
+}
+
+/// 记录公司行动API，启用自动调整时立即处理已到除权日的拆股/送转
+pub async fn create_corporate_action(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Json(request): Json<CreateCorporateActionRequest>,
+) -> impl IntoResponse {
+    let symbol = symbol.to_uppercase();
+    let service = CorporateActionService::new(state.db.pool().clone());
+    let action = match service.record(&symbol, &request).await {
+        Ok(action) => action,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response();
+        }
+    };
+
+    let mut rescaled_alerts = 0;
+    if state.corporate_actions.rescale_alerts && action.changes_share_count() {
+        match service.apply_due_rescales(&state.email_notifier).await {
+            Ok(count) => rescaled_alerts = count,
+            Err(e) => tracing::error!("Failed to rescale alerts for {}: {}", symbol, e),
+        }
+    }
+
+    (
+        StatusCode::CREATED,
+        Json(serde_json::json!({
+            "action": action,
+            "rescaled_alerts": rescaled_alerts
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1,122 @@
+use crate::handlers::market::AppState;
+use crate::models::CreateFxRateRequest;
+use crate::services::{parse_currency_pair, FX_SOURCE_MANUAL};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+/// 获取各货币对最新汇率API
+pub async fn get_fx_rates(State(state): State<AppState>) -> impl IntoResponse {
+    match state.price_service.fx_rates().latest_rates().await {
+        Ok(rates) => Json(serde_json::json!({ "rates": rates })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list FX rates: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to list FX rates").into_response()
+        }
+    }
+}
+
+/// 手工录入汇率API
+pub async fn create_fx_rate(
+    State(state): State<AppState>,
+    Json(request): Json<CreateFxRateRequest>,
+) -> impl IntoResponse {
+    match state
+        .price_service
+        .fx_rates()
+        .record(
+            &request.base_currency,
+            &request.quote_currency,
+            request.rate,
+            FX_SOURCE_MANUAL,
+            request.as_of,
+        )
+        .await
+    {
+        Ok(rate) => (StatusCode::CREATED, Json(serde_json::json!(rate))).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FxHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+/// 获取货币对汇率历史API，货币对形如 USDCNY
+pub async fn get_fx_rate_history(
+    State(state): State<AppState>,
+    Path(pair): Path<String>,
+    Query(query): Query<FxHistoryQuery>,
+) -> impl IntoResponse {
+    let Some((base, quote)) = parse_currency_pair(&pair) else {
+        return (StatusCode::BAD_REQUEST, "Invalid currency pair").into_response();
+    };
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    match state
+        .price_service
+        .fx_rates()
+        .history(&base, &quote, limit)
+        .await
+    {
+        Ok(rates) => Json(serde_json::json!({
+            "base_currency": base,
+            "quote_currency": quote,
+            "rates": rates
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get FX history for {}: {}", pair, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to get FX history",
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FxConvertQuery {
+    pub amount: f64,
+    pub from: String,
+    pub to: String,
+}
+
+/// 货币换算API
+pub async fn convert_currency(
+    State(state): State<AppState>,
+    Query(query): Query<FxConvertQuery>,
+) -> impl IntoResponse {
+    match state
+        .price_service
+        .fx_rates()
+        .convert(query.amount, &query.from, &query.to)
+        .await
+    {
+        Ok(Some(conversion)) => Json(serde_json::json!(conversion)).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("没有可用的汇率: {} → {}", query.from, query.to)
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to convert currency: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to convert currency",
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::handlers::market::AppState;
+use crate::services::{BarInterval, IntradayStore};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{NaiveDateTime, Utc};
+use serde::Deserialize;
+
+/// 单次查询最多返回的K线数量
+const MAX_BARS: i64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+pub struct PriceBarsQuery {
+    /// 1m、5m、15m、1h、4h、1d 等能整除一天的周期，默认 1m
+    pub interval: Option<String>,
+    /// 起始时间（UTC），默认为结束时间前一天
+    pub from: Option<NaiveDateTime>,
+    /// 结束时间（UTC），默认为当前时间
+    pub to: Option<NaiveDateTime>,
+}
+
+/// 获取日内K线API
+pub async fn get_price_bars(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Query(query): Query<PriceBarsQuery>,
+) -> impl IntoResponse {
+    let interval = match query
+        .interval
+        .as_deref()
+        .map_or(Ok(BarInterval::MINUTE), str::parse)
+    {
+        Ok(interval) => interval,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    };
+    let to = query.to.unwrap_or_else(|| Utc::now().naive_utc());
+    let from = query.from.unwrap_or(to - chrono::Duration::days(1));
+    if from >= to || (to - from).num_seconds() / interval.seconds() > MAX_BARS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("查询区间无效或超过 {} 根K线", MAX_BARS)
+            })),
+        )
+            .into_response();
+    }
+
+    match IntradayStore::new(state.db.pool().clone())
+        .bars(&symbol, interval, from, to)
+        .await
+    {
+        Ok(bars) => Json(serde_json::json!({
+            "symbol": symbol,
+            "interval": interval,
+            "from": from,
+            "to": to,
+            "bars": bars
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get price bars for {}: {}", symbol, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to get price bars",
+            )
+                .into_response()
+        }
+    }
+}
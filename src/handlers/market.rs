@@ -1,6 +1,11 @@
+pub use crate::models::Market;
 use crate::{
+    config::{CorporateActionsConfig, StrategyConfig},
     models::Alert,
-    services::{Database, EmailNotifier},
+    services::{
+        Database, EmailNotifier, PriceService, QuoteService, SessionState, StrategyParamStore,
+        StrategyScanner, TradingCalendars,
+    },
 };
 use askama::Template;
 use axum::{
@@ -8,6 +13,7 @@ use axum::{
     http::StatusCode,
     response::{Html, IntoResponse, Json},
 };
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
 use serde_json;
 use std::sync::Arc;
 
@@ -16,61 +22,13 @@ use std::sync::Arc;
 pub struct AppState {
     pub db: Arc<Database>,
     pub email_notifier: Arc<EmailNotifier>,
-}
-
-/// 市场类型枚举
-#[derive(Debug, Clone, PartialEq)]
-pub enum Market {
-    US,     // 美股
-    CN,     // A股
-    Crypto, // 加密货币
-}
-
-impl std::str::FromStr for Market {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "us" => Ok(Market::US),
-            "cn" => Ok(Market::CN),
-            "crypto" => Ok(Market::Crypto),
-            _ => Err(()),
-        }
-    }
-}
-
-impl Market {
-    pub fn to_str(&self) -> &'static str {
-        match self {
-            Market::US => "us",
-            Market::CN => "cn",
-            Market::Crypto => "crypto",
-        }
-    }
-
-    pub fn display_name(&self) -> &'static str {
-        match self {
-            Market::US => "美股",
-            Market::CN => "A股",
-            Market::Crypto => "加密货币",
-        }
-    }
-
-    pub fn currency_symbol(&self) -> &'static str {
-        match self {
-            Market::US => "$",
-            Market::CN => "¥",
-            Market::Crypto => "",
-        }
-    }
-
-    pub fn flag_emoji(&self) -> &'static str {
-        match self {
-            Market::US => "🇺🇸",
-            Market::CN => "🇨🇳",
-            Market::Crypto => "₿",
-        }
-    }
+    pub price_service: Arc<PriceService>,
+    pub calendars: Arc<TradingCalendars>,
+    pub corporate_actions: Arc<CorporateActionsConfig>,
+    pub quotes: Arc<QuoteService>,
+    pub strategy: Arc<StrategyConfig>,
+    pub scanner: Arc<StrategyScanner>,
+    pub strategy_params: Arc<StrategyParamStore>,
 }
 
 /// 首页导航中心模板
@@ -89,7 +47,8 @@ pub struct MarketSummary {
     pub market: Market,
     pub active_count: i32,
     pub status: String,
-    pub trend: f64, // 整体趋势百分比
+    pub trend: f64,           // 整体趋势百分比
+    pub source_healthy: bool, // 是否至少有一个可用数据源
 }
 
 /// 策略信息
@@ -166,10 +125,10 @@ async fn get_real_market_summaries(state: &AppState) -> Result<Vec<MarketSummary
     let mut summaries = Vec::new();
 
     // 查询各市场的活跃预警数量
-    for market in [Market::US, Market::CN, Market::Crypto] {
+    for market in Market::ALL {
         let count: i64 = match market {
             Market::US => {
-                // 美股：不包含 .SZ/.SS/.SH 后缀的股票
+                // 美股：不包含 A股、港股、期货、外汇后缀的代码
                 sqlx::query_scalar(
                     r#"
                     SELECT COUNT(*) 
@@ -178,6 +137,11 @@ async fn get_real_market_summaries(state: &AppState) -> Result<Vec<MarketSummary
                     AND symbol NOT LIKE '%.SZ'
                     AND symbol NOT LIKE '%.SS' 
                     AND symbol NOT LIKE '%.SH'
+                    AND symbol NOT LIKE '%.BJ'
+                    AND symbol NOT LIKE '%.HK'
+                    AND symbol NOT LIKE '%.SHF'
+                    AND symbol NOT LIKE '%=F'
+                    AND symbol NOT LIKE '%=X'
                     AND symbol NOT LIKE 'BTC%'
                     AND symbol NOT LIKE 'ETH%'
                     "#,
@@ -186,18 +150,45 @@ async fn get_real_market_summaries(state: &AppState) -> Result<Vec<MarketSummary
                 .await?
             }
             Market::CN => {
-                // A股：以 .SZ/.SS/.SH 结尾的股票
+                // A股：以 .SZ/.SS/.SH/.BJ 结尾的股票
                 sqlx::query_scalar(
                     r#"
                     SELECT COUNT(*) 
                     FROM alerts 
                     WHERE status = 'active' 
-                    AND (symbol LIKE '%.SZ' OR symbol LIKE '%.SS' OR symbol LIKE '%.SH')
+                    AND (symbol LIKE '%.SZ' OR symbol LIKE '%.SS' OR symbol LIKE '%.SH' OR symbol LIKE '%.BJ')
                     "#,
                 )
                 .fetch_one(state.db.pool())
                 .await?
             }
+            Market::HK => {
+                // 港股：以 .HK 结尾的股票
+                sqlx::query_scalar(
+                    r#"
+                    SELECT COUNT(*) 
+                    FROM alerts 
+                    WHERE status = 'active' 
+                    AND symbol LIKE '%.HK'
+                    "#,
+                )
+                .fetch_one(state.db.pool())
+                .await?
+            }
+            Market::Futures | Market::CnFutures | Market::FX => {
+                // 期货、外汇：按代码后缀匹配
+                sqlx::query_scalar(
+                    r#"
+                    SELECT COUNT(*) 
+                    FROM alerts 
+                    WHERE status = 'active' 
+                    AND symbol LIKE '%' || ?
+                    "#,
+                )
+                .bind(market_suffix(&market))
+                .fetch_one(state.db.pool())
+                .await?
+            }
             Market::Crypto => {
                 // 加密货币：更广泛的匹配模式
                 sqlx::query_scalar(
@@ -219,10 +210,11 @@ async fn get_real_market_summaries(state: &AppState) -> Result<Vec<MarketSummary
         };
 
         summaries.push(MarketSummary {
-            market: market.clone(),
+            market,
             active_count: count as i32,
-            status: get_market_status(&market),
+            status: get_market_status(&state.calendars, &market),
             trend: calculate_market_trend(&market).await,
+            source_healthy: state.price_service.market_has_healthy_source(&market),
         });
     }
 
@@ -238,26 +230,25 @@ async fn get_fallback_market_summaries(state: &AppState) -> Vec<MarketSummary> {
             .await
             .unwrap_or(0);
 
-    vec![
-        MarketSummary {
-            market: Market::US,
-            active_count: (total_active as f32 * 0.5) as i32,
-            status: get_market_status(&Market::US),
-            trend: 0.0,
-        },
-        MarketSummary {
-            market: Market::CN,
-            active_count: (total_active as f32 * 0.3) as i32,
-            status: get_market_status(&Market::CN),
-            trend: 0.0,
-        },
-        MarketSummary {
-            market: Market::Crypto,
-            active_count: (total_active as f32 * 0.2) as i32,
-            status: get_market_status(&Market::Crypto),
-            trend: 0.0,
-        },
-    ]
+    // 按大致比例分摊到各市场
+    Market::ALL
+        .into_iter()
+        .map(|market| {
+            let share = match market {
+                Market::US => 0.4,
+                Market::CN => 0.2,
+                Market::HK | Market::Futures | Market::Crypto => 0.1,
+                Market::CnFutures | Market::FX => 0.05,
+            };
+            MarketSummary {
+                market,
+                active_count: (total_active as f32 * share) as i32,
+                status: get_market_status(&state.calendars, &market),
+                trend: 0.0,
+                source_healthy: state.price_service.market_has_healthy_source(&market),
+            }
+        })
+        .collect()
 }
 
 /// 获取紧急预警
@@ -315,54 +306,18 @@ async fn get_breakout_signals_count(state: &AppState) -> i32 {
 }
 
 /// 获取市场状态
-fn get_market_status(market: &Market) -> String {
-    use chrono::{Datelike, Timelike, Utc, Weekday};
-    let now = Utc::now();
-
-    match market {
-        Market::US => {
-            // 美股交易时间：北京时间 22:30-05:00 (夏令时) 或 23:30-06:00 (冬令时)
-            // 简化处理，假设夏令时
-            let beijing_time = now + chrono::Duration::hours(8);
-            let weekday = beijing_time.weekday();
-
-            // 周末休市
-            if weekday == Weekday::Sat || weekday == Weekday::Sun {
-                return "休市中".to_string();
-            }
-
-            let hour = beijing_time.hour();
-            if (6..22).contains(&hour) {
-                "开盘中".to_string()
-            } else {
-                "休市中".to_string()
-            }
-        }
-        Market::CN => {
-            // A股交易时间：工作日 9:30-11:30, 13:00-15:00
-            let beijing_time = now + chrono::Duration::hours(8);
-            let weekday = beijing_time.weekday();
-
-            // 周末休市
-            if weekday == Weekday::Sat || weekday == Weekday::Sun {
-                return "休市中".to_string();
-            }
-
-            let hour = beijing_time.hour();
-            let minute = beijing_time.minute();
-
-            let is_morning_session =
-                (hour == 9 && minute >= 30) || hour == 10 || (hour == 11 && minute < 30);
-
-            let is_afternoon_session = (hour == 13) || (hour == 14) || (hour == 15 && minute == 0);
-
-            if is_morning_session || is_afternoon_session {
-                "开盘中".to_string()
-            } else {
-                "休市中".to_string()
-            }
-        }
-        Market::Crypto => "24h交易".to_string(),
+fn get_market_status(calendars: &TradingCalendars, market: &Market) -> String {
+    let status = calendars.for_market(market).status(Utc::now());
+
+    match status.state {
+        SessionState::AlwaysOpen => "24h交易".to_string(),
+        SessionState::Open => "开盘中".to_string(),
+        SessionState::Break if matches!(market, Market::CN | Market::HK) => "午间休市".to_string(),
+        SessionState::Break => "盘中休市".to_string(),
+        SessionState::Closed => match status.holiday {
+            Some(holiday) => format!("休市中（{}）", holiday),
+            None => "休市中".to_string(),
+        },
     }
 }
 
@@ -418,10 +373,10 @@ pub async fn market_handler(
     };
 
     let template = MarketTemplate {
-        market: market.clone(),
+        market,
         alerts,
-        market_status: get_market_status(&market),
-        next_event: calculate_next_market_event(&market),
+        market_status: get_market_status(&state.calendars, &market),
+        next_event: calculate_next_market_event(&state.calendars, &market),
     };
 
     match template.render() {
@@ -439,7 +394,7 @@ async fn get_market_alerts(state: &AppState, market: &Market) -> Result<Vec<Aler
 
     let alerts = match market {
         Market::US => {
-            // 美股：不包含 .SZ/.SS/.SH 后缀的股票，且不包含加密货币
+            // 美股：不包含 A股、港股、期货、外汇后缀的代码，且不包含加密货币
             let alerts = sqlx::query_as::<_, Alert>(
                 r#"
                 SELECT id, symbol, condition, price, status, created_at, updated_at, triggered_at, notification_email
@@ -448,6 +403,11 @@ async fn get_market_alerts(state: &AppState, market: &Market) -> Result<Vec<Aler
                 AND symbol NOT LIKE '%.SZ'
                 AND symbol NOT LIKE '%.SS' 
                 AND symbol NOT LIKE '%.SH'
+                AND symbol NOT LIKE '%.BJ'
+                AND symbol NOT LIKE '%.HK'
+                AND symbol NOT LIKE '%.SHF'
+                AND symbol NOT LIKE '%=F'
+                AND symbol NOT LIKE '%=X'
                 AND symbol NOT LIKE 'BTC%'
                 AND symbol NOT LIKE 'ETH%'
                 AND symbol NOT LIKE 'USDT%'
@@ -469,13 +429,13 @@ async fn get_market_alerts(state: &AppState, market: &Market) -> Result<Vec<Aler
             alerts
         }
         Market::CN => {
-            // A股：以 .SZ/.SS/.SH 结尾的股票
+            // A股：以 .SZ/.SS/.SH/.BJ 结尾的股票
             let alerts = sqlx::query_as::<_, Alert>(
                 r#"
                 SELECT id, symbol, condition, price, status, created_at, updated_at, triggered_at, notification_email
                 FROM alerts 
                 WHERE status = 'active' 
-                AND (symbol LIKE '%.SZ' OR symbol LIKE '%.SS' OR symbol LIKE '%.SH')
+                AND (symbol LIKE '%.SZ' OR symbol LIKE '%.SS' OR symbol LIKE '%.SH' OR symbol LIKE '%.BJ')
                 ORDER BY created_at DESC
                 "#
             )
@@ -485,6 +445,45 @@ async fn get_market_alerts(state: &AppState, market: &Market) -> Result<Vec<Aler
             tracing::info!("A股市场查询到 {} 个活跃警报", alerts.len());
             alerts
         }
+        Market::HK => {
+            // 港股：以 .HK 结尾的股票
+            let alerts = sqlx::query_as::<_, Alert>(
+                r#"
+                SELECT id, symbol, condition, price, status, created_at, updated_at, triggered_at, notification_email
+                FROM alerts 
+                WHERE status = 'active' 
+                AND symbol LIKE '%.HK'
+                ORDER BY created_at DESC
+                "#
+            )
+            .fetch_all(state.db.pool())
+            .await?;
+
+            tracing::info!("港股市场查询到 {} 个活跃警报", alerts.len());
+            alerts
+        }
+        Market::Futures | Market::CnFutures | Market::FX => {
+            // 期货、外汇：按代码后缀匹配
+            let alerts = sqlx::query_as::<_, Alert>(
+                r#"
+                SELECT id, symbol, condition, price, status, created_at, updated_at, triggered_at, notification_email
+                FROM alerts 
+                WHERE status = 'active' 
+                AND symbol LIKE '%' || ?
+                ORDER BY created_at DESC
+                "#
+            )
+            .bind(market_suffix(market))
+            .fetch_all(state.db.pool())
+            .await?;
+
+            tracing::info!(
+                "{}市场查询到 {} 个活跃警报",
+                market.display_name(),
+                alerts.len()
+            );
+            alerts
+        }
         Market::Crypto => {
             // 加密货币：更广泛的匹配模式
             let alerts = sqlx::query_as::<_, Alert>(
@@ -512,46 +511,50 @@ async fn get_market_alerts(state: &AppState, market: &Market) -> Result<Vec<Aler
     Ok(alerts)
 }
 
-/// 计算下次市场事件
-fn calculate_next_market_event(market: &Market) -> String {
-    use chrono::{Datelike, Timelike, Utc, Weekday};
+/// 期货、外汇代码的后缀
+fn market_suffix(market: &Market) -> &'static str {
+    match market {
+        Market::Futures => "=F",
+        Market::CnFutures => ".SHF",
+        Market::FX => "=X",
+        _ => "",
+    }
+}
+
+/// 计算下次市场事件（北京时间）
+fn calculate_next_market_event(calendars: &TradingCalendars, market: &Market) -> String {
     let now = Utc::now();
-    let beijing_time = now + chrono::Duration::hours(8);
+    let status = calendars.for_market(market).status(now);
 
-    match market {
-        Market::US => {
-            let weekday = beijing_time.weekday();
-            if weekday == Weekday::Sat || weekday == Weekday::Sun {
-                "周一22:30开盘".to_string()
-            } else {
-                let hour = beijing_time.hour();
-                if !(6..22).contains(&hour) {
-                    "6小时后收盘".to_string()
-                } else {
-                    "今晚22:30开盘".to_string()
-                }
-            }
-        }
-        Market::CN => {
-            let weekday = beijing_time.weekday();
-            if weekday == Weekday::Sat || weekday == Weekday::Sun {
-                "周一9:30开盘".to_string()
-            } else {
-                let hour = beijing_time.hour();
-                let minute = beijing_time.minute();
-
-                if hour < 9 || (hour == 9 && minute < 30) {
-                    "今日9:30开盘".to_string()
-                } else if hour >= 15 {
-                    "明日9:30开盘".to_string()
-                } else if (11..13).contains(&hour) {
-                    "13:00开盘".to_string()
-                } else {
-                    "15:00收盘".to_string()
-                }
-            }
+    match status.state {
+        SessionState::AlwaysOpen => "持续交易中".to_string(),
+        SessionState::Open => match status.next_close {
+            Some(close) => format!("{}收盘", format_beijing_time(now, close)),
+            None => "交易中".to_string(),
+        },
+        SessionState::Break | SessionState::Closed => match status.next_open {
+            Some(open) => format!("{}开盘", format_beijing_time(now, open)),
+            None => "开盘时间待定".to_string(),
+        },
+    }
+}
+
+/// 以北京时间格式化事件时间，如“今日9:30”“明日22:30”“周一9:30”“10月08日 9:30”
+fn format_beijing_time(now: DateTime<Utc>, at: DateTime<Utc>) -> String {
+    let beijing = FixedOffset::east_opt(8 * 3600).expect("valid offset");
+    let today = now.with_timezone(&beijing).date_naive();
+    let local = at.with_timezone(&beijing);
+    let time = local.format("%-H:%M");
+
+    match (local.date_naive() - today).num_days() {
+        0 => format!("今日{}", time),
+        1 => format!("明日{}", time),
+        2..=6 => {
+            let weekday = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"]
+                [local.weekday().num_days_from_monday() as usize];
+            format!("{}{}", weekday, time)
         }
-        Market::Crypto => "持续交易中".to_string(),
+        _ => format!("{} {}", local.format("%m月%d日"), time),
     }
 }
 
@@ -560,25 +563,18 @@ pub async fn get_stock_price(
     Path(symbol): Path<String>,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
-    // 从price_history表查询最新价格
-    let price_result = sqlx::query_scalar::<_, f64>(
-        r#"
-        SELECT close_price
-        FROM price_history 
-        WHERE symbol = ?
-        ORDER BY date DESC, created_at DESC
-        LIMIT 1
-        "#,
-    )
-    .bind(&symbol)
-    .fetch_optional(state.db.pool())
-    .await;
+    // 经统一行情服务获取，实时获取失败时使用最近保存的行情并标记 stale
+    let price_result = state.quotes.get_quote_or_stored(&symbol).await;
 
     match price_result {
-        Ok(Some(price)) => {
+        Ok(Some(quote)) => {
             let price_info = serde_json::json!({
                 "symbol": symbol,
-                "price": price,
+                "price": quote.price,
+                "source": quote.source,
+                "timestamp": quote.timestamp,
+                "data_quality": quote.data_quality,
+                "stale": quote.stale,
                 "status": "success"
             });
             Json(price_info).into_response()
@@ -1,7 +1,11 @@
 // handlers/mod.rs - 路由处理器模块
 // 为多市场UI优化准备的处理器模块
 
+pub mod corporate_actions;
+pub mod fx;
+pub mod intraday;
 pub mod market;
+pub mod providers;
 pub mod strategy;
 
 pub use strategy::*;
@@ -0,0 +1,64 @@
+use crate::handlers::market::{AppState, Market};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+/// 获取各数据源令牌桶状态API
+pub async fn get_rate_limits(State(state): State<AppState>) -> impl IntoResponse {
+    Json(serde_json::json!({
+        "providers": state.price_service.rate_limit_snapshot()
+    }))
+}
+
+/// 获取各数据源健康状况与熔断状态API
+pub async fn get_provider_health(State(state): State<AppState>) -> impl IntoResponse {
+    let markets: Vec<serde_json::Value> = Market::ALL
+        .iter()
+        .map(|market| {
+            serde_json::json!({
+                "market": market.to_str(),
+                "providers": state.price_service.market_providers(market),
+                "healthy": state.price_service.market_has_healthy_source(market),
+            })
+        })
+        .collect();
+
+    Json(serde_json::json!({
+        "providers": state.price_service.provider_health(),
+        "markets": markets,
+        "stream": state.price_service.stream_status().await
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuarantineQuery {
+    pub symbol: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// 获取被隔离的异常行情API
+pub async fn get_quarantined_prices(
+    State(state): State<AppState>,
+    Query(query): Query<QuarantineQuery>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    match state
+        .db
+        .list_quarantined_prices(query.symbol.as_deref(), limit)
+        .await
+    {
+        Ok(rows) => Json(serde_json::json!({ "quarantined": rows })).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to list quarantined prices: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to list quarantined prices",
+            )
+                .into_response()
+        }
+    }
+}
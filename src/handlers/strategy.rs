@@ -1,11 +1,19 @@
+use crate::config::PatternParamsOverride;
 use crate::handlers::market::AppState;
-use crate::services::{StrategyAnalyzer, StrategySignal as AnalyzerSignal};
+use crate::models::{AlertCondition, AlertIndicator, Market};
+use crate::services::{
+    AlertRule, BacktestStrategy, Backtester, BarInterval, CompositeScore, ParamScope,
+    PriceAdjustment, Recommendation, ScanRun, StoredSignal, StrategyAnalyzer,
+    StrategySignal as AnalyzerSignal,
+};
 use askama::Template;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{Html, IntoResponse, Json},
 };
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
 
 /// 策略信号数据
 #[derive(Debug)]
@@ -18,18 +26,76 @@ pub struct StrategySignal {
     pub generated_at: String,
 }
 
+/// 收盘扫描记录摘要
+#[derive(Debug)]
+pub struct ScanRunSummary {
+    pub market: String,
+    pub trigger: String,
+    pub session_close: String,
+    pub status: String,
+    pub symbols_total: i64,
+    pub symbols_failed: i64,
+    pub signals_new: i64,
+    pub signals_duplicate: i64,
+    pub signals_expired: i64,
+    pub symbols_scored: i64,
+    pub started_at: String,
+    pub error: String,
+}
+
+impl From<ScanRun> for ScanRunSummary {
+    fn from(run: ScanRun) -> Self {
+        let market = run
+            .market
+            .parse::<Market>()
+            .map(|m| m.display_name().to_string())
+            .unwrap_or(run.market);
+        Self {
+            market,
+            trigger: if run.trigger_type == "manual" {
+                "手动"
+            } else {
+                "收盘后"
+            }
+            .to_string(),
+            session_close: run
+                .session_close
+                .map(|t| t.format("%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            status: match run.status.as_str() {
+                "completed" => "✅ 完成",
+                "failed" => "❌ 失败",
+                _ => "⏳ 运行中",
+            }
+            .to_string(),
+            symbols_total: run.symbols_total,
+            symbols_failed: run.symbols_failed,
+            signals_new: run.signals_new,
+            signals_duplicate: run.signals_duplicate,
+            signals_expired: run.signals_expired,
+            symbols_scored: run.symbols_scored,
+            started_at: run.started_at.format("%m-%d %H:%M").to_string(),
+            error: run.error.unwrap_or_default(),
+        }
+    }
+}
+
 /// 策略监控页面模板
 #[derive(Template)]
 #[template(path = "strategy.html")]
 pub struct StrategyTemplate {
     pub cn_signals: Vec<StrategySignal>,
     pub global_signals: Vec<StrategySignal>,
+    pub scan_runs: Vec<ScanRunSummary>,
 }
 
 /// 策略监控页面处理器
 pub async fn strategy_handler(State(app_state): State<AppState>) -> impl IntoResponse {
     // 使用真实的策略分析数据
-    let analyzer = StrategyAnalyzer::new(app_state.db.pool().clone());
+    let analyzer = StrategyAnalyzer::new(
+        app_state.db.pool().clone(),
+        app_state.strategy.indicators.clone(),
+    );
 
     // 获取最近的策略信号
     let recent_signals = analyzer.get_recent_signals(20).await.unwrap_or_default();
@@ -38,8 +104,8 @@ pub async fn strategy_handler(State(app_state): State<AppState>) -> impl IntoRes
     let mut cn_signals = Vec::new();
     let mut global_signals = Vec::new();
 
-    for (symbol, signal) in recent_signals {
-        let template_signal = convert_analyzer_signal_to_template(symbol, signal);
+    for stored in recent_signals {
+        let template_signal = convert_analyzer_signal_to_template(stored.symbol, stored.signal);
 
         // 根据股票代码判断市场类型
         if is_cn_stock(&template_signal.symbol) {
@@ -82,9 +148,19 @@ pub async fn strategy_handler(State(app_state): State<AppState>) -> impl IntoRes
         }];
     }
 
+    let scan_runs = app_state
+        .scanner
+        .recent_runs(10)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(ScanRunSummary::from)
+        .collect();
+
     let template = StrategyTemplate {
         cn_signals,
         global_signals,
+        scan_runs,
     };
 
     match template.render() {
@@ -96,14 +172,26 @@ pub async fn strategy_handler(State(app_state): State<AppState>) -> impl IntoRes
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AnalyzeQuery {
+    /// forward（默认）为前复权价格，raw 为原始价格
+    #[serde(default)]
+    pub adjust: PriceAdjustment,
+}
+
 /// 分析指定股票的策略信号 API
 pub async fn analyze_symbol_strategy(
     Path(symbol): Path<String>,
+    Query(query): Query<AnalyzeQuery>,
     State(app_state): State<AppState>,
 ) -> Result<Json<Vec<AnalyzerSignal>>, StatusCode> {
-    let analyzer = StrategyAnalyzer::new(app_state.db.pool().clone());
+    let analyzer = StrategyAnalyzer::new(
+        app_state.db.pool().clone(),
+        app_state.strategy.indicators.clone(),
+    );
 
-    match analyzer.analyze_symbol(&symbol).await {
+    let analyzer = analyzer.with_params(app_state.strategy_params.snapshot());
+    match analyzer.analyze_symbol_with(&symbol, query.adjust).await {
         Ok(signals) => Ok(Json(signals)),
         Err(e) => {
             tracing::error!("Failed to analyze symbol {}: {}", symbol, e);
@@ -112,11 +200,184 @@ pub async fn analyze_symbol_strategy(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct IndicatorQuery {
+    #[serde(default)]
+    pub adjust: PriceAdjustment,
+    /// 返回最近多少个交易日，指定 `interval` 时为K线根数
+    #[serde(default = "default_indicator_days")]
+    pub days: i32,
+    /// 日内K线周期（如 5m、1h），不填为日线
+    pub interval: Option<BarInterval>,
+}
+
+fn default_indicator_days() -> i32 {
+    120
+}
+
+/// 单只股票的技术指标值 API，A股附带换手率
+pub async fn get_indicators(
+    Path(symbol): Path<String>,
+    Query(query): Query<IndicatorQuery>,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    let params = app_state.strategy.indicators.clone();
+    let analyzer = StrategyAnalyzer::new(app_state.db.pool().clone(), params.clone());
+    let days = query.days.clamp(1, 1000);
+
+    if let Some(interval) = query.interval {
+        return match analyzer
+            .get_bar_indicator_values(&symbol, interval, days)
+            .await
+        {
+            Ok(values) => Json(serde_json::json!({
+                "symbol": symbol,
+                "interval": interval,
+                "params": params,
+                "values": values
+            }))
+            .into_response(),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to calculate {} indicators for {}: {}",
+                    interval,
+                    symbol,
+                    e
+                );
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to calculate indicators",
+                )
+                    .into_response()
+            }
+        };
+    }
+
+    let float_shares = app_state.price_service.float_shares(&symbol).await;
+    match analyzer
+        .get_indicator_values(&symbol, days, query.adjust, float_shares)
+        .await
+    {
+        Ok(mut values) => {
+            if let Some(latest) = values.last_mut() {
+                app_state
+                    .price_service
+                    .adjust_intraday_volume_ratio(&symbol, latest);
+            }
+            Json(serde_json::json!({
+                "symbol": symbol,
+                "adjust": query.adjust,
+                "float_shares": float_shares,
+                "params": params,
+                "values": values
+            }))
+            .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to calculate indicators for {}: {}", symbol, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to calculate indicators",
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BacktestQuery {
+    pub strategy: BacktestStrategy,
+    #[serde(default)]
+    pub adjust: PriceAdjustment,
+    /// 回放最近多少个交易日
+    #[serde(default = "default_backtest_days")]
+    pub days: i32,
+    /// 预警规则回测：指标（不填为收盘价）、条件与阈值
+    pub indicator: Option<AlertIndicator>,
+    pub condition: Option<AlertCondition>,
+    pub threshold: Option<f64>,
+    // 以下参数覆盖 [strategy.backtest] 中的配置
+    pub initial_capital: Option<f64>,
+    pub slippage_percent: Option<f64>,
+    pub commission_percent: Option<f64>,
+    pub min_commission: Option<f64>,
+    pub stamp_duty_percent: Option<f64>,
+    pub take_profit_percent: Option<f64>,
+    pub stop_loss_percent: Option<f64>,
+    pub max_holding_days: Option<usize>,
+}
+
+fn default_backtest_days() -> i32 {
+    750
+}
+
+/// 策略/预警规则回测 API
+pub async fn run_backtest(
+    Path(symbol): Path<String>,
+    Query(query): Query<BacktestQuery>,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    let mut config = app_state.strategy.backtest.clone();
+    config.initial_capital = query.initial_capital.unwrap_or(config.initial_capital);
+    config.slippage_percent = query.slippage_percent.unwrap_or(config.slippage_percent);
+    config.commission_percent = query
+        .commission_percent
+        .unwrap_or(config.commission_percent);
+    config.min_commission = query.min_commission.unwrap_or(config.min_commission);
+    config.stamp_duty_percent = query
+        .stamp_duty_percent
+        .unwrap_or(config.stamp_duty_percent);
+    config.take_profit_percent = query
+        .take_profit_percent
+        .unwrap_or(config.take_profit_percent);
+    config.stop_loss_percent = query.stop_loss_percent.unwrap_or(config.stop_loss_percent);
+    config.max_holding_days = query.max_holding_days.unwrap_or(config.max_holding_days);
+
+    if config.initial_capital <= 0.0 {
+        return (StatusCode::BAD_REQUEST, "initial_capital must be positive").into_response();
+    }
+    let rule = match (query.condition, query.threshold) {
+        (Some(condition), Some(threshold)) => Some(AlertRule {
+            indicator: query.indicator,
+            condition,
+            threshold,
+        }),
+        _ if query.strategy == BacktestStrategy::Alert => {
+            return (
+                StatusCode::BAD_REQUEST,
+                "alert strategy requires condition and threshold",
+            )
+                .into_response();
+        }
+        _ => None,
+    };
+
+    let analyzer = StrategyAnalyzer::new(
+        app_state.db.pool().clone(),
+        app_state.strategy.indicators.clone(),
+    );
+    let analyzer = analyzer.with_params(app_state.strategy_params.snapshot());
+    let days = query.days.clamp(1, 5000);
+    match Backtester::new(analyzer, config)
+        .run(&symbol, query.strategy, rule, days, query.adjust)
+        .await
+    {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to backtest {}: {}", symbol, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to run backtest").into_response()
+        }
+    }
+}
+
 /// 获取最新策略信号 API
 pub async fn get_strategy_signals(
     State(app_state): State<AppState>,
-) -> Result<Json<Vec<(String, AnalyzerSignal)>>, StatusCode> {
-    let analyzer = StrategyAnalyzer::new(app_state.db.pool().clone());
+) -> Result<Json<Vec<StoredSignal>>, StatusCode> {
+    let analyzer = StrategyAnalyzer::new(
+        app_state.db.pool().clone(),
+        app_state.strategy.indicators.clone(),
+    );
 
     match analyzer.get_recent_signals(50).await {
         Ok(signals) => Ok(Json(signals)),
@@ -127,6 +388,244 @@ pub async fn get_strategy_signals(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ScanRunsQuery {
+    #[serde(default = "default_scan_runs_limit")]
+    pub limit: i64,
+}
+
+fn default_scan_runs_limit() -> i64 {
+    20
+}
+
+/// 收盘扫描记录 API
+pub async fn get_scan_runs(
+    Query(query): Query<ScanRunsQuery>,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    match app_state
+        .scanner
+        .recent_runs(query.limit.clamp(1, 200))
+        .await
+    {
+        Ok(runs) => Json(runs).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get scan runs: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to get scan runs").into_response()
+        }
+    }
+}
+
+/// 手动触发指定市场的策略扫描 API，扫描在后台运行
+pub async fn trigger_scan(
+    Path(market): Path<String>,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    let market = match market.parse::<Market>() {
+        Ok(m) => m,
+        Err(_) => return (StatusCode::NOT_FOUND, "Market not found").into_response(),
+    };
+
+    match app_state.scanner.clone().start_manual_scan(market).await {
+        Ok(Some(run_id)) => (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({
+                "run_id": run_id,
+                "market": market.to_str(),
+                "universe": app_state.scanner.config().universe.as_str()
+            })),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::BAD_REQUEST,
+            "No symbols of this market in the scan universe",
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to start scan for {:?}: {}", market, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to start scan").into_response()
+        }
+    }
+}
+
+/// 指数成分股 API
+pub async fn get_index_constituents(
+    Path(index): Path<String>,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    match app_state.scanner.index_constituents(&index).await {
+        Ok(symbols) => Json(serde_json::json!({
+            "index": index.to_uppercase(),
+            "symbols": symbols
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to get constituents of {}: {}", index, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to get index constituents",
+            )
+                .into_response()
+        }
+    }
+}
+
+/// 整体替换指数成分股 API
+pub async fn set_index_constituents(
+    Path(index): Path<String>,
+    State(app_state): State<AppState>,
+    Json(symbols): Json<Vec<String>>,
+) -> impl IntoResponse {
+    match app_state
+        .scanner
+        .set_index_constituents(&index, &symbols)
+        .await
+    {
+        Ok(count) => Json(serde_json::json!({
+            "index": index.to_uppercase(),
+            "count": count
+        }))
+        .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to set constituents of {}: {}", index, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to set index constituents",
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RankingQuery {
+    /// 评分日期，不填为每只股票最新一次评分
+    pub date: Option<NaiveDate>,
+    pub market: Option<String>,
+    pub recommendation: Option<Recommendation>,
+    #[serde(default = "default_ranking_limit")]
+    pub limit: usize,
+}
+
+fn default_ranking_limit() -> usize {
+    50
+}
+
+/// 排名条目：综合评分加上操作建议的中文名称
+#[derive(Debug, Serialize)]
+struct Ranking {
+    #[serde(flatten)]
+    score: CompositeScore,
+    recommendation_label: &'static str,
+}
+
+/// 按综合评分排名 API
+pub async fn get_rankings(
+    Query(query): Query<RankingQuery>,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    let market = match query.market.as_deref().map(str::parse::<Market>) {
+        None => None,
+        Some(Ok(m)) => Some(m),
+        Some(Err(_)) => return (StatusCode::NOT_FOUND, "Market not found").into_response(),
+    };
+    let analyzer = StrategyAnalyzer::new(
+        app_state.db.pool().clone(),
+        app_state.strategy.indicators.clone(),
+    );
+
+    match analyzer
+        .ranked_analyses(query.date, query.recommendation)
+        .await
+    {
+        Ok(scores) => {
+            let rankings: Vec<_> = scores
+                .into_iter()
+                .filter(|score| market.is_none_or(|m| Market::from_symbol(&score.symbol) == m))
+                .take(query.limit.clamp(1, 500))
+                .map(|score| Ranking {
+                    recommendation_label: score.recommendation.label(),
+                    score,
+                })
+                .collect();
+            Json(rankings).into_response()
+        }
+        Err(e) => {
+            tracing::error!("Failed to rank strategy analyses: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to get rankings").into_response()
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StrategyParamsQuery {
+    /// 指定后同时返回该股票生效的参数组
+    pub symbol: Option<String>,
+}
+
+/// 形态策略参数 API：默认值与各市场、板块的覆盖
+pub async fn get_strategy_params(
+    Query(query): Query<StrategyParamsQuery>,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    let params = app_state.strategy_params.snapshot();
+    let resolved = query.symbol.as_deref().map(|symbol| params.resolve(symbol));
+    Json(serde_json::json!({
+        "params": params,
+        "resolved": resolved
+    }))
+}
+
+/// 修改某个范围（default、市场或A股板块）的形态策略参数，只覆盖请求中给出的字段
+pub async fn update_strategy_params(
+    Path(scope): Path<String>,
+    State(app_state): State<AppState>,
+    Json(params): Json<PatternParamsOverride>,
+) -> impl IntoResponse {
+    let scope = match scope.parse::<ParamScope>() {
+        Ok(scope) => scope,
+        Err(_) => return (StatusCode::NOT_FOUND, "Unknown parameter scope").into_response(),
+    };
+    if let Err(e) = params.validate() {
+        return (StatusCode::BAD_REQUEST, e.to_string()).into_response();
+    }
+
+    match app_state.strategy_params.update(scope, &params).await {
+        Ok(params) => Json(params).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to update strategy params {}: {}", scope.key(), e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to update strategy params",
+            )
+                .into_response()
+        }
+    }
+}
+
+/// 撤销某个范围通过 API 做的修改，恢复为配置文件中的参数
+pub async fn reset_strategy_params(
+    Path(scope): Path<String>,
+    State(app_state): State<AppState>,
+) -> impl IntoResponse {
+    let scope = match scope.parse::<ParamScope>() {
+        Ok(scope) => scope,
+        Err(_) => return (StatusCode::NOT_FOUND, "Unknown parameter scope").into_response(),
+    };
+
+    match app_state.strategy_params.reset(scope).await {
+        Ok(params) => Json(params).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to reset strategy params {}: {}", scope.key(), e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to reset strategy params",
+            )
+                .into_response()
+        }
+    }
+}
+
 /// 将分析器信号转换为模板信号
 fn convert_analyzer_signal_to_template(symbol: String, signal: AnalyzerSignal) -> StrategySignal {
     match signal {
@@ -165,7 +664,7 @@ fn convert_analyzer_signal_to_template(symbol: String, signal: AnalyzerSignal) -
             }
             .to_string(),
             signal_type: format!("📊 {}", s.indicator_name),
-            price: s.value,
+            price: s.price,
             description: s.description,
             generated_at: "实时".to_string(),
         },
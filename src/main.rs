@@ -3,26 +3,33 @@ mod handlers;
 mod models;
 mod services;
 mod templates;
+mod utils;
 
 use crate::handlers::{
     market::{dashboard_handler, market_handler, AppState},
     strategy_handler,
 };
-use crate::models::{AlertResponse, CreateAlertRequest};
-use crate::services::{Database, EmailNotifier, PriceService};
+use crate::models::{AlertCondition, AlertResponse, CreateAlertRequest, Market};
+use crate::services::{
+    adjustment_factors, AlertRule, BacktestStrategy, Backtester, CorporateActionService, Database,
+    EmailNotifier, PriceAdjustment, PriceService, QuoteService, StrategyAnalyzer,
+    StrategyParamStore, StrategyScanner, TradingCalendars,
+};
 use crate::templates::{AlertFormTemplate, IndexTemplate};
 use askama::Template;
 use axum::{
     extract::{Json, Path, Query, State},
     http::{HeaderMap, StatusCode},
     response::{Html, IntoResponse},
-    routing::{get, get_service},
+    routing::{get, get_service, post, put},
     Router,
 };
 use std::sync::Arc;
 use tower_http::services::ServeDir;
 use tower_http::trace::TraceLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{
+    fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt,
+};
 
 // 使用handlers模块中的AppState定义
 
@@ -56,31 +63,87 @@ async fn main() -> anyhow::Result<()> {
     // Load configuration
     let config = config::Config::load()?;
 
+    // 命令行回测：trade_alert_rust backtest <symbol> [选项]，不启动服务，日志输出到 stderr
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let backtest_cli = args.first().map(String::as_str) == Some("backtest");
+
     // Initialize logging
+    let log_writer = if backtest_cli {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(&config.logging.level))
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(log_writer))
         .init();
 
     // Initialize database
     let db = Arc::new(Database::new(&config.database.url).await?);
 
+    if backtest_cli {
+        return run_backtest_cli(&db, &config, &args[1..]).await;
+    }
+
     // Initialize email notifier
     let email_notifier = Arc::new(EmailNotifier::new(config.email.clone())?);
 
+    // Load trading calendars
+    let calendars = Arc::new(TradingCalendars::load(&config.calendar.data_dir)?);
+
     // Initialize price service with Arc
     let price_service = Arc::new(PriceService::new(
         db.pool().clone(),
         &config.price_fetcher,
         email_notifier.clone(),
+        calendars.clone(),
+        config.strategy.indicators.clone(),
     ));
     let price_config = Arc::new(config.price_fetcher.clone());
-    price_service.start_price_updater(price_config).await;
+    price_service
+        .clone()
+        .start_price_updater(price_config)
+        .await;
+
+    // 统一行情服务，所有接口经它获取报价
+    let quotes = Arc::new(QuoteService::new(price_service.clone(), db.pool().clone()));
+
+    // 公司行动：定期按到期的拆股/送转调整预警价位
+    let corporate_actions = Arc::new(config.corporate_actions.clone());
+    if corporate_actions.rescale_alerts {
+        Arc::new(CorporateActionService::new(db.pool().clone())).start_rescale_loop(
+            email_notifier.clone(),
+            std::time::Duration::from_secs(corporate_actions.check_interval_secs),
+        );
+    }
+
+    // 形态策略参数：配置文件为基础，叠加运行时通过 API 保存的修改
+    let strategy_params = Arc::new(
+        StrategyParamStore::load(db.pool().clone(), config.strategy.params.clone()).await?,
+    );
+
+    // 收盘后策略扫描
+    let scanner = Arc::new(StrategyScanner::new(
+        db.pool().clone(),
+        &config.strategy,
+        strategy_params.clone(),
+        calendars.clone(),
+    ));
+    if config.strategy.scanner.enabled {
+        scanner.clone().start_scan_loop();
+    }
 
     // Create application state
     let state = AppState {
         db: db.clone(),
         email_notifier,
+        price_service,
+        calendars,
+        corporate_actions,
+        quotes,
+        strategy: Arc::new(config.strategy.clone()),
+        scanner,
+        strategy_params,
     };
 
     // Build our application with a route
@@ -101,7 +164,70 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/prices/:symbol", get(get_price_history))
         .route("/api/prices/:symbol/latest", get(get_latest_price))
         .route("/api/prices/:symbol/history", get(get_price_history))
+        .route(
+            "/api/prices/:symbol/bars",
+            get(handlers::intraday::get_price_bars),
+        )
+        .route(
+            "/api/prices/:symbol/indicators",
+            get(handlers::strategy::get_indicators),
+        )
+        .route(
+            "/api/prices/:symbol/backtest",
+            get(handlers::strategy::run_backtest),
+        )
         .route("/api/test-email", get(send_test_email))
+        // 策略API
+        .route(
+            "/api/strategy/analyze/:symbol",
+            get(handlers::strategy::analyze_symbol_strategy),
+        )
+        .route(
+            "/api/strategy/signals",
+            get(handlers::strategy::get_strategy_signals),
+        )
+        .route(
+            "/api/strategy/params",
+            get(handlers::strategy::get_strategy_params),
+        )
+        .route(
+            "/api/strategy/params/:scope",
+            put(handlers::strategy::update_strategy_params)
+                .delete(handlers::strategy::reset_strategy_params),
+        )
+        .route(
+            "/api/strategy/rankings",
+            get(handlers::strategy::get_rankings),
+        )
+        .route(
+            "/api/strategy/scans",
+            get(handlers::strategy::get_scan_runs),
+        )
+        .route(
+            "/api/strategy/scans/:market",
+            post(handlers::strategy::trigger_scan),
+        )
+        .route(
+            "/api/strategy/indices/:index",
+            get(handlers::strategy::get_index_constituents)
+                .put(handlers::strategy::set_index_constituents),
+        )
+        // 汇率API
+        .route(
+            "/api/fx/rates",
+            get(handlers::fx::get_fx_rates).post(handlers::fx::create_fx_rate),
+        )
+        .route(
+            "/api/fx/rates/:pair/history",
+            get(handlers::fx::get_fx_rate_history),
+        )
+        .route("/api/fx/convert", get(handlers::fx::convert_currency))
+        // 公司行动API
+        .route(
+            "/api/corporate-actions/:symbol",
+            get(handlers::corporate_actions::list_corporate_actions)
+                .post(handlers::corporate_actions::create_corporate_action),
+        )
         // 新增股票搜索API
         .route("/api/stocks/search", get(search_stocks))
         .route("/api/stocks/markets", get(get_markets))
@@ -110,6 +236,19 @@ async fn main() -> anyhow::Result<()> {
             "/api/stock-price/:symbol",
             get(handlers::market::get_stock_price),
         )
+        // 数据源状态API
+        .route(
+            "/api/providers/rate-limits",
+            get(handlers::providers::get_rate_limits),
+        )
+        .route(
+            "/api/providers/health",
+            get(handlers::providers::get_provider_health),
+        )
+        .route(
+            "/api/providers/quarantine",
+            get(handlers::providers::get_quarantined_prices),
+        )
         // 静态文件服务
         .nest_service("/static", get_service(ServeDir::new("static")))
         .layer(TraceLayer::new_for_http())
@@ -126,6 +265,129 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+const BACKTEST_USAGE: &str =
+    "用法: trade_alert_rust backtest <symbol> --strategy limit_up_pullback|bottom_breakout|alert \
+[--days 750] [--adjust forward|raw] [--indicator kdj_j] [--condition above|below] [--threshold 0] \
+[--capital 100000] [--slippage 0.1] [--commission 0.025] [--min-commission 5] [--stamp-duty 0.05] \
+[--take-profit 10] [--stop-loss 5] [--max-holding 10] [--json]";
+
+// 命令行回测，参数未指定时使用 [strategy.backtest] 配置
+async fn run_backtest_cli(
+    db: &Database,
+    config: &config::Config,
+    args: &[String],
+) -> anyhow::Result<()> {
+    let symbol = match args.first() {
+        Some(symbol) if !symbol.starts_with("--") => symbol.clone(),
+        _ => anyhow::bail!(BACKTEST_USAGE),
+    };
+
+    let mut params = config.strategy.backtest.clone();
+    let mut strategy = None;
+    let mut days = 750;
+    let mut adjustment = PriceAdjustment::Forward;
+    let (mut indicator, mut condition, mut threshold) = (None, None, None);
+    let mut json = false;
+
+    let mut rest = args[1..].iter();
+    while let Some(flag) = rest.next() {
+        if flag == "--json" {
+            json = true;
+            continue;
+        }
+        let value = rest
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("{} 缺少参数值\n{}", flag, BACKTEST_USAGE))?;
+        let number = || -> anyhow::Result<f64> {
+            value
+                .parse()
+                .map_err(|_| anyhow::anyhow!("{} 的参数值 {} 不是数字", flag, value))
+        };
+        match flag.as_str() {
+            "--strategy" => strategy = Some(value.parse::<BacktestStrategy>()?),
+            "--days" => days = number()? as i32,
+            "--adjust" => adjustment = serde_json::from_value(serde_json::json!(value))?,
+            "--indicator" => indicator = Some(serde_json::from_value(serde_json::json!(value))?),
+            "--condition" => {
+                condition = Some(match value.to_lowercase().as_str() {
+                    "above" => AlertCondition::Above,
+                    "below" => AlertCondition::Below,
+                    _ => anyhow::bail!("--condition 只能是 above 或 below"),
+                })
+            }
+            "--threshold" => threshold = Some(number()?),
+            "--capital" => params.initial_capital = number()?,
+            "--slippage" => params.slippage_percent = number()?,
+            "--commission" => params.commission_percent = number()?,
+            "--min-commission" => params.min_commission = number()?,
+            "--stamp-duty" => params.stamp_duty_percent = number()?,
+            "--take-profit" => params.take_profit_percent = number()?,
+            "--stop-loss" => params.stop_loss_percent = number()?,
+            "--max-holding" => params.max_holding_days = number()? as usize,
+            _ => anyhow::bail!("未知参数 {}\n{}", flag, BACKTEST_USAGE),
+        }
+    }
+
+    let strategy = strategy.ok_or_else(|| anyhow::anyhow!(BACKTEST_USAGE))?;
+    let rule = match (condition, threshold) {
+        (Some(condition), Some(threshold)) => Some(AlertRule {
+            indicator,
+            condition,
+            threshold,
+        }),
+        _ => None,
+    };
+
+    let strategy_params =
+        StrategyParamStore::load(db.pool().clone(), config.strategy.params.clone()).await?;
+    let analyzer = StrategyAnalyzer::new(db.pool().clone(), config.strategy.indicators.clone())
+        .with_params(strategy_params.snapshot());
+    let report = Backtester::new(analyzer, params)
+        .run(&symbol, strategy, rule, days, adjustment)
+        .await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} {}回测: {} ~ {}，{}根K线，{}个信号",
+        report.symbol,
+        strategy.label(),
+        report.start_date.as_deref().unwrap_or("-"),
+        report.end_date.as_deref().unwrap_or("-"),
+        report.bars,
+        report.signals
+    );
+    println!(
+        "期末权益 {:.2}（初始 {:.2}），总收益 {:.2}%，最大回撤 {:.2}%，胜率 {}，交易 {} 笔，费用 {:.2}",
+        report.final_equity,
+        report.params.initial_capital,
+        report.total_return_percent,
+        report.max_drawdown_percent,
+        report
+            .win_rate_percent
+            .map_or("-".to_string(), |rate| format!("{:.1}%", rate)),
+        report.trades.len(),
+        report.total_fees
+    );
+    for trade in &report.trades {
+        println!(
+            "{} {:>10.3} -> {} {:>10.3}  数量 {:>10.2}  盈亏 {:>12.2} ({:>+7.2}%)  {}",
+            trade.entry_date,
+            trade.entry_price,
+            trade.exit_date,
+            trade.exit_price,
+            trade.shares,
+            trade.pnl,
+            trade.return_percent,
+            trade.exit_reason.label()
+        );
+    }
+    Ok(())
+}
+
 // 首页处理函数
 async fn index_page(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
     let user_id = extract_user_id(&headers);
@@ -197,7 +459,10 @@ async fn create_alert(
     }
 
     match state.db.create_alert(&payload).await {
-        Ok(alert) => (StatusCode::CREATED, Json(AlertResponse::from(alert))).into_response(),
+        Ok(alert) => {
+            state.price_service.notify_alerts_changed();
+            (StatusCode::CREATED, Json(AlertResponse::from(alert))).into_response()
+        }
         Err(e) => {
             tracing::error!("Failed to create alert: {}", e);
             (StatusCode::INTERNAL_SERVER_ERROR, "Failed to create alert").into_response()
@@ -287,7 +552,10 @@ async fn delete_alert(
     };
 
     match delete_result {
-        Ok(true) => StatusCode::NO_CONTENT.into_response(),
+        Ok(true) => {
+            state.price_service.notify_alerts_changed();
+            StatusCode::NO_CONTENT.into_response()
+        }
         Ok(false) => (StatusCode::NOT_FOUND, "Alert not found").into_response(),
         Err(e) => {
             tracing::error!("Failed to delete alert: {}", e);
@@ -324,7 +592,10 @@ async fn update_alert(
     }
 
     match state.db.update_alert(id, &payload).await {
-        Ok(Some(alert)) => Json(AlertResponse::from(alert)).into_response(),
+        Ok(Some(alert)) => {
+            state.price_service.notify_alerts_changed();
+            Json(AlertResponse::from(alert)).into_response()
+        }
         Ok(None) => (StatusCode::NOT_FOUND, "Alert not found").into_response(),
         Err(e) => {
             tracing::error!("Failed to update alert: {}", e);
@@ -375,13 +646,26 @@ async fn edit_alert_form(State(state): State<AppState>, Path(id): Path<i64>) ->
 }
 
 // 获取股票价格历史
+#[derive(Debug, serde::Deserialize)]
+struct PriceHistoryQuery {
+    /// raw（默认）为原始价格，forward 为前复权价格
+    #[serde(default = "default_history_adjustment")]
+    adjust: PriceAdjustment,
+}
+
+fn default_history_adjustment() -> PriceAdjustment {
+    PriceAdjustment::Raw
+}
+
 async fn get_price_history(
     State(state): State<AppState>,
     Path(symbol): Path<String>,
+    Query(query): Query<PriceHistoryQuery>,
 ) -> impl IntoResponse {
     let result = sqlx::query!(
         r#"
-        SELECT close_price as price, volume, date, created_at
+        SELECT close_price as price, volume, date, created_at, data_quality,
+               source, fetch_origin, fetched_at, exchange_time, raw_hash
         FROM price_history
         WHERE symbol = ?
         ORDER BY date DESC
@@ -390,29 +674,57 @@ async fn get_price_history(
         symbol
     )
     .fetch_all(state.db.pool())
-    .await;
+    .await
+    .map_err(anyhow::Error::from);
+
+    let actions = if query.adjust == PriceAdjustment::Forward {
+        CorporateActionService::new(state.db.pool().clone())
+            .list(&symbol)
+            .await
+    } else {
+        Ok(Vec::new())
+    };
 
-    match result {
-        Ok(prices) => {
-            let price_data: Vec<_> = prices
+    match (result, actions) {
+        (Ok(mut prices), Ok(actions)) => {
+            // 复权因子按日期升序计算
+            prices.reverse();
+            let dates: Vec<String> = prices.iter().map(|row| row.date.to_string()).collect();
+            let bars: Vec<(&str, f64)> = dates
+                .iter()
+                .zip(&prices)
+                .map(|(date, row)| (date.as_str(), row.price))
+                .collect();
+            let factors = adjustment_factors(&bars, &actions);
+
+            let mut price_data: Vec<_> = prices
                 .into_iter()
-                .map(|row| {
+                .zip(factors)
+                .map(|(row, factor)| {
                     serde_json::json!({
-                        "price": row.price,
-                        "volume": row.volume,
+                        "price": row.price * factor.price,
+                        "volume": (row.volume as f64 * factor.volume).round() as i64,
                         "date": row.date,
-                        "created_at": row.created_at
+                        "created_at": row.created_at,
+                        "data_quality": row.data_quality,
+                        "source": row.source,
+                        "fetch_origin": row.fetch_origin,
+                        "fetched_at": row.fetched_at,
+                        "exchange_time": row.exchange_time,
+                        "raw_hash": row.raw_hash
                     })
                 })
                 .collect();
+            price_data.reverse();
 
             Json(serde_json::json!({
                 "symbol": symbol,
+                "adjust": query.adjust,
                 "prices": price_data
             }))
             .into_response()
         }
-        Err(e) => {
+        (Err(e), _) | (_, Err(e)) => {
             tracing::error!("Failed to get price history for {}: {}", symbol, e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
@@ -426,9 +738,35 @@ async fn get_price_history(
 /// 检测股票代码所属市场并返回货币信息
 fn detect_market_info(symbol: &str) -> (String, String, String) {
     // 返回 (market, currency, currency_symbol)
-    if symbol.ends_with(".SZ") || symbol.ends_with(".SS") || symbol.ends_with(".SH") {
-        // A股市场
+    if symbol.ends_with(".SZ")
+        || symbol.ends_with(".SS")
+        || symbol.ends_with(".SH")
+        || symbol.ends_with(".BJ")
+    {
+        // A股市场（沪深北）
         ("cn".to_string(), "CNY".to_string(), "¥".to_string())
+    } else if symbol.ends_with(".HK") {
+        // 港股市场
+        ("hk".to_string(), "HKD".to_string(), "HK$".to_string())
+    } else if symbol.ends_with(".SHF") {
+        // 国内期货
+        ("cn_futures".to_string(), "CNY".to_string(), "¥".to_string())
+    } else if symbol.ends_with("=F") {
+        // 国际期货以美元计价
+        ("futures".to_string(), "USD".to_string(), "$".to_string())
+    } else if let Some(pair) = symbol.strip_suffix("=X") {
+        // 外汇按报价货币显示，如 USDCNH=X 以人民币报价
+        let quote = pair.get(3..).unwrap_or("USD").to_string();
+        let currency_symbol = match quote.as_str() {
+            "USD" => "$",
+            "CNY" | "CNH" => "¥",
+            "HKD" => "HK$",
+            "EUR" => "€",
+            "GBP" => "£",
+            "JPY" => "JP¥",
+            _ => "",
+        };
+        ("fx".to_string(), quote, currency_symbol.to_string())
     } else if symbol.contains("-USD")
         || symbol.contains("-USDT")
         || symbol.contains("BTC")
@@ -465,80 +803,94 @@ fn detect_market_info(symbol: &str) -> (String, String, String) {
 }
 
 // 获取股票最新价格
+#[derive(Debug, serde::Deserialize)]
+struct LatestPriceQuery {
+    /// 额外返回换算为该货币的价格
+    currency: Option<String>,
+}
+
+/// 把价格换算为请求的货币，没有可用汇率时返回错误说明
+async fn converted_price(
+    state: &AppState,
+    price: f64,
+    from: &str,
+    to: Option<&str>,
+) -> serde_json::Value {
+    let Some(to) = to else {
+        return serde_json::Value::Null;
+    };
+    match state
+        .price_service
+        .fx_rates()
+        .convert(price, from, to)
+        .await
+    {
+        Ok(Some(conversion)) => serde_json::json!({
+            "currency": conversion.to,
+            "price": conversion.amount,
+            "rate": conversion.rate,
+            "rate_as_of": conversion.as_of
+        }),
+        Ok(None) => serde_json::json!({
+            "currency": to.to_uppercase(),
+            "error": format!("没有可用的汇率: {} → {}", from, to.to_uppercase())
+        }),
+        Err(e) => {
+            tracing::error!("Failed to convert {} to {}: {}", from, to, e);
+            serde_json::json!({ "currency": to.to_uppercase(), "error": "汇率换算失败" })
+        }
+    }
+}
+
 async fn get_latest_price(
     State(state): State<AppState>,
     Path(symbol): Path<String>,
+    Query(query): Query<LatestPriceQuery>,
 ) -> impl IntoResponse {
     let (market, currency, currency_symbol) = detect_market_info(&symbol);
 
-    let result = sqlx::query!(
-        r#"
-        SELECT close_price as price, volume, date, created_at
-        FROM price_history
-        WHERE symbol = ?
-        ORDER BY date DESC
-        LIMIT 1
-        "#,
-        symbol
-    )
-    .fetch_optional(state.db.pool())
-    .await;
-
-    match result {
-        Ok(Some(row)) => Json(serde_json::json!({
-            "symbol": symbol,
-            "price": row.price,
-            "volume": row.volume,
-            "date": row.date,
-            "created_at": row.created_at,
-            "market": market,
-            "currency": currency,
-            "currency_symbol": currency_symbol,
-            "name_en": null // 数据库无公司名，返回null
-        }))
-        .into_response(),
-        Ok(None) => {
-            // 数据库没有，实时查Yahoo
-            match fetch_price_from_yahoo(&symbol).await {
-                Ok((price, volume, name_en)) => {
-                    let today = chrono::Utc::now().date_naive();
-                    let now = chrono::Utc::now().naive_utc();
-                    // 写入数据库 - 使用当前价格作为所有OHLC值
-                    let _ = sqlx::query!(
-                        r#"
-                        INSERT OR REPLACE INTO price_history (symbol, date, open_price, high_price, low_price, close_price, volume, created_at)
-                        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-                        "#,
-                        symbol,
-                        today,
-                        price, // open_price
-                        price, // high_price  
-                        price, // low_price
-                        price, // close_price
-                        volume,
-                        now,
-                    )
-                    .execute(state.db.pool())
-                    .await;
-                    Json(serde_json::json!({
-                        "symbol": symbol,
-                        "price": price,
-                        "volume": volume,
-                        "date": today,
-                        "created_at": now,
-                        "market": market,
-                        "currency": currency,
-                        "currency_symbol": currency_symbol,
-                        "name_en": name_en
-                    }))
-                    .into_response()
-                }
-                Err(e) => {
-                    tracing::error!("Failed to fetch price from Yahoo for {}: {}", symbol, e);
-                    (StatusCode::NOT_FOUND, "Unable to fetch current price").into_response()
-                }
-            }
+    // 统一经行情服务获取：缓存未过期直接返回，否则按市场路由实时获取，失败时使用最近保存的行情
+    match state.quotes.get_quote_or_stored(&symbol).await {
+        Ok(Some(quote)) => {
+            // 最近一次获取失败时，界面显示"数据陈旧"标记
+            let feed_status = state
+                .db
+                .get_price_feed_status(&symbol)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::error!("Failed to get feed status for {}: {}", symbol, e);
+                    None
+                });
+            let created_at = quote.timestamp.naive_utc();
+            let stale = quote.stale || feed_status.as_ref().is_some_and(|s| s.is_stale());
+            let stale_since = feed_status
+                .as_ref()
+                .and_then(|s| s.stale_since())
+                .or(stale.then_some(created_at));
+            let converted =
+                converted_price(&state, quote.price, &currency, query.currency.as_deref()).await;
+
+            Json(serde_json::json!({
+                "symbol": symbol,
+                "price": quote.price,
+                "volume": quote.volume,
+                "date": quote.timestamp.date_naive(),
+                "created_at": created_at,
+                "source": quote.source,
+                "session": quote.session,
+                "session_label": quote.session.label(),
+                "market": market,
+                "currency": currency,
+                "currency_symbol": currency_symbol,
+                "data_quality": quote.data_quality,
+                "stale": stale,
+                "stale_since": stale_since,
+                "converted": converted,
+                "name_en": quote.name_en
+            }))
+            .into_response()
         }
+        Ok(None) => (StatusCode::NOT_FOUND, "Unable to fetch current price").into_response(),
         Err(e) => {
             tracing::error!("Failed to get latest price for {}: {}", symbol, e);
             (
@@ -550,73 +902,6 @@ async fn get_latest_price(
     }
 }
 
-// 辅助函数：直接从Yahoo API获取价格和公司名
-async fn fetch_price_from_yahoo(symbol: &str) -> anyhow::Result<(f64, i64, Option<String>)> {
-    use serde::Deserialize;
-    #[derive(Debug, Deserialize)]
-    struct YahooQuoteResponse {
-        chart: YahooChart,
-    }
-    #[derive(Debug, Deserialize)]
-    struct YahooChart {
-        result: Vec<YahooResult>,
-        error: Option<YahooError>,
-    }
-    #[derive(Debug, Deserialize)]
-    struct YahooResult {
-        meta: YahooMeta,
-    }
-    #[derive(Debug, Deserialize)]
-    struct YahooMeta {
-        #[serde(rename = "regularMarketPrice")]
-        regular_market_price: Option<f64>,
-        #[serde(rename = "regularMarketVolume")]
-        regular_market_volume: Option<i64>,
-        #[serde(rename = "shortName")]
-        short_name: Option<String>,
-    }
-    #[derive(Debug, Deserialize)]
-    struct YahooError {
-        code: String,
-        description: String,
-    }
-    let client = reqwest::Client::new();
-    let url = format!("https://query1.finance.yahoo.com/v8/finance/chart/{symbol}");
-    let response = client
-        .get(&url)
-        .header(
-            "User-Agent",
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
-        )
-        .timeout(std::time::Duration::from_secs(10))
-        .send()
-        .await?;
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
-    }
-    let yahoo_response: YahooQuoteResponse = response.json().await?;
-    if let Some(error) = yahoo_response.chart.error {
-        return Err(anyhow::anyhow!(
-            "Yahoo Finance error: {} - {}",
-            error.code,
-            error.description
-        ));
-    }
-    let result = yahoo_response
-        .chart
-        .result
-        .into_iter()
-        .next()
-        .ok_or_else(|| anyhow::anyhow!("No data returned for symbol {}", symbol))?;
-    let price = result
-        .meta
-        .regular_market_price
-        .ok_or_else(|| anyhow::anyhow!("No price data for symbol {}", symbol))?;
-    let volume = result.meta.regular_market_volume.unwrap_or(0);
-    let name_en = result.meta.short_name;
-    Ok((price, volume, name_en))
-}
-
 async fn send_test_email(State(state): State<AppState>) -> impl IntoResponse {
     match state.email_notifier.send_test_email().await {
         Ok(_) => {
@@ -670,6 +955,31 @@ async fn search_stocks(
         }
     }
 
+    // 搜索港股
+    if market == "all" || market == "hk" {
+        if let Ok(hk_results) = search_hk_stocks(&state, query).await {
+            results.extend(hk_results);
+        }
+    }
+
+    // 搜索期货（国际、国内）
+    if market == "all" || market == "futures" || market == "cn_futures" {
+        if let Ok(futures_results) = search_futures_contracts(&state, query).await {
+            results.extend(
+                futures_results
+                    .into_iter()
+                    .filter(|stock| market == "all" || stock["market"] == market),
+            );
+        }
+    }
+
+    // 搜索外汇
+    if market == "all" || market == "fx" {
+        if let Ok(fx_results) = search_fx_pairs(&state, query).await {
+            results.extend(fx_results);
+        }
+    }
+
     // 搜索加密货币
     if market == "all" || market == "crypto" {
         if let Ok(crypto_results) = search_crypto_stocks(&state, query).await {
@@ -703,10 +1013,42 @@ async fn get_markets() -> impl IntoResponse {
                 "code": "cn",
                 "name": "A股",
                 "name_en": "China A-Shares",
-                "symbol_format": "000001.SZ",
+                "symbol_format": "000001.SZ / 600036.SH / 430047.BJ",
+                "currency": "CNY",
+                "currency_symbol": "¥"
+            },
+            {
+                "code": "hk",
+                "name": "港股",
+                "name_en": "Hong Kong Stocks",
+                "symbol_format": "0700.HK",
+                "currency": "HKD",
+                "currency_symbol": "HK$"
+            },
+            {
+                "code": "futures",
+                "name": "国际期货",
+                "name_en": "Futures & Commodities",
+                "symbol_format": "GC=F",
+                "currency": "USD",
+                "currency_symbol": "$"
+            },
+            {
+                "code": "cn_futures",
+                "name": "国内期货",
+                "name_en": "China Futures (SHFE)",
+                "symbol_format": "AU0.SHF / AU2512.SHF",
                 "currency": "CNY",
                 "currency_symbol": "¥"
             },
+            {
+                "code": "fx",
+                "name": "外汇",
+                "name_en": "Foreign Exchange",
+                "symbol_format": "USDCNH=X",
+                "currency": "报价货币",
+                "currency_symbol": ""
+            },
             {
                 "code": "crypto",
                 "name": "加密货币",
@@ -779,6 +1121,188 @@ async fn search_cn_stocks(
     Ok(stocks)
 }
 
+/// 搜索港股股票
+async fn search_hk_stocks(
+    state: &AppState,
+    query: &str,
+) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+    let uppercase_query = query.to_uppercase();
+    let search_pattern = format!("%{query}%");
+    let search_pattern_en = format!("%{uppercase_query}%");
+
+    let results = sqlx::query!(
+        r#"
+        SELECT symbol, code, name_cn, name_en, pinyin, pinyin_short, industry, lot_size
+        FROM hk_stocks 
+        WHERE status = 'active' 
+        AND (
+            symbol LIKE ?1 OR 
+            code LIKE ?1 OR 
+            name_cn LIKE ?1 OR 
+            UPPER(name_en) LIKE ?2 OR 
+            pinyin LIKE ?1 OR 
+            pinyin_short LIKE ?1
+        )
+        ORDER BY 
+            CASE 
+                WHEN UPPER(symbol) = ?3 THEN 1
+                WHEN code = ?3 THEN 2
+                WHEN name_cn = ?3 THEN 3
+                WHEN symbol LIKE ?1 THEN 4
+                WHEN code LIKE ?1 THEN 5
+                WHEN name_cn LIKE ?1 THEN 6
+                ELSE 7
+            END
+        LIMIT 5
+        "#,
+        search_pattern,
+        search_pattern_en,
+        uppercase_query
+    )
+    .fetch_all(state.db.pool())
+    .await?;
+
+    let stocks: Vec<serde_json::Value> = results
+        .into_iter()
+        .map(|row| {
+            serde_json::json!({
+                "symbol": row.symbol,
+                "code": row.code,
+                "exchange": "HK",
+                "name_cn": row.name_cn,
+                "name_en": row.name_en,
+                "pinyin": row.pinyin,
+                "pinyin_short": row.pinyin_short,
+                "industry": row.industry,
+                "lot_size": row.lot_size,
+                "market": "hk",
+                "display_name": format!("{} ({})", row.name_cn, row.symbol),
+                "search_text": format!("{} {} {} {}", row.symbol, row.name_cn, row.pinyin, row.pinyin_short)
+            })
+        })
+        .collect();
+
+    Ok(stocks)
+}
+
+/// 搜索期货合约（国际期货与国内期货）
+async fn search_futures_contracts(
+    state: &AppState,
+    query: &str,
+) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+    let uppercase_query = query.to_uppercase();
+    let search_pattern = format!("%{uppercase_query}%");
+    let search_pattern_cn = format!("%{query}%");
+
+    let results = sqlx::query!(
+        r#"
+        SELECT symbol, code, exchange, name_cn, name_en, pinyin, pinyin_short, category, currency, unit
+        FROM futures_contracts 
+        WHERE status = 'active' 
+        AND (
+            UPPER(symbol) LIKE ?1 OR 
+            UPPER(code) LIKE ?1 OR 
+            UPPER(name_en) LIKE ?1 OR 
+            name_cn LIKE ?2 OR 
+            pinyin LIKE ?2 OR 
+            pinyin_short LIKE ?2
+        )
+        ORDER BY 
+            CASE 
+                WHEN UPPER(code) = ?3 THEN 1
+                WHEN UPPER(symbol) = ?3 THEN 2
+                WHEN name_cn = ?3 THEN 3
+                WHEN UPPER(symbol) LIKE ?1 THEN 4
+                WHEN name_cn LIKE ?2 THEN 5
+                ELSE 6
+            END
+        LIMIT 5
+        "#,
+        search_pattern,
+        search_pattern_cn,
+        uppercase_query
+    )
+    .fetch_all(state.db.pool())
+    .await?;
+
+    let contracts: Vec<serde_json::Value> = results
+        .into_iter()
+        .map(|row| {
+            let market = Market::from_symbol(&row.symbol);
+            serde_json::json!({
+                "symbol": row.symbol,
+                "code": row.code,
+                "exchange": row.exchange,
+                "name_cn": row.name_cn,
+                "name_en": row.name_en,
+                "pinyin": row.pinyin,
+                "pinyin_short": row.pinyin_short,
+                "industry": row.category,
+                "currency": row.currency,
+                "unit": row.unit,
+                "market": market.to_str(),
+                "display_name": format!("{} {} ({})", row.symbol, row.name_cn, row.exchange),
+                "search_text": format!("{} {} {} {}", row.symbol, row.name_cn, row.name_en, row.pinyin_short)
+            })
+        })
+        .collect();
+
+    Ok(contracts)
+}
+
+/// 搜索外汇货币对
+async fn search_fx_pairs(
+    state: &AppState,
+    query: &str,
+) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+    // 允许输入 USD/CNH、usdcnh 等形式
+    let normalized_query = query.to_uppercase().replace(['/', ' '], "");
+    let search_pattern = format!("%{normalized_query}%");
+    let search_pattern_cn = format!("%{query}%");
+
+    let results = sqlx::query!(
+        r#"
+        SELECT symbol, base_currency, quote_currency, name_cn, name_en
+        FROM fx_pairs 
+        WHERE status = 'active' 
+        AND (
+            UPPER(symbol) LIKE ?1 OR 
+            name_cn LIKE ?2
+        )
+        ORDER BY 
+            CASE 
+                WHEN UPPER(symbol) = ?3 || '=X' THEN 1
+                WHEN UPPER(symbol) LIKE ?3 || '%' THEN 2
+                ELSE 3
+            END
+        LIMIT 5
+        "#,
+        search_pattern,
+        search_pattern_cn,
+        normalized_query
+    )
+    .fetch_all(state.db.pool())
+    .await?;
+
+    let pairs: Vec<serde_json::Value> = results
+        .into_iter()
+        .map(|row| {
+            serde_json::json!({
+                "symbol": row.symbol,
+                "base_currency": row.base_currency,
+                "quote_currency": row.quote_currency,
+                "name_cn": row.name_cn,
+                "name_en": row.name_en,
+                "market": "fx",
+                "display_name": format!("{} ({})", row.name_cn, row.name_en),
+                "search_text": format!("{} {} {}", row.symbol, row.name_cn, row.name_en)
+            })
+        })
+        .collect();
+
+    Ok(pairs)
+}
+
 /// 搜索美股股票
 async fn search_us_stocks(
     state: &AppState,
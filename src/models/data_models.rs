@@ -1,4 +1,4 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use std::fmt;
@@ -16,13 +16,22 @@ pub struct Alert {
     pub notification_email: Option<String>,
     #[serde(default = "default_user_id")]
     pub user_id: String,
+    /// 阈值的计价货币，为空时与股票计价货币相同
+    #[serde(default)]
+    pub threshold_currency: Option<String>,
+    /// 美股盘前盘后的价格是否也参与判断
+    #[serde(default)]
+    pub extended_hours: bool,
+    /// 按技术指标判断时的指标，`price` 为指标阈值；为空时按价格判断
+    #[serde(default)]
+    pub indicator: Option<AlertIndicator>,
 }
 
 fn default_user_id() -> String {
     "default".to_string()
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "TEXT", rename_all = "lowercase")]
 pub enum AlertCondition {
     Above,
@@ -38,6 +47,46 @@ impl fmt::Display for AlertCondition {
     }
 }
 
+/// 预警可选的技术指标，阈值与指标取值同单位（布林带宽、换手率为百分比）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AlertIndicator {
+    KdjK,
+    KdjD,
+    KdjJ,
+    BollWidth,
+    VolumeRatio,
+    TurnoverRate,
+}
+
+impl AlertIndicator {
+    /// 界面与邮件显示的指标名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            AlertIndicator::KdjK => "KDJ K值",
+            AlertIndicator::KdjD => "KDJ D值",
+            AlertIndicator::KdjJ => "KDJ J值",
+            AlertIndicator::BollWidth => "布林带宽(%)",
+            AlertIndicator::VolumeRatio => "量比",
+            AlertIndicator::TurnoverRate => "换手率(%)",
+        }
+    }
+}
+
+impl fmt::Display for AlertIndicator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertIndicator::KdjK => write!(f, "kdj_k"),
+            AlertIndicator::KdjD => write!(f, "kdj_d"),
+            AlertIndicator::KdjJ => write!(f, "kdj_j"),
+            AlertIndicator::BollWidth => write!(f, "boll_width"),
+            AlertIndicator::VolumeRatio => write!(f, "volume_ratio"),
+            AlertIndicator::TurnoverRate => write!(f, "turnover_rate"),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "TEXT", rename_all = "lowercase")]
 pub enum AlertStatus {
@@ -67,6 +116,192 @@ impl PartialEq<&str> for AlertStatus {
     }
 }
 
+/// 价格数据质量
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DataQuality {
+    /// 数据源返回的真实行情
+    #[default]
+    Live,
+    /// 获取失败时生成的后备模拟数据，不参与预警判断
+    Synthetic,
+}
+
+impl fmt::Display for DataQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataQuality::Live => write!(f, "live"),
+            DataQuality::Synthetic => write!(f, "synthetic"),
+        }
+    }
+}
+
+/// 行情所属的交易时段
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MarketSession {
+    /// 常规交易时段
+    #[default]
+    Regular,
+    /// 美股盘前
+    PreMarket,
+    /// 美股盘后
+    AfterHours,
+}
+
+impl MarketSession {
+    /// 界面显示的时段名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            MarketSession::Regular => "盘中",
+            MarketSession::PreMarket => "盘前",
+            MarketSession::AfterHours => "盘后",
+        }
+    }
+}
+
+impl fmt::Display for MarketSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketSession::Regular => write!(f, "regular"),
+            MarketSession::PreMarket => write!(f, "pre_market"),
+            MarketSession::AfterHours => write!(f, "after_hours"),
+        }
+    }
+}
+
+/// 股票行情状态
+#[derive(Debug, Serialize, FromRow)]
+pub struct PriceFeedStatus {
+    pub symbol: String,
+    pub last_success_at: Option<NaiveDateTime>,
+    pub last_failure_at: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+}
+
+/// 校验未通过而被隔离的行情
+#[derive(Debug, Serialize, FromRow)]
+pub struct QuarantinedPrice {
+    pub id: i64,
+    pub symbol: String,
+    pub price: f64,
+    pub source: String,
+    pub reference_price: Option<f64>,
+    pub deviation_percent: Option<f64>,
+    pub confirm_source: Option<String>,
+    pub confirm_price: Option<f64>,
+    pub reason: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// 汇率记录：1单位基础货币折合的报价货币数量
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FxRate {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: f64,
+    pub source: String,
+    pub as_of: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFxRateRequest {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: f64,
+    /// 默认为当前时间
+    #[serde(default)]
+    pub as_of: Option<NaiveDateTime>,
+}
+
+/// 公司行动类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum CorporateActionType {
+    /// 拆股/合股
+    Split,
+    /// 现金分红（除息）
+    Dividend,
+    /// A股送转股，可同时派息（除权除息）
+    Bonus,
+}
+
+impl fmt::Display for CorporateActionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorporateActionType::Split => write!(f, "split"),
+            CorporateActionType::Dividend => write!(f, "dividend"),
+            CorporateActionType::Bonus => write!(f, "bonus"),
+        }
+    }
+}
+
+/// 公司行动：拆股、分红、送转
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CorporateAction {
+    pub id: i64,
+    pub symbol: String,
+    pub ex_date: NaiveDate,
+    pub action_type: CorporateActionType,
+    /// 每股变为多少股，无股本变动时为1
+    pub split_ratio: f64,
+    /// 每股现金分红
+    pub cash_dividend: f64,
+    pub description: Option<String>,
+    pub alerts_rescaled_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl CorporateAction {
+    /// 是否改变股本（需要调整预警价位）
+    pub fn changes_share_count(&self) -> bool {
+        (self.split_ratio - 1.0).abs() > f64::EPSILON
+    }
+
+    /// 除权前价格的复权因子：(前收盘 - 每股分红) / (前收盘 × 拆股比例)
+    pub fn adjustment_factor(&self, previous_close: f64) -> f64 {
+        let dividend_factor = if previous_close > 0.0 && self.cash_dividend < previous_close {
+            (previous_close - self.cash_dividend) / previous_close
+        } else {
+            1.0
+        };
+        dividend_factor / self.split_ratio
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCorporateActionRequest {
+    pub ex_date: NaiveDate,
+    pub action_type: CorporateActionType,
+    #[serde(default)]
+    pub split_ratio: Option<f64>,
+    #[serde(default)]
+    pub cash_dividend: Option<f64>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl PriceFeedStatus {
+    /// 行情陈旧的起始时间：最近一次获取失败且晚于最近一次成功时，返回最后一次成功的时间
+    pub fn stale_since(&self) -> Option<NaiveDateTime> {
+        match (self.last_success_at, self.last_failure_at) {
+            (Some(success), Some(failure)) if failure > success => Some(success),
+            _ => None,
+        }
+    }
+
+    pub fn is_stale(&self) -> bool {
+        match (self.last_success_at, self.last_failure_at) {
+            (None, Some(_)) => true,
+            (Some(success), Some(failure)) => failure > success,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateAlertRequest {
     pub symbol: String,
@@ -75,6 +310,15 @@ pub struct CreateAlertRequest {
     pub notification_email: Option<String>,
     #[serde(default = "default_user_id")]
     pub user_id: String,
+    /// 按其他货币设定阈值，如对 BABA 按人民币价格预警时为 CNY
+    #[serde(default)]
+    pub threshold_currency: Option<String>,
+    /// 美股盘前盘后的价格也参与判断
+    #[serde(default)]
+    pub extended_hours: bool,
+    /// 按技术指标设定预警，如量比高于 2 时为 volume_ratio
+    #[serde(default)]
+    pub indicator: Option<AlertIndicator>,
 }
 
 #[derive(Debug, Serialize)]
@@ -90,6 +334,9 @@ pub struct AlertResponse {
     pub notification_email: Option<String>,
     #[serde(default = "default_user_id")]
     pub user_id: String,
+    pub threshold_currency: Option<String>,
+    pub extended_hours: bool,
+    pub indicator: Option<AlertIndicator>,
 }
 
 // 用于模板渲染的 Alert 结构体
@@ -105,6 +352,10 @@ pub struct AlertForTemplate {
     pub updated_at: String,
     pub triggered_at: Option<String>,
     pub notification_email: Option<String>,
+    pub threshold_currency: Option<String>,
+    pub extended_hours: bool,
+    pub indicator: Option<String>,
+    pub indicator_label: Option<String>,
 }
 
 impl From<Alert> for AlertForTemplate {
@@ -121,6 +372,10 @@ impl From<Alert> for AlertForTemplate {
                 .triggered_at
                 .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string()),
             notification_email: alert.notification_email,
+            threshold_currency: alert.threshold_currency,
+            extended_hours: alert.extended_hours,
+            indicator: alert.indicator.map(|i| i.to_string()),
+            indicator_label: alert.indicator.map(|i| i.label().to_string()),
         }
     }
 }
@@ -138,6 +393,9 @@ impl From<Alert> for AlertResponse {
             triggered_at: alert.triggered_at,
             notification_email: alert.notification_email,
             user_id: alert.user_id,
+            threshold_currency: alert.threshold_currency,
+            extended_hours: alert.extended_hours,
+            indicator: alert.indicator,
         }
     }
 }
@@ -152,3 +410,142 @@ impl Alert {
         }
     }
 }
+
+/// 市场类型枚举
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Market {
+    US,        // 美股
+    CN,        // A股（沪深北）
+    HK,        // 港股
+    Futures,   // 国际期货/商品 (GC=F、CL=F)
+    CnFutures, // 国内期货 (上期所)
+    FX,        // 外汇
+    Crypto,    // 加密货币
+}
+
+impl std::str::FromStr for Market {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "us" => Ok(Market::US),
+            "cn" => Ok(Market::CN),
+            "hk" => Ok(Market::HK),
+            "futures" => Ok(Market::Futures),
+            "cn_futures" => Ok(Market::CnFutures),
+            "fx" => Ok(Market::FX),
+            "crypto" => Ok(Market::Crypto),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Market {
+    /// 全部市场，按首页展示顺序排列
+    pub const ALL: [Market; 7] = [
+        Market::US,
+        Market::CN,
+        Market::HK,
+        Market::Futures,
+        Market::CnFutures,
+        Market::FX,
+        Market::Crypto,
+    ];
+
+    /// 根据股票代码后缀判断所属市场
+    pub fn from_symbol(symbol: &str) -> Self {
+        let symbol = symbol.to_uppercase();
+        if symbol.ends_with(".SZ")
+            || symbol.ends_with(".SS")
+            || symbol.ends_with(".SH")
+            || symbol.ends_with(".BJ")
+        {
+            Market::CN
+        } else if symbol.ends_with(".HK") {
+            Market::HK
+        } else if symbol.ends_with(".SHF") {
+            Market::CnFutures
+        } else if symbol.ends_with("=F") {
+            Market::Futures
+        } else if symbol.ends_with("=X") {
+            Market::FX
+        } else if symbol.ends_with("-USD") || symbol.ends_with("-USDT") {
+            Market::Crypto
+        } else {
+            Market::US
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Market::US => "us",
+            Market::CN => "cn",
+            Market::HK => "hk",
+            Market::Futures => "futures",
+            Market::CnFutures => "cn_futures",
+            Market::FX => "fx",
+            Market::Crypto => "crypto",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Market::US => "美股",
+            Market::CN => "A股",
+            Market::HK => "港股",
+            Market::Futures => "国际期货",
+            Market::CnFutures => "国内期货",
+            Market::FX => "外汇",
+            Market::Crypto => "加密货币",
+        }
+    }
+
+    pub fn currency_symbol(&self) -> &'static str {
+        match self {
+            Market::US => "$",
+            Market::CN => "¥",
+            Market::HK => "HK$",
+            Market::Futures => "$",
+            Market::CnFutures => "¥",
+            Market::FX => "",
+            Market::Crypto => "",
+        }
+    }
+
+    /// 计价货币代码，外汇的报价货币因货币对而异
+    pub fn currency(&self) -> &'static str {
+        match self {
+            Market::US => "USD",
+            Market::CN => "CNY",
+            Market::HK => "HKD",
+            Market::Futures => "USD",
+            Market::CnFutures => "CNY",
+            Market::FX => "",
+            Market::Crypto => "USDT",
+        }
+    }
+
+    /// 股票代码的计价货币，外汇取货币对的报价货币（USDCNH=X 为 CNH）
+    pub fn symbol_currency(symbol: &str) -> String {
+        match Market::from_symbol(symbol) {
+            Market::FX => {
+                let pair = symbol.to_uppercase();
+                let pair = pair.trim_end_matches("=X");
+                pair.get(3..6).unwrap_or("USD").to_string()
+            }
+            market => market.currency().to_string(),
+        }
+    }
+
+    pub fn flag_emoji(&self) -> &'static str {
+        match self {
+            Market::US => "🇺🇸",
+            Market::CN => "🇨🇳",
+            Market::HK => "🇭🇰",
+            Market::Futures => "🛢️",
+            Market::CnFutures => "🏭",
+            Market::FX => "💱",
+            Market::Crypto => "₿",
+        }
+    }
+}
@@ -0,0 +1,577 @@
+use crate::config::BacktestConfig;
+use crate::models::{AlertCondition, AlertIndicator, Market};
+use crate::services::{ParamSet, PriceAdjustment, PriceData, StrategyAnalyzer};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// 实盘策略分析使用的K线数，回测时每根K线只看最近这么多历史
+const ANALYSIS_WINDOW: usize = 250;
+/// 历史数据少于该值时不产生策略信号，与实盘分析一致
+const MIN_ANALYSIS_BARS: usize = 60;
+
+/// 回测策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BacktestStrategy {
+    /// 涨停回踩
+    LimitUpPullback,
+    /// 底部突破
+    BottomBreakout,
+    /// 预警规则：价格或指标满足条件时买入
+    Alert,
+}
+
+impl std::str::FromStr for BacktestStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "limit_up_pullback" => Ok(Self::LimitUpPullback),
+            "bottom_breakout" => Ok(Self::BottomBreakout),
+            "alert" => Ok(Self::Alert),
+            _ => Err(anyhow!(
+                "未知策略 {}，可选 limit_up_pullback、bottom_breakout、alert",
+                s
+            )),
+        }
+    }
+}
+
+impl BacktestStrategy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::LimitUpPullback => "涨停回踩",
+            Self::BottomBreakout => "底部突破",
+            Self::Alert => "预警规则",
+        }
+    }
+}
+
+/// 预警规则：收盘价（或指定指标）由不满足变为满足条件时发出买入信号
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AlertRule {
+    #[serde(default)]
+    pub indicator: Option<AlertIndicator>,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+}
+
+impl AlertRule {
+    fn matches(&self, value: f64) -> bool {
+        match self.condition {
+            AlertCondition::Above => value >= self.threshold,
+            AlertCondition::Below => value <= self.threshold,
+        }
+    }
+}
+
+/// 离场原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    TakeProfit,
+    StopLoss,
+    MaxHolding,
+    /// 回测结束时仍持仓，按最后收盘价平仓
+    EndOfData,
+}
+
+impl ExitReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::TakeProfit => "止盈",
+            Self::StopLoss => "止损",
+            Self::MaxHolding => "到期",
+            Self::EndOfData => "回测结束",
+        }
+    }
+}
+
+/// 一笔完整交易，价格为含滑点的成交价
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestTrade {
+    pub entry_date: String,
+    pub entry_price: f64,
+    pub exit_date: String,
+    pub exit_price: f64,
+    pub shares: f64,
+    /// 买卖佣金与印花税合计
+    pub fees: f64,
+    pub pnl: f64,
+    /// 相对买入成本（含费用）的收益率（%）
+    pub return_percent: f64,
+    pub holding_bars: usize,
+    pub exit_reason: ExitReason,
+}
+
+/// 回测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub symbol: String,
+    pub strategy: BacktestStrategy,
+    pub rule: Option<AlertRule>,
+    pub adjust: PriceAdjustment,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub bars: usize,
+    pub signals: usize,
+    pub final_equity: f64,
+    pub total_return_percent: f64,
+    /// 按每根K线收盘市值计算的最大回撤（%）
+    pub max_drawdown_percent: f64,
+    /// 盈利交易占比（%），没有交易时为 None
+    pub win_rate_percent: Option<f64>,
+    pub total_fees: f64,
+    pub trades: Vec<BacktestTrade>,
+    pub params: BacktestConfig,
+    /// 形态策略使用的参数组，预警规则回测为空
+    pub param_set: Option<ParamSet>,
+}
+
+/// 撮合规则：A股按100股一手、T+1、卖出收印花税，加密货币可买卖零碎数量
+#[derive(Debug, Clone, Copy)]
+struct MarketRules {
+    lot_size: Option<f64>,
+    t_plus_one: bool,
+    stamp_duty: bool,
+}
+
+impl MarketRules {
+    fn for_market(market: Market) -> Self {
+        match market {
+            Market::CN => Self {
+                lot_size: Some(100.0),
+                t_plus_one: true,
+                stamp_duty: true,
+            },
+            Market::Crypto | Market::FX => Self {
+                lot_size: None,
+                t_plus_one: false,
+                stamp_duty: false,
+            },
+            _ => Self {
+                lot_size: Some(1.0),
+                t_plus_one: false,
+                stamp_duty: false,
+            },
+        }
+    }
+}
+
+struct Position {
+    entry_index: usize,
+    /// 买入K线的日期，T+1 与最长持有天数按交易日而不是K线数判断
+    entry_date: String,
+    /// 买入后已经过的交易日数
+    held_days: usize,
+    entry_price: f64,
+    shares: f64,
+    cost: f64,
+    entry_fees: f64,
+}
+
+/// 模拟结果
+struct Simulation {
+    trades: Vec<BacktestTrade>,
+    final_equity: f64,
+    max_drawdown_percent: f64,
+}
+
+/// 回测引擎：逐根K线回放历史价格，信号在收盘时产生、次日开盘成交
+pub struct Backtester {
+    analyzer: StrategyAnalyzer,
+    config: BacktestConfig,
+}
+
+impl Backtester {
+    pub fn new(analyzer: StrategyAnalyzer, config: BacktestConfig) -> Self {
+        Self { analyzer, config }
+    }
+
+    /// 回测最近 `days` 根日K线
+    pub async fn run(
+        &self,
+        symbol: &str,
+        strategy: BacktestStrategy,
+        rule: Option<AlertRule>,
+        days: i32,
+        adjustment: PriceAdjustment,
+    ) -> Result<BacktestReport> {
+        if strategy == BacktestStrategy::Alert && rule.is_none() {
+            return Err(anyhow!("预警规则回测需要指定条件与阈值"));
+        }
+
+        let prices = self
+            .analyzer
+            .get_price_history(symbol, days, adjustment)
+            .await?;
+        let entries = self.entry_signals(symbol, strategy, rule, &prices).await?;
+        let rules = MarketRules::for_market(Market::from_symbol(symbol));
+        let simulation = simulate(&prices, &entries, rules, &self.config);
+
+        let wins = simulation.trades.iter().filter(|t| t.pnl > 0.0).count();
+        let report = BacktestReport {
+            symbol: symbol.to_string(),
+            strategy,
+            rule: rule.filter(|_| strategy == BacktestStrategy::Alert),
+            adjust: adjustment,
+            start_date: prices.first().map(|p| p.date.clone()),
+            end_date: prices.last().map(|p| p.date.clone()),
+            bars: prices.len(),
+            signals: entries.iter().filter(|e| **e).count(),
+            final_equity: simulation.final_equity,
+            total_return_percent: (simulation.final_equity / self.config.initial_capital - 1.0)
+                * 100.0,
+            max_drawdown_percent: simulation.max_drawdown_percent,
+            win_rate_percent: (!simulation.trades.is_empty())
+                .then(|| wins as f64 / simulation.trades.len() as f64 * 100.0),
+            total_fees: simulation.trades.iter().fold(0.0, |sum, t| sum + t.fees),
+            trades: simulation.trades,
+            params: self.config.clone(),
+            param_set: (strategy != BacktestStrategy::Alert)
+                .then(|| self.analyzer.param_set(symbol)),
+        };
+
+        info!(
+            "回测完成: {} {} {}根K线，{}笔交易，收益{:.2}%",
+            symbol,
+            strategy.label(),
+            report.bars,
+            report.trades.len(),
+            report.total_return_percent
+        );
+        Ok(report)
+    }
+
+    /// 每根K线收盘时是否产生买入信号，只使用当根及之前的数据
+    async fn entry_signals(
+        &self,
+        symbol: &str,
+        strategy: BacktestStrategy,
+        rule: Option<AlertRule>,
+        prices: &[PriceData],
+    ) -> Result<Vec<bool>> {
+        let mut entries = Vec::with_capacity(prices.len());
+        match (strategy, rule) {
+            (BacktestStrategy::Alert, Some(rule)) => {
+                let float_shares = self.analyzer.float_shares(symbol).await?;
+                let values = self.analyzer.calculate_indicators(prices, float_shares);
+                let mut was_matched = false;
+                for values in &values {
+                    let value = match rule.indicator {
+                        Some(indicator) => values.value(indicator),
+                        None => Some(values.close),
+                    };
+                    let matched = value.is_some_and(|v| rule.matches(v));
+                    entries.push(matched && !was_matched);
+                    was_matched = matched;
+                }
+            }
+            _ => {
+                for end in 1..=prices.len() {
+                    let window = &prices[end.saturating_sub(ANALYSIS_WINDOW)..end];
+                    let signal = if window.len() < MIN_ANALYSIS_BARS {
+                        false
+                    } else if strategy == BacktestStrategy::LimitUpPullback {
+                        self.analyzer
+                            .analyze_limit_up_pullback(symbol, window)
+                            .await?
+                            .is_some()
+                    } else {
+                        self.analyzer
+                            .analyze_bottom_breakout(symbol, window)
+                            .await?
+                            .is_some()
+                    };
+                    entries.push(signal);
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// 撮合模拟：空仓时收盘信号于次日开盘全仓买入，持仓时按止损、止盈、到期顺序检查离场
+fn simulate(
+    prices: &[PriceData],
+    entries: &[bool],
+    rules: MarketRules,
+    config: &BacktestConfig,
+) -> Simulation {
+    let slippage = config.slippage_percent / 100.0;
+    let commission =
+        |amount: f64| (amount * config.commission_percent / 100.0).max(config.min_commission);
+
+    let mut cash = config.initial_capital;
+    let mut position: Option<Position> = None;
+    let mut pending_entry = false;
+    let mut trades = Vec::new();
+    let mut peak = cash;
+    let mut max_drawdown = 0.0_f64;
+
+    let mut close_position = |position: Position, index: usize, price: f64, reason| {
+        let exit_price = price * (1.0 - slippage);
+        let proceeds = position.shares * exit_price;
+        let stamp_duty = if rules.stamp_duty {
+            proceeds * config.stamp_duty_percent / 100.0
+        } else {
+            0.0
+        };
+        let exit_fees = commission(proceeds) + stamp_duty;
+        let pnl = proceeds - exit_fees - position.cost - position.entry_fees;
+        trades.push(BacktestTrade {
+            entry_date: position.entry_date,
+            entry_price: position.entry_price,
+            exit_date: prices[index].date.clone(),
+            exit_price,
+            shares: position.shares,
+            fees: position.entry_fees + exit_fees,
+            pnl,
+            return_percent: pnl / (position.cost + position.entry_fees) * 100.0,
+            holding_bars: index - position.entry_index,
+            exit_reason: reason,
+        });
+        proceeds - exit_fees
+    };
+
+    for (i, bar) in prices.iter().enumerate() {
+        if let Some(p) = position.as_mut() {
+            if i > 0 && prices[i - 1].date != bar.date && bar.date > p.entry_date {
+                p.held_days += 1;
+            }
+        }
+
+        if pending_entry {
+            pending_entry = false;
+            let entry_price = bar.open * (1.0 + slippage);
+            let shares = position_size(cash, entry_price, rules.lot_size, config);
+            if shares > 0.0 {
+                let cost = shares * entry_price;
+                let entry_fees = commission(cost);
+                cash -= cost + entry_fees;
+                position = Some(Position {
+                    entry_index: i,
+                    entry_date: bar.date.clone(),
+                    held_days: 0,
+                    entry_price,
+                    shares,
+                    cost,
+                    entry_fees,
+                });
+            }
+        }
+
+        // A股 T+1：买入当天不能卖出，同一天的多根K线都算买入当天
+        let sellable = position
+            .as_ref()
+            .filter(|p| !rules.t_plus_one || bar.date > p.entry_date);
+        if let Some(p) = sellable {
+            let stop = p.entry_price * (1.0 - config.stop_loss_percent / 100.0);
+            let target = p.entry_price * (1.0 + config.take_profit_percent / 100.0);
+            // 跳空越过止损/止盈价时按开盘价成交；同一根K线同时触及时保守地按止损处理
+            let exit = if config.stop_loss_percent > 0.0 && bar.low <= stop {
+                Some((bar.open.min(stop), ExitReason::StopLoss))
+            } else if config.take_profit_percent > 0.0 && bar.high >= target {
+                Some((bar.open.max(target), ExitReason::TakeProfit))
+            } else if config.max_holding_days > 0 && p.held_days >= config.max_holding_days {
+                Some((bar.close, ExitReason::MaxHolding))
+            } else {
+                None
+            };
+            if let Some((price, reason)) = exit {
+                if let Some(p) = position.take() {
+                    cash += close_position(p, i, price, reason);
+                }
+            }
+        }
+
+        let equity = cash + position.as_ref().map_or(0.0, |p| p.shares * bar.close);
+        peak = peak.max(equity);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - equity) / peak * 100.0);
+        }
+
+        if position.is_none() && entries.get(i).copied().unwrap_or(false) {
+            pending_entry = true;
+        }
+    }
+
+    if let (Some(p), Some(last)) = (position.take(), prices.last()) {
+        cash += close_position(p, prices.len() - 1, last.close, ExitReason::EndOfData);
+    }
+
+    Simulation {
+        trades,
+        final_equity: cash,
+        max_drawdown_percent: max_drawdown,
+    }
+}
+
+/// 可用资金能买入的数量，扣除佣金后按整手取整
+fn position_size(cash: f64, price: f64, lot_size: Option<f64>, config: &BacktestConfig) -> f64 {
+    if price <= 0.0 || cash <= 0.0 {
+        return 0.0;
+    }
+    let rate = config.commission_percent / 100.0;
+    let fee = |shares: f64| (shares * price * rate).max(config.min_commission);
+    match lot_size {
+        Some(lot) => {
+            let mut shares = (cash / (price * (1.0 + rate)) / lot).floor() * lot;
+            while shares > 0.0 && shares * price + fee(shares) > cash {
+                shares -= lot;
+            }
+            shares
+        }
+        None => ((cash - config.min_commission) / (price * (1.0 + rate))).max(0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(date: &str, open: f64, high: f64, low: f64, close: f64) -> PriceData {
+        PriceData {
+            date: date.to_string(),
+            open,
+            high,
+            low,
+            close,
+            volume: 1000,
+            change_percent: None,
+        }
+    }
+
+    fn config() -> BacktestConfig {
+        BacktestConfig {
+            initial_capital: 10_000.0,
+            slippage_percent: 0.0,
+            commission_percent: 0.0,
+            min_commission: 0.0,
+            stamp_duty_percent: 0.1,
+            take_profit_percent: 10.0,
+            stop_loss_percent: 5.0,
+            max_holding_days: 0,
+        }
+    }
+
+    #[test]
+    fn test_t_plus_one_and_stamp_duty() {
+        // 第1天收盘发出信号，第2天开盘10元买入；当天即跌破止损价，但A股 T+1 只能第3天卖出
+        let prices = vec![
+            bar("d1", 10.0, 10.0, 10.0, 10.0),
+            bar("d2", 10.0, 10.0, 9.0, 9.2),
+            bar("d3", 9.3, 9.4, 9.1, 9.2),
+        ];
+        let entries = [true, false, false];
+
+        let cn = simulate(
+            &prices,
+            &entries,
+            MarketRules::for_market(Market::CN),
+            &config(),
+        );
+        let trade = &cn.trades[0];
+        assert_eq!((trade.shares, trade.exit_date.as_str()), (1000.0, "d3"));
+        assert_eq!(trade.exit_reason, ExitReason::StopLoss);
+        // 跳空低开于止损价之下，按开盘价9.3成交，印花税千分之一
+        assert_eq!(trade.exit_price, 9.3);
+        assert!((trade.fees - 9.3).abs() < 1e-9);
+        assert!((cn.final_equity - (10_000.0 - 700.0 - 9.3)).abs() < 1e-9);
+        assert!((cn.max_drawdown_percent - 8.0).abs() < 1e-9);
+
+        // 美股可以当天止损，按止损价9.5成交且没有印花税
+        let us = simulate(
+            &prices,
+            &entries,
+            MarketRules::for_market(Market::US),
+            &config(),
+        );
+        let trade = &us.trades[0];
+        assert_eq!((trade.exit_date.as_str(), trade.exit_price), ("d2", 9.5));
+        assert_eq!(trade.fees, 0.0);
+        assert_eq!(us.final_equity, 9_500.0);
+    }
+
+    #[test]
+    fn test_t_plus_one_uses_trading_dates() {
+        // 同一天有两根K线：买入后第二根K线跌破止损价，但仍是买入当天，A股不能卖出
+        let prices = vec![
+            bar("d1", 10.0, 10.0, 10.0, 10.0),
+            bar("d2", 10.0, 10.0, 10.0, 10.0),
+            bar("d2", 10.0, 10.0, 9.0, 9.2),
+            bar("d3", 9.3, 9.4, 9.1, 9.2),
+        ];
+        let sim = simulate(
+            &prices,
+            &[true, false, false, false],
+            MarketRules::for_market(Market::CN),
+            &config(),
+        );
+        let trade = &sim.trades[0];
+        assert_eq!(
+            (trade.entry_date.as_str(), trade.exit_date.as_str()),
+            ("d2", "d3")
+        );
+        assert_eq!(trade.exit_reason, ExitReason::StopLoss);
+
+        // 最长持有天数同样按交易日计算，同一天的多根K线只算一天
+        let mut config = config();
+        config.take_profit_percent = 0.0;
+        config.stop_loss_percent = 0.0;
+        config.max_holding_days = 1;
+        let prices = vec![
+            bar("d1", 10.0, 10.0, 10.0, 10.0),
+            bar("d2", 10.0, 10.0, 10.0, 10.0),
+            bar("d3", 10.0, 10.0, 10.0, 10.1),
+            bar("d3", 10.0, 10.0, 10.0, 10.2),
+            bar("d4", 10.0, 10.0, 10.0, 10.3),
+        ];
+        let sim = simulate(
+            &prices,
+            &[true, false, false, false, false],
+            MarketRules::for_market(Market::US),
+            &config,
+        );
+        let trade = &sim.trades[0];
+        assert_eq!(trade.exit_date, "d3");
+        assert_eq!(trade.exit_reason, ExitReason::MaxHolding);
+        assert_eq!(trade.exit_price, 10.1);
+    }
+
+    #[test]
+    fn test_slippage_fees_and_lot_size() {
+        let mut config = config();
+        config.slippage_percent = 1.0;
+        config.commission_percent = 0.1;
+        config.min_commission = 5.0;
+        config.take_profit_percent = 0.0;
+        config.stop_loss_percent = 0.0;
+        config.max_holding_days = 1;
+
+        let prices = vec![
+            bar("d1", 10.0, 10.0, 10.0, 10.0),
+            bar("d2", 10.0, 10.5, 10.0, 10.0),
+            bar("d3", 11.0, 11.0, 11.0, 11.0),
+        ];
+        let sim = simulate(
+            &prices,
+            &[true, false, false],
+            MarketRules::for_market(Market::CN),
+            &config,
+        );
+        let trade = &sim.trades[0];
+        // 买入价10.1，扣除佣金后整手为900股；次日按收盘价11减滑点卖出
+        assert_eq!(trade.shares, 900.0);
+        assert!((trade.entry_price - 10.1).abs() < 1e-9);
+        assert!((trade.exit_price - 10.89).abs() < 1e-9);
+        assert_eq!(trade.exit_reason, ExitReason::MaxHolding);
+        let buy_fee = 9090.0 * 0.001;
+        let sell_amount = 900.0 * 10.89;
+        let sell_fee = sell_amount * 0.001 + sell_amount * 0.001;
+        assert!((trade.fees - (buy_fee + sell_fee)).abs() < 1e-9);
+        assert!((trade.pnl - (sell_amount - 9090.0 - trade.fees)).abs() < 1e-9);
+        assert!(trade.pnl > 0.0);
+    }
+}
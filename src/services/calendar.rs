@@ -0,0 +1,693 @@
+use crate::models::{Market, MarketSession};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// 查找下一次/上一次开收盘时最多向前/向后搜索的天数（春节长假不超过10天）
+const SEARCH_DAYS: u64 = 30;
+/// 隔夜时段最早在所属交易日之前几天开盘（周五夜盘属于下周一）
+const MAX_LEAD_DAYS: u64 = 3;
+
+// 内置日历数据，数据目录中没有对应文件时使用
+const BUILTIN_US: &str = include_str!("../../config/calendars/us.toml");
+const BUILTIN_CN: &str = include_str!("../../config/calendars/cn.toml");
+const BUILTIN_HK: &str = include_str!("../../config/calendars/hk.toml");
+const BUILTIN_FUTURES: &str = include_str!("../../config/calendars/futures.toml");
+const BUILTIN_CN_FUTURES: &str = include_str!("../../config/calendars/cn_futures.toml");
+const BUILTIN_FX: &str = include_str!("../../config/calendars/fx.toml");
+const BUILTIN_CRYPTO: &str = include_str!("../../config/calendars/crypto.toml");
+
+#[derive(Debug, Deserialize)]
+struct CalendarFile {
+    name: String,
+    timezone: String,
+    #[serde(default)]
+    always_open: bool,
+    covered_through: Option<NaiveDate>,
+    #[serde(default)]
+    sessions: Vec<SessionFile>,
+    #[serde(default)]
+    holidays: Vec<HolidayFile>,
+    #[serde(default)]
+    early_closes: Vec<EarlyCloseFile>,
+    #[serde(default)]
+    extended_hours: Option<ExtendedHoursFile>,
+}
+
+/// 盘前盘后交易时间：盘前从 `pre_open` 到常规开盘，盘后从常规收盘到 `post_close`
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct ExtendedHoursFile {
+    #[serde(deserialize_with = "deserialize_time")]
+    pre_open: NaiveTime,
+    #[serde(deserialize_with = "deserialize_time")]
+    post_close: NaiveTime,
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionFile {
+    #[serde(deserialize_with = "deserialize_time")]
+    open: NaiveTime,
+    #[serde(deserialize_with = "deserialize_time")]
+    close: NaiveTime,
+    #[serde(default)]
+    from: SessionStart,
+}
+
+/// 交易时段在哪一天开盘；收盘时间不晚于开盘时间的时段跨越午夜，次日收盘
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum SessionStart {
+    /// 交易日当天开盘
+    #[default]
+    SameDay,
+    /// 前一自然日开盘（如 CME Globex 周日晚间开盘属于周一）
+    PreviousDay,
+    /// 前一交易日晚间开盘（如上期所夜盘），节假日前最后一个交易日不开夜盘
+    PreviousTradingDay,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Session {
+    open: NaiveTime,
+    close: NaiveTime,
+    from: SessionStart,
+}
+
+#[derive(Debug, Deserialize)]
+struct HolidayFile {
+    date: NaiveDate,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EarlyCloseFile {
+    date: NaiveDate,
+    #[serde(deserialize_with = "deserialize_time")]
+    close: NaiveTime,
+}
+
+fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&value, "%H:%M").map_err(serde::de::Error::custom)
+}
+
+/// 市场当前所处的交易阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// 连续竞价中
+    Open,
+    /// 同一天内两个交易时段之间（A股午间休市、期货盘中休息）
+    Break,
+    /// 非交易时间、周末或节假日
+    Closed,
+    /// 全天候交易
+    AlwaysOpen,
+}
+
+/// 某一时刻的市场状态
+#[derive(Debug, Clone)]
+pub struct MarketStatus {
+    pub state: SessionState,
+    /// 当天为节假日时的节日名称
+    pub holiday: Option<String>,
+    pub next_open: Option<DateTime<Utc>>,
+    pub next_close: Option<DateTime<Utc>>,
+}
+
+/// 单个市场的交易日历
+#[derive(Debug, Clone)]
+pub struct TradingCalendar {
+    name: String,
+    timezone: Tz,
+    always_open: bool,
+    covered_through: Option<NaiveDate>,
+    sessions: Vec<Session>,
+    holidays: HashMap<NaiveDate, String>,
+    early_closes: HashMap<NaiveDate, NaiveTime>,
+    extended_hours: Option<ExtendedHoursFile>,
+}
+
+impl TradingCalendar {
+    /// 从 TOML 格式的日历数据解析
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let file: CalendarFile = toml::from_str(content)?;
+        let timezone: Tz = file
+            .timezone
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid timezone {}: {}", file.timezone, e))?;
+
+        if !file.always_open && file.sessions.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Calendar {} has no trading sessions",
+                file.name
+            ));
+        }
+
+        // 前一天开盘的时段排在当天时段之前，保证各时段按时间先后排列
+        let mut sessions: Vec<Session> = file
+            .sessions
+            .iter()
+            .map(|s| Session {
+                open: s.open,
+                close: s.close,
+                from: s.from,
+            })
+            .collect();
+        sessions.sort_by_key(|s| (s.from == SessionStart::SameDay, s.open));
+
+        Ok(Self {
+            name: file.name,
+            timezone,
+            always_open: file.always_open,
+            covered_through: file.covered_through,
+            sessions,
+            holidays: file
+                .holidays
+                .into_iter()
+                .map(|h| (h.date, h.name))
+                .collect(),
+            early_closes: file
+                .early_closes
+                .into_iter()
+                .map(|e| (e.date, e.close))
+                .collect(),
+            extended_hours: file.extended_hours,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_always_open(&self) -> bool {
+        self.always_open
+    }
+
+    /// 日历数据覆盖的最后日期，之后的节假日未知
+    pub fn covered_through(&self) -> Option<NaiveDate> {
+        self.covered_through
+    }
+
+    /// 当地日期对应的节假日名称
+    pub fn holiday(&self, date: NaiveDate) -> Option<&str> {
+        self.holidays.get(&date).map(String::as_str)
+    }
+
+    /// 当地日期是否为交易日
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        if self.always_open {
+            return true;
+        }
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains_key(&date)
+    }
+
+    /// 交易日（当地日期）的各交易时段（UTC），已考虑提前收盘、夏令时与隔夜时段
+    pub fn sessions_on(&self, date: NaiveDate) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        if self.always_open || !self.is_trading_day(date) {
+            return Vec::new();
+        }
+
+        let early_close = self.early_closes.get(&date).copied();
+        self.sessions
+            .iter()
+            .filter_map(|session| {
+                let open_date = match session.from {
+                    SessionStart::SameDay => date,
+                    SessionStart::PreviousDay => date.pred_opt()?,
+                    SessionStart::PreviousTradingDay => self.previous_night_session_date(date)?,
+                };
+                let overnight = session.close <= session.open;
+                let close_date = if overnight {
+                    open_date.succ_opt()?
+                } else {
+                    open_date
+                };
+
+                // 提前收盘只影响当天收盘的时段
+                let close = match early_close {
+                    Some(_) if close_date != date => session.close,
+                    Some(early) if !overnight && early <= session.open => return None,
+                    Some(early) => session.close.min(early),
+                    None => session.close,
+                };
+                Some((
+                    self.to_utc(open_date, session.open)?,
+                    self.to_utc(close_date, close)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// 指定时刻是否处于交易时段
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        if self.always_open {
+            return true;
+        }
+        let today = self.local_date(at);
+        (0..=MAX_LEAD_DAYS + 1)
+            .filter_map(|offset| (today - Days::new(1)).checked_add_days(Days::new(offset)))
+            .flat_map(|date| self.sessions_on(date))
+            .any(|(open, close)| open <= at && at < close)
+    }
+
+    /// 指定时刻之后（含）的下一次开盘时间
+    pub fn next_open(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.always_open {
+            return None;
+        }
+        self.upcoming_sessions(at)
+            .map(|(open, _)| open)
+            .find(|&open| open >= at)
+    }
+
+    /// 当前交易时段的收盘时间，未开盘时为下一个交易时段的收盘时间
+    pub fn next_close(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.always_open {
+            return None;
+        }
+        self.upcoming_sessions(at)
+            .map(|(_, close)| close)
+            .find(|&close| close > at)
+    }
+
+    /// 指定时刻之前（含）最近一次收盘时间
+    pub fn last_close(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.always_open {
+            return None;
+        }
+        // 夜盘属于之后的交易日，因此从之后几天开始往回找
+        let latest = self.local_date(at) + Days::new(MAX_LEAD_DAYS);
+        (0..=SEARCH_DAYS + MAX_LEAD_DAYS)
+            .filter_map(|offset| latest.checked_sub_days(Days::new(offset)))
+            .flat_map(|date| self.sessions_on(date).into_iter().rev())
+            .map(|(_, close)| close)
+            .find(|&close| close <= at)
+    }
+
+    /// 指定时刻之前（含）最近一个交易日的收盘时间，不含午休等日内休市
+    pub fn last_day_close(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.always_open {
+            return None;
+        }
+        let latest = self.local_date(at) + Days::new(MAX_LEAD_DAYS);
+        (0..=SEARCH_DAYS + MAX_LEAD_DAYS)
+            .filter_map(|offset| latest.checked_sub_days(Days::new(offset)))
+            .filter_map(|date| {
+                self.sessions_on(date)
+                    .into_iter()
+                    .map(|(_, close)| close)
+                    .max()
+            })
+            .find(|&close| close <= at)
+    }
+
+    /// 当地交易日已完成的交易时间占比（0~1），休市日与全天候市场返回 None
+    pub fn session_progress(&self, at: DateTime<Utc>) -> Option<f64> {
+        let sessions = self.sessions_on(self.local_date(at));
+        let total: i64 = sessions
+            .iter()
+            .map(|(open, close)| (*close - *open).num_seconds())
+            .sum();
+        if total <= 0 {
+            return None;
+        }
+        let elapsed: i64 = sessions
+            .iter()
+            .map(|(open, close)| (at.clamp(*open, *close) - *open).num_seconds())
+            .sum();
+        Some(elapsed as f64 / total as f64)
+    }
+
+    /// 指定时刻所处的盘前或盘后时段，常规交易时段与无盘前盘后交易的市场返回 None
+    pub fn extended_session(&self, at: DateTime<Utc>) -> Option<MarketSession> {
+        self.extended_windows_on(self.local_date(at))
+            .into_iter()
+            .find(|(_, start, end)| *start <= at && at < *end)
+            .map(|(session, _, _)| session)
+    }
+
+    /// 指定时刻之后的下一个盘前或盘后时段开始时间
+    pub fn next_extended_open(&self, at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let today = self.local_date(at);
+        (0..=SEARCH_DAYS)
+            .filter_map(|offset| today.checked_add_days(Days::new(offset)))
+            .flat_map(|date| self.extended_windows_on(date))
+            .map(|(_, start, _)| start)
+            .find(|&start| start > at)
+    }
+
+    /// 交易日的盘前、盘后时段（UTC）；提前收盘日的盘后时段随收盘提前，时长不变
+    fn extended_windows_on(
+        &self,
+        date: NaiveDate,
+    ) -> Vec<(MarketSession, DateTime<Utc>, DateTime<Utc>)> {
+        let Some(extended) = self.extended_hours else {
+            return Vec::new();
+        };
+        let sessions = self.sessions_on(date);
+        let (Some((open, _)), Some((_, close)), Some(last)) =
+            (sessions.first(), sessions.last(), self.sessions.last())
+        else {
+            return Vec::new();
+        };
+
+        let mut windows = Vec::new();
+        if let Some(pre_open) = self.to_utc(date, extended.pre_open) {
+            windows.push((MarketSession::PreMarket, pre_open, *open));
+        }
+        let post_duration = extended.post_close - last.close;
+        windows.push((MarketSession::AfterHours, *close, *close + post_duration));
+        windows
+    }
+
+    /// 指定时刻的市场状态
+    pub fn status(&self, at: DateTime<Utc>) -> MarketStatus {
+        if self.always_open {
+            return MarketStatus {
+                state: SessionState::AlwaysOpen,
+                holiday: None,
+                next_open: None,
+                next_close: None,
+            };
+        }
+
+        let today = self.local_date(at);
+        let next_open = self.next_open(at);
+        // 上一次收盘与下一次开盘在同一天内视为盘中休市，否则为收盘
+        let same_day =
+            |time: Option<DateTime<Utc>>| time.is_some_and(|t| self.local_date(t) == today);
+        let state = if self.is_open(at) {
+            SessionState::Open
+        } else if same_day(self.last_close(at)) && same_day(next_open) {
+            SessionState::Break
+        } else {
+            SessionState::Closed
+        };
+
+        MarketStatus {
+            state,
+            holiday: self.holiday(today).map(str::to_string),
+            next_open,
+            next_close: self.next_close(at),
+        }
+    }
+
+    /// 夜盘开盘日期：前一交易日，期间有节假日（节前最后一个交易日）时不开夜盘
+    fn previous_night_session_date(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let previous = (1..=SEARCH_DAYS)
+            .filter_map(|offset| date.checked_sub_days(Days::new(offset)))
+            .find(|&d| self.is_trading_day(d))?;
+        let holiday_between = previous
+            .iter_days()
+            .skip(1)
+            .take_while(|&d| d < date)
+            .any(|d| self.holidays.contains_key(&d));
+        (!holiday_between).then_some(previous)
+    }
+
+    fn upcoming_sessions(
+        &self,
+        at: DateTime<Utc>,
+    ) -> impl Iterator<Item = (DateTime<Utc>, DateTime<Utc>)> + '_ {
+        // 从前一天开始，覆盖前一天开盘、尚未收盘的隔夜时段
+        let yesterday = self.local_date(at) - Days::new(1);
+        (0..=SEARCH_DAYS + 1)
+            .filter_map(move |offset| yesterday.checked_add_days(Days::new(offset)))
+            .flat_map(|date| self.sessions_on(date))
+    }
+
+    fn local_date(&self, at: DateTime<Utc>) -> NaiveDate {
+        at.with_timezone(&self.timezone).date_naive()
+    }
+
+    fn to_utc(&self, date: NaiveDate, time: NaiveTime) -> Option<DateTime<Utc>> {
+        self.timezone
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+/// 各市场的交易日历
+#[derive(Debug, Clone)]
+pub struct TradingCalendars {
+    us: TradingCalendar,
+    cn: TradingCalendar,
+    hk: TradingCalendar,
+    futures: TradingCalendar,
+    cn_futures: TradingCalendar,
+    fx: TradingCalendar,
+    crypto: TradingCalendar,
+}
+
+impl TradingCalendars {
+    /// 从数据目录加载日历（us.toml、cn.toml、hk.toml、futures.toml、cn_futures.toml、fx.toml、crypto.toml），缺失的文件使用内置数据
+    pub fn load(data_dir: impl AsRef<Path>) -> Result<Self> {
+        let data_dir = data_dir.as_ref();
+        let calendars = Self {
+            us: load_calendar(data_dir, "us.toml", BUILTIN_US)?,
+            cn: load_calendar(data_dir, "cn.toml", BUILTIN_CN)?,
+            hk: load_calendar(data_dir, "hk.toml", BUILTIN_HK)?,
+            futures: load_calendar(data_dir, "futures.toml", BUILTIN_FUTURES)?,
+            cn_futures: load_calendar(data_dir, "cn_futures.toml", BUILTIN_CN_FUTURES)?,
+            fx: load_calendar(data_dir, "fx.toml", BUILTIN_FX)?,
+            crypto: load_calendar(data_dir, "crypto.toml", BUILTIN_CRYPTO)?,
+        };
+
+        let today = Utc::now().date_naive();
+        for calendar in [
+            &calendars.us,
+            &calendars.cn,
+            &calendars.hk,
+            &calendars.futures,
+            &calendars.cn_futures,
+            &calendars.fx,
+        ] {
+            if calendar
+                .covered_through()
+                .is_some_and(|through| through < today + Days::new(SEARCH_DAYS))
+            {
+                warn!(
+                    "Trading calendar {} only covers holidays through {:?}",
+                    calendar.name(),
+                    calendar.covered_through()
+                );
+            }
+        }
+
+        Ok(calendars)
+    }
+
+    /// 仅使用内置日历数据
+    #[cfg(test)]
+    pub fn builtin() -> Self {
+        Self {
+            us: TradingCalendar::from_toml(BUILTIN_US).expect("invalid builtin US calendar"),
+            cn: TradingCalendar::from_toml(BUILTIN_CN).expect("invalid builtin CN calendar"),
+            hk: TradingCalendar::from_toml(BUILTIN_HK).expect("invalid builtin HK calendar"),
+            futures: TradingCalendar::from_toml(BUILTIN_FUTURES)
+                .expect("invalid builtin futures calendar"),
+            cn_futures: TradingCalendar::from_toml(BUILTIN_CN_FUTURES)
+                .expect("invalid builtin CN futures calendar"),
+            fx: TradingCalendar::from_toml(BUILTIN_FX).expect("invalid builtin FX calendar"),
+            crypto: TradingCalendar::from_toml(BUILTIN_CRYPTO)
+                .expect("invalid builtin crypto calendar"),
+        }
+    }
+
+    pub fn for_market(&self, market: &Market) -> &TradingCalendar {
+        match market {
+            Market::US => &self.us,
+            Market::CN => &self.cn,
+            Market::HK => &self.hk,
+            Market::Futures => &self.futures,
+            Market::CnFutures => &self.cn_futures,
+            Market::FX => &self.fx,
+            Market::Crypto => &self.crypto,
+        }
+    }
+}
+
+fn load_calendar(data_dir: &Path, file_name: &str, builtin: &str) -> Result<TradingCalendar> {
+    let path = data_dir.join(file_name);
+    if !path.exists() {
+        return TradingCalendar::from_toml(builtin);
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read trading calendar {}", path.display()))?;
+    let calendar = TradingCalendar::from_toml(&content)
+        .with_context(|| format!("Invalid trading calendar {}", path.display()))?;
+    info!(
+        "Loaded trading calendar {} from {}",
+        calendar.name(),
+        path.display()
+    );
+    Ok(calendar)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_us_holidays_dst_and_early_close() {
+        let calendars = TradingCalendars::builtin();
+        let us = calendars.for_market(&Market::US);
+
+        // 感恩节休市
+        let thanksgiving = us.status(utc("2025-11-27T15:00:00Z"));
+        assert_eq!(thanksgiving.state, SessionState::Closed);
+        assert_eq!(thanksgiving.holiday.as_deref(), Some("感恩节"));
+        assert_eq!(thanksgiving.next_open, Some(utc("2025-11-28T14:30:00Z")));
+
+        // 感恩节次日13:00(EST)提前收盘
+        assert_eq!(
+            us.next_close(utc("2025-11-28T15:00:00Z")),
+            Some(utc("2025-11-28T18:00:00Z"))
+        );
+        assert!(!us.is_open(utc("2025-11-28T19:00:00Z")));
+
+        // 冬令时 14:30 UTC 开盘，夏令时 13:30 UTC 开盘
+        assert!(!us.is_open(utc("2025-01-06T14:00:00Z")));
+        assert!(us.is_open(utc("2025-03-10T14:00:00Z")));
+    }
+
+    #[test]
+    fn test_us_extended_hours() {
+        let calendars = TradingCalendars::builtin();
+        let us = calendars.for_market(&Market::US);
+
+        // 04:00-09:30 盘前，16:00-20:00 盘后（EST）
+        assert_eq!(us.extended_session(utc("2025-01-06T08:59:00Z")), None);
+        assert_eq!(
+            us.extended_session(utc("2025-01-06T09:00:00Z")),
+            Some(MarketSession::PreMarket)
+        );
+        assert_eq!(us.extended_session(utc("2025-01-06T15:00:00Z")), None);
+        assert_eq!(
+            us.extended_session(utc("2025-01-06T21:30:00Z")),
+            Some(MarketSession::AfterHours)
+        );
+        assert_eq!(us.extended_session(utc("2025-01-07T01:00:00Z")), None);
+
+        // 感恩节次日13:00提前收盘，盘后到17:00；感恩节当天没有盘前盘后
+        assert_eq!(
+            us.extended_session(utc("2025-11-28T21:30:00Z")),
+            Some(MarketSession::AfterHours)
+        );
+        assert_eq!(us.extended_session(utc("2025-11-28T22:30:00Z")), None);
+        assert_eq!(us.extended_session(utc("2025-11-27T12:00:00Z")), None);
+        assert_eq!(
+            us.next_extended_open(utc("2025-11-27T12:00:00Z")),
+            Some(utc("2025-11-28T09:00:00Z"))
+        );
+
+        let cn = calendars.for_market(&Market::CN);
+        assert_eq!(cn.extended_session(utc("2025-01-06T08:00:00Z")), None);
+    }
+
+    #[test]
+    fn test_cn_lunch_break_and_spring_festival() {
+        let calendars = TradingCalendars::builtin();
+        let cn = calendars.for_market(&Market::CN);
+
+        // 北京时间 12:00 午间休市，13:00 开盘
+        let lunch = cn.status(utc("2025-06-03T04:00:00Z"));
+        assert_eq!(lunch.state, SessionState::Break);
+        assert_eq!(lunch.next_open, Some(utc("2025-06-03T05:00:00Z")));
+
+        // 上午两小时、下午两小时，午休期间进度停在一半
+        assert_eq!(cn.session_progress(utc("2025-06-03T02:30:00Z")), Some(0.25));
+        assert_eq!(cn.session_progress(utc("2025-06-03T04:00:00Z")), Some(0.5));
+        assert_eq!(cn.session_progress(utc("2025-06-03T08:00:00Z")), Some(1.0));
+        // 午休不算作当日收盘，上一交易日为端午节前的 5 月 30 日
+        assert_eq!(
+            cn.last_day_close(utc("2025-06-03T04:00:00Z")),
+            Some(utc("2025-05-30T07:00:00Z"))
+        );
+
+        // 春节休市，节后首个交易日开盘
+        let festival = cn.status(utc("2026-02-18T02:00:00Z"));
+        assert_eq!(festival.state, SessionState::Closed);
+        assert_eq!(festival.holiday.as_deref(), Some("春节"));
+        assert_eq!(festival.next_open, Some(utc("2026-02-24T01:30:00Z")));
+        assert_eq!(
+            cn.last_close(utc("2026-02-18T02:00:00Z")),
+            Some(utc("2026-02-13T07:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_hk_half_day_and_christmas() {
+        let calendars = TradingCalendars::builtin();
+        let hk = calendars.for_market(&Market::HK);
+
+        // 香港时间 12:30 午间休市
+        assert_eq!(
+            hk.status(utc("2025-06-03T04:30:00Z")).state,
+            SessionState::Break
+        );
+
+        // 平安夜半日市12:00收盘，圣诞假期后首个交易日开盘
+        assert_eq!(
+            hk.next_close(utc("2025-12-24T02:00:00Z")),
+            Some(utc("2025-12-24T04:00:00Z"))
+        );
+        let christmas_eve = hk.status(utc("2025-12-24T05:30:00Z"));
+        assert_eq!(christmas_eve.state, SessionState::Closed);
+        assert_eq!(christmas_eve.next_open, Some(utc("2025-12-29T01:30:00Z")));
+    }
+
+    #[test]
+    fn test_overnight_futures_sessions() {
+        let calendars = TradingCalendars::builtin();
+
+        // CME Globex 每日17:00-18:00(美东)休息，周五收盘后到周日18:00开盘
+        let futures = calendars.for_market(&Market::Futures);
+        assert!(futures.is_open(utc("2025-06-04T03:00:00Z")));
+        assert_eq!(
+            futures.status(utc("2025-06-04T21:30:00Z")).state,
+            SessionState::Break
+        );
+        let weekend = futures.status(utc("2025-06-06T21:30:00Z"));
+        assert_eq!(weekend.state, SessionState::Closed);
+        assert_eq!(weekend.next_open, Some(utc("2025-06-08T22:00:00Z")));
+
+        // 上期所周五夜盘属于下周一，次日凌晨02:30收盘
+        let shfe = calendars.for_market(&Market::CnFutures);
+        assert!(shfe.is_open(utc("2025-06-06T13:30:00Z")));
+        assert_eq!(
+            shfe.last_close(utc("2025-06-06T19:00:00Z")),
+            Some(utc("2025-06-06T18:30:00Z"))
+        );
+
+        // 国庆节前最后一个交易日不开夜盘
+        assert!(!shfe.is_open(utc("2025-09-30T13:30:00Z")));
+        assert_eq!(
+            shfe.next_open(utc("2025-09-30T13:30:00Z")),
+            Some(utc("2025-10-09T01:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_crypto_always_open() {
+        let calendars = TradingCalendars::builtin();
+        let crypto = calendars.for_market(&Market::Crypto);
+
+        assert!(crypto.is_open(utc("2025-12-25T00:00:00Z")));
+        assert_eq!(
+            crypto.status(utc("2025-12-25T00:00:00Z")).state,
+            SessionState::AlwaysOpen
+        );
+    }
+}
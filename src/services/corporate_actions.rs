@@ -0,0 +1,365 @@
+use crate::models::{Alert, CorporateAction, CorporateActionType, CreateCorporateActionRequest};
+use crate::services::{EmailNotifier, PriceData};
+use anyhow::Result;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// 历史价格复权方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceAdjustment {
+    /// 原始成交价格
+    Raw,
+    /// 前复权：以最新价格为基准调整除权日前的历史价格
+    #[default]
+    Forward,
+}
+
+/// 单根K线的复权因子
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdjustmentFactor {
+    pub price: f64,
+    pub volume: f64,
+}
+
+impl Default for AdjustmentFactor {
+    fn default() -> Self {
+        Self {
+            price: 1.0,
+            volume: 1.0,
+        }
+    }
+}
+
+/// 计算前复权因子
+///
+/// `bars` 为按日期升序排列的 (日期, 原始收盘价)，日期格式为 YYYY-MM-DD。
+/// 每个公司行动以除权日前一根K线的原始收盘价计算因子，作用于除权日之前的所有K线。
+pub fn adjustment_factors(
+    bars: &[(&str, f64)],
+    actions: &[CorporateAction],
+) -> Vec<AdjustmentFactor> {
+    let mut factors = vec![AdjustmentFactor::default(); bars.len()];
+
+    for action in actions {
+        let ex_date = action.ex_date.format("%Y-%m-%d").to_string();
+        let first_after = bars.partition_point(|(date, _)| *date < ex_date.as_str());
+        if first_after == 0 {
+            continue;
+        }
+
+        let previous_close = bars[first_after - 1].1;
+        let price_factor = action.adjustment_factor(previous_close);
+        for factor in &mut factors[..first_after] {
+            factor.price *= price_factor;
+            factor.volume *= action.split_ratio;
+        }
+    }
+
+    factors
+}
+
+/// 对按日期升序排列的K线做前复权，并重算除权日的涨跌幅
+pub fn adjust_prices(prices: &mut [PriceData], actions: &[CorporateAction]) {
+    if actions.is_empty() || prices.is_empty() {
+        return;
+    }
+
+    let bars: Vec<(&str, f64)> = prices.iter().map(|p| (p.date.as_str(), p.close)).collect();
+    let factors = adjustment_factors(&bars, actions);
+
+    let ex_indices: Vec<usize> = actions
+        .iter()
+        .map(|action| {
+            let ex_date = action.ex_date.format("%Y-%m-%d").to_string();
+            prices.partition_point(|p| p.date < ex_date)
+        })
+        .filter(|&i| i > 0 && i < prices.len())
+        .collect();
+
+    for (price, factor) in prices.iter_mut().zip(&factors) {
+        price.open *= factor.price;
+        price.high *= factor.price;
+        price.low *= factor.price;
+        price.close *= factor.price;
+        price.volume = (price.volume as f64 * factor.volume).round() as u64;
+    }
+
+    // 数据源给出的除权日涨跌幅可能基于未复权的前收盘，统一按复权后的前收盘重算
+    for i in ex_indices {
+        let previous_close = prices[i - 1].close;
+        if previous_close > 0.0 {
+            prices[i].change_percent = Some((prices[i].close / previous_close - 1.0) * 100.0);
+        }
+    }
+}
+
+/// 公司行动服务：记录拆股/分红/送转，并在除权后调整活跃预警
+pub struct CorporateActionService {
+    db: SqlitePool,
+}
+
+impl CorporateActionService {
+    pub fn new(db: SqlitePool) -> Self {
+        Self { db }
+    }
+
+    /// 记录一条公司行动
+    pub async fn record(
+        &self,
+        symbol: &str,
+        request: &CreateCorporateActionRequest,
+    ) -> Result<CorporateAction> {
+        let split_ratio = match request.action_type {
+            CorporateActionType::Dividend => 1.0,
+            _ => request.split_ratio.unwrap_or(1.0),
+        };
+        let cash_dividend = request.cash_dividend.unwrap_or(0.0);
+
+        if !split_ratio.is_finite() || split_ratio <= 0.0 {
+            anyhow::bail!("拆股比例必须大于0");
+        }
+        if !cash_dividend.is_finite() || cash_dividend < 0.0 {
+            anyhow::bail!("每股分红不能为负数");
+        }
+        match request.action_type {
+            CorporateActionType::Split if (split_ratio - 1.0).abs() <= f64::EPSILON => {
+                anyhow::bail!("拆股必须提供不等于1的拆股比例");
+            }
+            CorporateActionType::Dividend if cash_dividend == 0.0 => {
+                anyhow::bail!("分红必须提供每股分红金额");
+            }
+            _ => {}
+        }
+
+        let action = sqlx::query_as!(
+            CorporateAction,
+            r#"
+            INSERT INTO corporate_actions (symbol, ex_date, action_type, split_ratio, cash_dividend, description)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING id as "id!", symbol, ex_date, action_type as "action_type: _",
+                      split_ratio, cash_dividend, description, alerts_rescaled_at, created_at
+            "#,
+            symbol,
+            request.ex_date,
+            request.action_type,
+            split_ratio,
+            cash_dividend,
+            request.description,
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        info!(
+            "记录公司行动: {} {} 除权日 {} 比例 {} 分红 {}",
+            action.symbol, action.action_type, action.ex_date, split_ratio, cash_dividend
+        );
+        Ok(action)
+    }
+
+    /// 指定股票的全部公司行动，按除权日升序
+    pub async fn list(&self, symbol: &str) -> Result<Vec<CorporateAction>> {
+        let actions = sqlx::query_as!(
+            CorporateAction,
+            r#"
+            SELECT id as "id!", symbol, ex_date, action_type as "action_type: _",
+                   split_ratio, cash_dividend, description, alerts_rescaled_at, created_at
+            FROM corporate_actions
+            WHERE symbol = ?
+            ORDER BY ex_date ASC
+            "#,
+            symbol
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(actions)
+    }
+
+    /// 调整已到除权日、尚未处理的拆股/送转对应的活跃预警价位，返回调整的预警数量
+    ///
+    /// 只调整除权日之前创建的预警，之后创建的预警本身就是以除权后价格设定的。
+    pub async fn apply_due_rescales(&self, email_notifier: &EmailNotifier) -> Result<usize> {
+        let today = Local::now().date_naive();
+        let due = sqlx::query_as!(
+            CorporateAction,
+            r#"
+            SELECT id as "id!", symbol, ex_date, action_type as "action_type: _",
+                   split_ratio, cash_dividend, description, alerts_rescaled_at, created_at
+            FROM corporate_actions
+            WHERE alerts_rescaled_at IS NULL AND split_ratio != 1.0 AND ex_date <= ?
+            ORDER BY ex_date ASC
+            "#,
+            today
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut rescaled = 0;
+        for action in due {
+            let alerts = self.rescale_alerts(&action).await?;
+            rescaled += alerts.len();
+
+            for (alert, old_price) in &alerts {
+                info!(
+                    "预警 {} ({}) 价位按除权调整: {} -> {}",
+                    alert.id, alert.symbol, old_price, alert.price
+                );
+                if let Err(e) = email_notifier
+                    .send_alert_rescaled_notification(alert, *old_price, &action)
+                    .await
+                {
+                    warn!("预警 {} 价位调整通知发送失败: {}", alert.id, e);
+                }
+            }
+        }
+
+        Ok(rescaled)
+    }
+
+    /// 在一个事务中调整预警价位并标记公司行动已处理，返回 (调整后的预警, 原价位)
+    async fn rescale_alerts(&self, action: &CorporateAction) -> Result<Vec<(Alert, f64)>> {
+        let mut tx = self.db.begin().await?;
+
+        let alerts = sqlx::query_as!(
+            Alert,
+            r#"
+            UPDATE alerts
+            SET price = price / ?, updated_at = CURRENT_TIMESTAMP
+            WHERE symbol = ? AND status = 'active' AND created_at < ? AND indicator IS NULL
+            RETURNING id as "id!", symbol, condition as "condition: _", price,
+                      status as "status: _", created_at, updated_at, triggered_at,
+                      notification_email,
+                      COALESCE(user_id, 'default') as "user_id!", threshold_currency, extended_hours,
+                      indicator as "indicator: _"
+            "#,
+            action.split_ratio,
+            action.symbol,
+            action.ex_date,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE corporate_actions SET alerts_rescaled_at = CURRENT_TIMESTAMP WHERE id = ?",
+            action.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(alerts
+            .into_iter()
+            .map(|alert| {
+                let old_price = alert.price * action.split_ratio;
+                (alert, old_price)
+            })
+            .collect())
+    }
+
+    /// 定期处理到期的拆股/送转
+    pub fn start_rescale_loop(
+        self: Arc<Self>,
+        email_notifier: Arc<EmailNotifier>,
+        check_interval: Duration,
+    ) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                interval.tick().await;
+                match self.apply_due_rescales(&email_notifier).await {
+                    Ok(0) => {}
+                    Ok(count) => info!("按公司行动调整了 {} 个预警价位", count),
+                    Err(e) => error!("处理公司行动预警调整失败: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn bar(date: &str, close: f64, change_percent: Option<f64>) -> PriceData {
+        PriceData {
+            date: date.to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1_000,
+            change_percent,
+        }
+    }
+
+    fn action(ex_date: &str, split_ratio: f64, cash_dividend: f64) -> CorporateAction {
+        CorporateAction {
+            id: 1,
+            symbol: "TEST".to_string(),
+            ex_date: NaiveDate::parse_from_str(ex_date, "%Y-%m-%d").unwrap(),
+            action_type: if cash_dividend > 0.0 && split_ratio == 1.0 {
+                CorporateActionType::Dividend
+            } else {
+                CorporateActionType::Split
+            },
+            split_ratio,
+            cash_dividend,
+            description: None,
+            alerts_rescaled_at: None,
+            created_at: NaiveDate::from_ymd_opt(2025, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_split_and_dividend_forward_adjustment() {
+        let mut prices = vec![
+            bar("2025-06-02", 1000.0, None),
+            bar("2025-06-03", 1010.0, Some(1.0)),
+            // 10拆1：原始数据显示 -90%
+            bar("2025-06-04", 102.0, Some(-89.9)),
+            bar("2025-06-05", 100.0, Some(-1.96)),
+            // 每股分红2元
+            bar("2025-06-06", 99.0, Some(-1.0)),
+        ];
+        let actions = vec![
+            action("2025-06-04", 10.0, 0.0),
+            action("2025-06-06", 1.0, 2.0),
+        ];
+
+        adjust_prices(&mut prices, &actions);
+
+        let dividend_factor = (100.0 - 2.0) / 100.0;
+        assert!((prices[1].close - 101.0 * dividend_factor).abs() < 1e-9);
+        assert!((prices[3].close - 100.0 * dividend_factor).abs() < 1e-9);
+        assert_eq!(prices[4].close, 99.0);
+        assert_eq!(prices[0].volume, 10_000);
+        assert_eq!(prices[3].volume, 1_000);
+
+        // 除权日涨跌幅按复权后的前收盘重算，不再出现假暴跌
+        let split_change = prices[2].change_percent.unwrap();
+        assert!((split_change - (102.0 / 101.0 - 1.0) * 100.0).abs() < 1e-9);
+        let dividend_change = prices[4].change_percent.unwrap();
+        assert!((dividend_change - (99.0 / 98.0 - 1.0) * 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_actions_outside_history_are_ignored() {
+        let mut prices = vec![bar("2025-06-02", 10.0, None), bar("2025-06-03", 10.5, None)];
+        let actions = vec![action("2025-01-02", 2.0, 0.0)];
+
+        adjust_prices(&mut prices, &actions);
+
+        assert_eq!(prices[0].close, 10.0);
+        assert_eq!(prices[1].close, 10.5);
+    }
+}
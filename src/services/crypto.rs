@@ -0,0 +1,302 @@
+use crate::config::CryptoProviderConfig;
+use crate::services::rate_limiter::RateLimiter;
+use crate::utils::raw_response_hash;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+/// 支持的加密货币交易所
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CryptoVenue {
+    /// Binance 风格接口：/api/v3/ticker/24hr、/api/v3/klines
+    Binance,
+    /// OKX 风格接口：/api/v5/market/ticker、/api/v5/market/candles
+    Okx,
+}
+
+impl CryptoVenue {
+    /// 交易所名称，同时作为限流配置与映射表的键
+    pub fn name(&self) -> &'static str {
+        match self {
+            CryptoVenue::Binance => "binance",
+            CryptoVenue::Okx => "okx",
+        }
+    }
+
+    /// 交易所官方接口地址
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            CryptoVenue::Binance => "https://api.binance.com",
+            CryptoVenue::Okx => "https://www.okx.com",
+        }
+    }
+
+    /// 按交易所命名规则由基础币种和报价币种推导交易对名称
+    ///
+    /// 交易所没有美元现货对，USD 报价映射到 USDT；稳定币自身不映射。
+    pub fn default_venue_symbol(&self, base: &str, quote: &str) -> Option<String> {
+        let base = base.to_uppercase();
+        let quote = match quote.to_uppercase().as_str() {
+            "USD" => "USDT".to_string(),
+            other => other.to_string(),
+        };
+        if base == quote || base == "USDT" {
+            return None;
+        }
+        Some(match self {
+            CryptoVenue::Binance => format!("{base}{quote}"),
+            CryptoVenue::Okx => format!("{base}-{quote}"),
+        })
+    }
+}
+
+/// 24小时行情
+#[derive(Debug, Clone)]
+pub struct CryptoTicker {
+    pub last_price: f64,
+    pub volume: f64,
+    pub timestamp: DateTime<Utc>,
+    /// 原始响应摘要
+    pub raw_hash: String,
+}
+
+/// 日K线
+#[derive(Debug, Clone)]
+pub struct CryptoKline {
+    pub date: NaiveDate,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    /// 所在响应的原始摘要
+    pub raw_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceTicker {
+    #[serde(rename = "lastPrice")]
+    last_price: String,
+    volume: String,
+    #[serde(rename = "closeTime")]
+    close_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceError {
+    code: i64,
+    msg: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OkxResponse<T> {
+    code: String,
+    msg: String,
+    data: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OkxTicker {
+    last: String,
+    #[serde(rename = "vol24h")]
+    vol_24h: String,
+    ts: String,
+}
+
+/// 加密货币交易所行情客户端
+pub struct CryptoExchangeClient {
+    client: Client,
+    venue: CryptoVenue,
+    base_url: String,
+    rate_limiter: Arc<RateLimiter>,
+}
+
+impl CryptoExchangeClient {
+    pub fn new(config: &CryptoProviderConfig, rate_limiter: Arc<RateLimiter>) -> Result<Self> {
+        Ok(Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(config.request_timeout_secs))
+                .build()?,
+            venue: config.venue,
+            base_url: config
+                .base_url
+                .as_deref()
+                .unwrap_or(config.venue.default_base_url())
+                .trim_end_matches('/')
+                .to_string(),
+            rate_limiter,
+        })
+    }
+
+    pub fn venue(&self) -> CryptoVenue {
+        self.venue
+    }
+
+    /// 获取最新成交价与24小时成交量
+    pub async fn fetch_ticker(&self, venue_symbol: &str) -> Result<CryptoTicker> {
+        info!(
+            "Fetching crypto ticker for {} from {}",
+            venue_symbol,
+            self.venue.name()
+        );
+
+        match self.venue {
+            CryptoVenue::Binance => {
+                let body = self
+                    .get(
+                        "/api/v3/ticker/24hr",
+                        &[("symbol", venue_symbol.to_string())],
+                    )
+                    .await?;
+                let ticker: BinanceTicker = serde_json::from_str(&body)?;
+                Ok(CryptoTicker {
+                    last_price: parse_number(&ticker.last_price, "lastPrice")?,
+                    volume: parse_number(&ticker.volume, "volume")?,
+                    timestamp: timestamp_millis(ticker.close_time)?,
+                    raw_hash: raw_response_hash(&body),
+                })
+            }
+            CryptoVenue::Okx => {
+                let body = self
+                    .get(
+                        "/api/v5/market/ticker",
+                        &[("instId", venue_symbol.to_string())],
+                    )
+                    .await?;
+                let ticker = parse_okx::<OkxTicker>(&body)?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| anyhow::anyhow!("No ticker returned for {}", venue_symbol))?;
+                Ok(CryptoTicker {
+                    last_price: parse_number(&ticker.last, "last")?,
+                    volume: parse_number(&ticker.vol_24h, "vol24h")?,
+                    timestamp: timestamp_millis(parse_number(&ticker.ts, "ts")? as i64)?,
+                    raw_hash: raw_response_hash(&body),
+                })
+            }
+        }
+    }
+
+    /// 获取最近 `limit` 根UTC日K线，按日期升序返回
+    pub async fn fetch_daily_klines(
+        &self,
+        venue_symbol: &str,
+        limit: u32,
+    ) -> Result<Vec<CryptoKline>> {
+        let mut klines = match self.venue {
+            CryptoVenue::Binance => {
+                let body = self
+                    .get(
+                        "/api/v3/klines",
+                        &[
+                            ("symbol", venue_symbol.to_string()),
+                            ("interval", "1d".to_string()),
+                            ("limit", limit.to_string()),
+                        ],
+                    )
+                    .await?;
+                // [开盘时间, 开, 高, 低, 收, 成交量, 收盘时间, ...]
+                let rows: Vec<Vec<serde_json::Value>> = serde_json::from_str(&body)?;
+                let raw_hash = raw_response_hash(&body);
+                rows.iter()
+                    .map(|row| parse_kline_row(row, &raw_hash))
+                    .collect::<Result<Vec<_>>>()?
+            }
+            CryptoVenue::Okx => {
+                let body = self
+                    .get(
+                        "/api/v5/market/candles",
+                        &[
+                            ("instId", venue_symbol.to_string()),
+                            ("bar", "1Dutc".to_string()),
+                            ("limit", limit.to_string()),
+                        ],
+                    )
+                    .await?;
+                // [开盘时间, 开, 高, 低, 收, 成交量, ...]，最新的在前
+                let raw_hash = raw_response_hash(&body);
+                parse_okx::<Vec<serde_json::Value>>(&body)?
+                    .iter()
+                    .map(|row| parse_kline_row(row, &raw_hash))
+                    .collect::<Result<Vec<_>>>()?
+            }
+        };
+
+        klines.sort_by_key(|k| k.date);
+        Ok(klines)
+    }
+
+    async fn get(&self, path: &str, query: &[(&str, String)]) -> Result<String> {
+        let url = format!("{}{}", self.base_url, path);
+        let request = self.client.get(&url).query(query);
+        let (status, body) = self.rate_limiter.send(self.venue.name(), request).await?;
+
+        if !status.is_success() {
+            // Binance 在4xx时返回 {"code": ..., "msg": ...}
+            if let Ok(error) = serde_json::from_str::<BinanceError>(&body) {
+                return Err(anyhow::anyhow!(
+                    "{} error: {} - {}",
+                    self.venue.name(),
+                    error.code,
+                    error.msg
+                ));
+            }
+            return Err(anyhow::anyhow!("HTTP error: {}", status));
+        }
+
+        Ok(body)
+    }
+}
+
+fn parse_okx<T: serde::de::DeserializeOwned>(body: &str) -> Result<Vec<T>> {
+    let response: OkxResponse<T> = serde_json::from_str(body)?;
+    if response.code != "0" {
+        return Err(anyhow::anyhow!(
+            "okx error: {} - {}",
+            response.code,
+            response.msg
+        ));
+    }
+    Ok(response.data)
+}
+
+fn parse_kline_row(row: &[serde_json::Value], raw_hash: &str) -> Result<CryptoKline> {
+    if row.len() < 6 {
+        return Err(anyhow::anyhow!("Malformed kline row: {:?}", row));
+    }
+    let field = |index: usize, name: &str| -> Result<f64> {
+        match &row[index] {
+            serde_json::Value::String(s) => parse_number(s, name),
+            serde_json::Value::Number(n) => n
+                .as_f64()
+                .ok_or_else(|| anyhow::anyhow!("Invalid {}: {}", name, n)),
+            other => Err(anyhow::anyhow!("Invalid {}: {}", name, other)),
+        }
+    };
+
+    Ok(CryptoKline {
+        date: timestamp_millis(field(0, "open time")? as i64)?.date_naive(),
+        open: field(1, "open")?,
+        high: field(2, "high")?,
+        low: field(3, "low")?,
+        close: field(4, "close")?,
+        volume: field(5, "volume")?,
+        raw_hash: raw_hash.to_string(),
+    })
+}
+
+fn parse_number(value: &str, name: &str) -> Result<f64> {
+    value
+        .parse::<f64>()
+        .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", name, e))
+}
+
+fn timestamp_millis(millis: i64) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(millis)
+        .ok_or_else(|| anyhow::anyhow!("Invalid timestamp: {}", millis))
+}
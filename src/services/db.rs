@@ -1,4 +1,6 @@
-use crate::models::{Alert, AlertStatus, CreateAlertRequest};
+use crate::models::{
+    Alert, AlertStatus, CreateAlertRequest, Market, PriceFeedStatus, QuarantinedPrice,
+};
 use anyhow::Result;
 use sqlx::sqlite::SqlitePool;
 use std::path::Path;
@@ -47,22 +49,27 @@ impl Database {
         let price = request.price;
         let notification_email = request.notification_email.as_deref();
         let user_id = &request.user_id;
+        let threshold_currency = normalize_threshold_currency(request);
 
         let alert = sqlx::query_as!(
             Alert,
             r#"
-            INSERT INTO alerts (symbol, condition, price, status, notification_email, user_id)
-            VALUES (?, ?, ?, 'active', ?, ?)
+            INSERT INTO alerts (symbol, condition, price, status, notification_email, user_id, threshold_currency, extended_hours, indicator)
+            VALUES (?, ?, ?, 'active', ?, ?, ?, ?, ?)
             RETURNING id as "id!", symbol, condition as "condition: _", price, 
                      status as "status: _", created_at, updated_at, triggered_at, 
                      notification_email, 
-                     COALESCE(user_id, 'default') as "user_id!"
+                     COALESCE(user_id, 'default') as "user_id!", threshold_currency, extended_hours,
+                     indicator as "indicator: _"
             "#,
             symbol,
             condition,
             price,
             notification_email,
             user_id,
+            threshold_currency,
+            request.extended_hours,
+            request.indicator,
         )
         .fetch_one(&self.pool)
         .await?;
@@ -76,7 +83,8 @@ impl Database {
             r#"
             SELECT id as "id!", symbol, condition as "condition: _", price, 
                    status as "status: _", created_at, updated_at, triggered_at, notification_email,
-                   COALESCE(user_id, 'default') as "user_id!"
+                   COALESCE(user_id, 'default') as "user_id!", threshold_currency, extended_hours,
+                   indicator as "indicator: _"
             FROM alerts
             ORDER BY created_at DESC
             "#
@@ -93,7 +101,8 @@ impl Database {
             r#"
             SELECT id as "id!", symbol, condition as "condition: _", price, 
                    status as "status: _", created_at, updated_at, triggered_at, notification_email,
-                   COALESCE(user_id, 'default') as "user_id!"
+                   COALESCE(user_id, 'default') as "user_id!", threshold_currency, extended_hours,
+                   indicator as "indicator: _"
             FROM alerts
             WHERE COALESCE(user_id, 'default') = ?
             ORDER BY created_at DESC
@@ -112,7 +121,8 @@ impl Database {
             r#"
             SELECT id as "id!", symbol, condition as "condition: _", price, 
                    status as "status: _", created_at, updated_at, triggered_at, notification_email,
-                   COALESCE(user_id, 'default') as "user_id!"
+                   COALESCE(user_id, 'default') as "user_id!", threshold_currency, extended_hours,
+                   indicator as "indicator: _"
             FROM alerts
             WHERE id = ?
             "#,
@@ -130,7 +140,8 @@ impl Database {
             r#"
             SELECT id as "id!", symbol, condition as "condition: _", price, 
                    status as "status: _", created_at, updated_at, triggered_at, notification_email,
-                   COALESCE(user_id, 'default') as "user_id!"
+                   COALESCE(user_id, 'default') as "user_id!", threshold_currency, extended_hours,
+                   indicator as "indicator: _"
             FROM alerts
             WHERE id = ? AND COALESCE(user_id, 'default') = ?
             "#,
@@ -217,16 +228,21 @@ impl Database {
         let condition = condition_str.as_str();
         let price = request.price;
         let notification_email = request.notification_email.as_deref();
+        let threshold_currency = normalize_threshold_currency(request);
         let result = sqlx::query!(
             r#"
             UPDATE alerts
-            SET symbol = ?, condition = ?, price = ?, notification_email = ?, updated_at = CURRENT_TIMESTAMP
+            SET symbol = ?, condition = ?, price = ?, notification_email = ?, threshold_currency = ?,
+                extended_hours = ?, indicator = ?, updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             "#,
             symbol,
             condition,
             price,
             notification_email,
+            threshold_currency,
+            request.extended_hours,
+            request.indicator,
             id
         )
         .execute(&self.pool)
@@ -239,6 +255,48 @@ impl Database {
         }
     }
 
+    pub async fn get_price_feed_status(&self, symbol: &str) -> Result<Option<PriceFeedStatus>> {
+        let status = sqlx::query_as!(
+            PriceFeedStatus,
+            r#"
+            SELECT symbol as "symbol!", last_success_at, last_failure_at, last_error
+            FROM price_feed_status
+            WHERE symbol = ?
+            "#,
+            symbol
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(status)
+    }
+
+    /// 最近被隔离的行情，可按股票代码过滤
+    pub async fn list_quarantined_prices(
+        &self,
+        symbol: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<QuarantinedPrice>> {
+        let rows = sqlx::query_as!(
+            QuarantinedPrice,
+            r#"
+            SELECT id as "id!", symbol, price, source, reference_price, deviation_percent,
+                   confirm_source, confirm_price, reason, created_at
+            FROM price_quarantine
+            WHERE ? IS NULL OR symbol = ?
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+            symbol,
+            symbol,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
     // 演示模式相关功能
     pub async fn count_alerts_by_user(&self, user_id: &str) -> Result<i64> {
         let count = sqlx::query_scalar!(
@@ -273,7 +331,8 @@ impl Database {
             r#"
             SELECT id as "id!", symbol, condition as "condition: _", price, 
                    status as "status: _", created_at, updated_at, triggered_at, notification_email,
-                   COALESCE(user_id, 'default') as "user_id!"
+                   COALESCE(user_id, 'default') as "user_id!", threshold_currency, extended_hours,
+                   indicator as "indicator: _"
             FROM alerts
             WHERE COALESCE(user_id, 'default') = ? AND status = 'active'
             ORDER BY created_at DESC
@@ -287,6 +346,16 @@ impl Database {
     }
 }
 
+/// 阈值货币统一为大写；与股票计价货币相同或按技术指标判断时不保存，按原值比较
+fn normalize_threshold_currency(request: &CreateAlertRequest) -> Option<String> {
+    request
+        .threshold_currency
+        .as_deref()
+        .map(|c| c.trim().to_uppercase())
+        .filter(|c| !c.is_empty() && *c != Market::symbol_currency(&request.symbol))
+        .filter(|_| request.indicator.is_none())
+}
+
 // Re-export common types
 pub use sqlx::Error as DbError;
 #[allow(dead_code)]
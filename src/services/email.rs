@@ -1,5 +1,5 @@
 use crate::config::EmailConfig;
-use crate::models::Alert;
+use crate::models::{Alert, CorporateAction, MarketSession};
 use chrono::Local;
 use lettre::{
     message::{header::ContentType, Mailbox},
@@ -53,14 +53,18 @@ impl EmailNotifier {
         &self,
         alert: &Alert,
         current_price: f64,
+        session: MarketSession,
     ) -> anyhow::Result<()> {
         if !self.config.enabled {
             info!("邮件通知已禁用，跳过发送");
             return Ok(());
         }
 
-        let subject = format!("交易预警触发 - {}", alert.symbol);
-        let body = self.create_alert_email_body(alert, current_price)?;
+        let subject = match session {
+            MarketSession::Regular => format!("交易预警触发 - {}", alert.symbol),
+            _ => format!("交易预警触发 - {}（{}）", alert.symbol, session.label()),
+        };
+        let body = self.create_alert_email_body(alert, current_price, session)?;
 
         // 优先使用预警设置的邮箱，否则使用默认邮箱
         let target_email = alert
@@ -71,6 +75,29 @@ impl EmailNotifier {
         self.send_email_to(&subject, &body, target_email).await
     }
 
+    /// 拆股/送转后预警价位已自动调整，通知预警所有者
+    pub async fn send_alert_rescaled_notification(
+        &self,
+        alert: &Alert,
+        old_price: f64,
+        action: &CorporateAction,
+    ) -> anyhow::Result<()> {
+        if !self.config.enabled {
+            info!("邮件通知已禁用，跳过发送");
+            return Ok(());
+        }
+
+        let subject = format!("预警价位已按除权调整 - {}", alert.symbol);
+        let body = self.create_rescaled_email_body(alert, old_price, action);
+
+        let target_email = alert
+            .notification_email
+            .as_ref()
+            .unwrap_or(&self.config.to_email);
+
+        self.send_email_to(&subject, &body, target_email).await
+    }
+
     pub async fn send_test_email(&self) -> anyhow::Result<()> {
         if !self.config.enabled {
             return Err(anyhow::anyhow!("邮件通知功能已禁用"));
@@ -118,17 +145,33 @@ impl EmailNotifier {
         }
     }
 
-    fn create_alert_email_body(&self, alert: &Alert, current_price: f64) -> anyhow::Result<String> {
+    fn create_alert_email_body(
+        &self,
+        alert: &Alert,
+        current_price: f64,
+        session: MarketSession,
+    ) -> anyhow::Result<String> {
         let now = Local::now();
         let alert_type = match alert.condition {
             crate::models::AlertCondition::Above => "突破上限",
             crate::models::AlertCondition::Below => "跌破下限",
         };
 
+        // 指标预警显示指标名称与取值，不带货币符号
+        let (value_name, unit) = match alert.indicator {
+            Some(indicator) => (indicator.label(), ""),
+            None => ("价格", "$"),
+        };
         let price_change = if alert.condition == crate::models::AlertCondition::Above {
-            format!("价格从 ${:.2} 上涨至 ${:.2}", alert.price, current_price)
+            format!(
+                "{}从 {}{:.2} 上涨至 {}{:.2}",
+                value_name, unit, alert.price, unit, current_price
+            )
         } else {
-            format!("价格从 ${:.2} 下跌至 ${:.2}", alert.price, current_price)
+            format!(
+                "{}从 {}{:.2} 下跌至 {}{:.2}",
+                value_name, unit, alert.price, unit, current_price
+            )
         };
 
         // 创建更简单的HTML模板
@@ -157,9 +200,9 @@ impl EmailNotifier {
         <div class="content">
             <h3>预警详情</h3>
             <p><strong>预警类型:</strong> {alert_type}</p>
-            <p><strong>当前价格:</strong> <span class="price">${current_price:.2}</span></p>
-            <p><strong>设定价格:</strong> ${target_price:.2}</p>
-            <p><strong>价格变化:</strong> {price_change}</p>
+            <p><strong>当前{value_name}:</strong> <span class="price">{unit}{current_price:.2}</span> ({session})</p>
+            <p><strong>设定{value_name}:</strong> {unit}{target_price:.2}</p>
+            <p><strong>{value_name}变化:</strong> {price_change}</p>
             <p><strong>预警ID:</strong> {alert_id}</p>
             <p><strong>触发时间:</strong> {trigger_time}</p>
         </div>
@@ -173,7 +216,10 @@ impl EmailNotifier {
             "#,
             symbol = alert.symbol,
             alert_type = alert_type,
+            value_name = value_name,
+            unit = unit,
             current_price = current_price,
+            session = session.label(),
             target_price = alert.price,
             price_change = price_change,
             alert_id = alert.id,
@@ -183,6 +229,59 @@ impl EmailNotifier {
         Ok(body)
     }
 
+    fn create_rescaled_email_body(
+        &self,
+        alert: &Alert,
+        old_price: f64,
+        action: &CorporateAction,
+    ) -> String {
+        format!(
+            r#"
+<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <style>
+        body {{ font-family: Arial, sans-serif; line-height: 1.6; color: #333; }}
+        .container {{ max-width: 600px; margin: 0 auto; padding: 20px; }}
+        .header {{ background-color: #fff3cd; padding: 20px; border-radius: 5px; text-align: center; }}
+        .content {{ background-color: #f8f9fa; padding: 20px; border-radius: 5px; margin: 20px 0; }}
+        .footer {{ text-align: center; color: #666; margin-top: 20px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>🔁 预警价位已调整</h1>
+            <h2>{symbol}</h2>
+        </div>
+
+        <div class="content">
+            <p>{symbol} 于 {ex_date} 除权（{action_type}，每股变为 {ratio} 股），原价位已不再可比，系统已自动调整您的预警。</p>
+            <p><strong>原设定价格:</strong> {old_price:.4}</p>
+            <p><strong>调整后价格:</strong> {new_price:.4}</p>
+            <p><strong>预警ID:</strong> {alert_id}</p>
+            <p><strong>说明:</strong> {description}</p>
+        </div>
+
+        <div class="footer">
+            <p>此邮件由交易预警系统自动发送</p>
+        </div>
+    </div>
+</body>
+</html>
+            "#,
+            symbol = alert.symbol,
+            ex_date = action.ex_date,
+            action_type = action.action_type,
+            ratio = action.split_ratio,
+            old_price = old_price,
+            new_price = alert.price,
+            alert_id = alert.id,
+            description = action.description.as_deref().unwrap_or("-"),
+        )
+    }
+
     fn create_test_email_body(&self) -> String {
         let now = Local::now();
 
@@ -1,17 +1,31 @@
-use crate::config::PriceFetcherConfig;
+use crate::config::{IndicatorConfig, PriceFetcherConfig};
+use crate::models::{AlertIndicator, DataQuality, Market, MarketSession};
+use crate::services::calendar::TradingCalendars;
+use crate::services::corporate_actions::PriceAdjustment;
+use crate::services::crypto::CryptoExchangeClient;
 use crate::services::email::EmailNotifier;
+use crate::services::fx::{parse_currency_pair, FxRateService};
+use crate::services::indicators::{Indicator, IndicatorSet};
+use crate::services::intraday::IntradayStore;
+use crate::services::provider_health::{ProviderHealth, ProviderHealthSnapshot};
+use crate::services::quotes::{Quote, QuoteCache};
+use crate::services::rate_limiter::{BucketSnapshot, RateLimiter};
+use crate::services::strategy_analyzer::{cn_stocks_symbol, IndicatorValues, StrategyAnalyzer};
+use crate::services::stream::{PriceStream, StreamStatus, StreamTick};
+use crate::services::validation::{deviation_percent, PriceValidator, Verdict};
+use crate::utils::raw_response_hash;
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
 
 use reqwest::Client;
 use serde::Deserialize;
 use sqlx::SqlitePool;
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
-use tokio::sync::Semaphore;
+use tokio::sync::{mpsc, watch, RwLock};
+use tokio::task::JoinSet;
 use tokio::time;
 use tracing::{error, info, warn};
 
@@ -29,6 +43,35 @@ struct YahooChart {
 #[derive(Debug, Deserialize)]
 struct YahooResult {
     meta: YahooMeta,
+    /// 分钟K线时间，仅在请求 includePrePost 时使用
+    #[serde(default)]
+    timestamp: Vec<i64>,
+    #[serde(default)]
+    indicators: Option<YahooIndicators>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooIndicators {
+    #[serde(default)]
+    quote: Vec<YahooIndicatorQuote>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooIndicatorQuote {
+    #[serde(default)]
+    close: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooTradingPeriods {
+    pre: YahooTradingPeriod,
+    post: YahooTradingPeriod,
+}
+
+#[derive(Debug, Deserialize)]
+struct YahooTradingPeriod {
+    start: i64,
+    end: i64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,10 +80,16 @@ struct YahooMeta {
     regular_market_price: Option<f64>,
     #[serde(rename = "regularMarketVolume")]
     regular_market_volume: Option<i64>,
+    /// 最新成交的 Unix 时间（秒）
+    #[serde(rename = "regularMarketTime")]
+    regular_market_time: Option<i64>,
     #[allow(dead_code)]
     symbol: String,
     #[serde(rename = "shortName")]
     short_name: Option<String>,
+    /// 当天盘前、常规、盘后时段的起止时间
+    #[serde(rename = "currentTradingPeriod")]
+    current_trading_period: Option<YahooTradingPeriods>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -49,23 +98,163 @@ struct YahooError {
     description: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug)]
 struct StockPrice {
     symbol: String,
     price: f64,
     volume: i64,
+    // 本服务获取行情的时间
     timestamp: chrono::DateTime<Utc>,
     name_en: Option<String>,
+    quality: DataQuality,
+    // 产生该价格的数据源
+    source: &'static str,
+    // 数据源给出的行情时间
+    exchange_time: Option<DateTime<Utc>>,
+    // 原始响应摘要，模拟价格为空
+    raw_hash: Option<String>,
+    // 行情所属交易时段
+    session: MarketSession,
 }
 
-// 缓存结构
-#[derive(Debug, Clone)]
-struct PriceCache {
-    #[allow(dead_code)]
-    price: f64,
-    #[allow(dead_code)]
-    volume: i64,
-    timestamp: chrono::DateTime<Utc>,
+impl StockPrice {
+    fn to_quote(&self) -> Quote {
+        Quote {
+            symbol: self.symbol.clone(),
+            price: self.price,
+            volume: self.volume,
+            timestamp: self.timestamp,
+            source: self.source.to_string(),
+            name_en: self.name_en.clone(),
+            session: self.session,
+            data_quality: self.quality,
+            stale: false,
+        }
+    }
+}
+
+/// 价格校验结果
+#[derive(Debug)]
+enum Validation {
+    Accepted,
+    Rejected(String),
+}
+
+// 数据源名称，同时作为限流配置的键
+const PROVIDER_SINA: &str = "sina";
+const PROVIDER_TENCENT: &str = "tencent";
+const PROVIDER_YAHOO: &str = "yahoo";
+// 模拟后备价格的来源标记
+const SOURCE_SYNTHETIC: &str = "synthetic";
+
+// 价格写入的触发方式，随价格历史保存
+const ORIGIN_POLLER: &str = "poller";
+const ORIGIN_STREAM: &str = "stream";
+const ORIGIN_ON_DEMAND: &str = "on_demand";
+const ORIGIN_BACKFILL: &str = "backfill";
+
+/// 无第二数据源时，同一数据源在该时间内重复给出一致价格即视为确认
+const REPEAT_CONFIRM_WINDOW_MINUTES: i64 = 10;
+
+/// 单个股票在一个更新周期内的结果
+#[derive(Debug)]
+enum FetchOutcome {
+    Updated(Duration),
+    Fallback(Duration),
+    Rejected,
+    Failed,
+    TimedOut,
+}
+
+/// 价格更新周期的统计信息
+#[derive(Debug, Default)]
+struct CycleStats {
+    updated: usize,
+    fallback: usize,
+    rejected: usize,
+    failed: usize,
+    timed_out: usize,
+    cached: usize,
+    total_latency: Duration,
+    slowest: Option<(String, Duration)>,
+}
+
+impl CycleStats {
+    fn record(&mut self, symbol: String, outcome: FetchOutcome) {
+        let latency = match outcome {
+            FetchOutcome::Updated(latency) => {
+                self.updated += 1;
+                latency
+            }
+            FetchOutcome::Fallback(latency) => {
+                self.fallback += 1;
+                latency
+            }
+            FetchOutcome::Rejected => {
+                self.rejected += 1;
+                return;
+            }
+            FetchOutcome::Failed => {
+                self.failed += 1;
+                return;
+            }
+            FetchOutcome::TimedOut => {
+                self.timed_out += 1;
+                return;
+            }
+        };
+
+        self.total_latency += latency;
+        if self.slowest.as_ref().is_none_or(|(_, max)| latency > *max) {
+            self.slowest = Some((symbol, latency));
+        }
+    }
+
+    fn log(&self, cycle: &str, elapsed: Duration) {
+        let fetched = self.updated + self.fallback;
+        let avg_latency_ms = if fetched > 0 {
+            self.total_latency.as_millis() / fetched as u128
+        } else {
+            0
+        };
+        let slowest = self
+            .slowest
+            .as_ref()
+            .map(|(symbol, latency)| format!("{} {}ms", symbol, latency.as_millis()))
+            .unwrap_or_else(|| "-".to_string());
+
+        info!(
+            "{} price update cycle finished in {}ms: updated={}, fallback={}, rejected={}, failed={}, timed_out={}, cached={}, avg_latency={}ms, slowest={}",
+            cycle,
+            elapsed.as_millis(),
+            self.updated,
+            self.fallback,
+            self.rejected,
+            self.failed,
+            self.timed_out,
+            self.cached,
+            avg_latency_ms,
+            slowest
+        );
+    }
+}
+
+/// 收盘后等待多久再获取收盘快照，确保数据源已给出最终收盘价
+const POST_CLOSE_DELAY_SECS: i64 = 120;
+/// 休市期间单次休眠的上限
+const MAX_CLOSED_SLEEP: Duration = Duration::from_secs(3600);
+/// 流通股本超过该天数未更新时重新获取
+const FLOAT_SHARES_MAX_AGE_DAYS: i64 = 7;
+/// 流通股本获取失败后的重试等待，按连续失败次数翻倍，最长一天
+const FLOAT_SHARES_RETRY_BASE: Duration = Duration::from_secs(300);
+const FLOAT_SHARES_RETRY_MAX: Duration = Duration::from_secs(86_400);
+
+/// 指标预警使用的指标状态：已输入最新一根日线之前的全部日线
+struct IndicatorState {
+    /// 最新一根日线的日期，出现新的日线时重新预热
+    latest_date: String,
+    set: IndicatorSet,
+    float_shares: Option<f64>,
 }
 
 // 价格服务状态
@@ -73,11 +262,33 @@ pub struct PriceService {
     client: Client,
     db: SqlitePool,
     update_interval: Duration,
-    cache: Arc<RwLock<HashMap<String, PriceCache>>>,
-    semaphore: Arc<Semaphore>,
-    request_count: AtomicU64,
-    last_reset: AtomicU64,
+    cache: Arc<QuoteCache>,
+    rate_limiter: Arc<RateLimiter>,
     email_notifier: Arc<EmailNotifier>,
+    crypto: Option<Arc<CryptoExchangeClient>>,
+    // 本系统交易对 -> 交易所交易对
+    crypto_symbols: Arc<RwLock<HashMap<String, String>>>,
+    // 本次运行中已回填过日K线的交易对
+    crypto_backfilled: Arc<RwLock<HashSet<String>>>,
+    stream: Option<Arc<PriceStream>>,
+    // 预警增删改后递增，推送订阅与休市中的市场调度据此立即响应
+    alerts_changed: Arc<watch::Sender<u64>>,
+    stream_persist_interval: Duration,
+    // 推送行情最近一次写入价格历史的时间
+    stream_persisted: Arc<RwLock<HashMap<String, time::Instant>>>,
+    calendars: Arc<TradingCalendars>,
+    health: Arc<ProviderHealth>,
+    validator: PriceValidator,
+    fx: Arc<FxRateService>,
+    // 日内行情存储，未启用时为 None
+    intraday: Option<Arc<IntradayStore>>,
+    extended_hours_enabled: bool,
+    // 指标预警使用的技术指标参数
+    indicators: IndicatorConfig,
+    // 各股票的指标状态，同一天内每次预警检查只需输入最新一根日线
+    indicator_states: Arc<RwLock<HashMap<String, IndicatorState>>>,
+    // 流通股本获取失败的股票：连续失败次数与下次重试时间
+    float_shares_failures: Arc<RwLock<HashMap<String, (u32, time::Instant)>>>,
 }
 
 impl PriceService {
@@ -85,53 +296,453 @@ impl PriceService {
         db: SqlitePool,
         config: &PriceFetcherConfig,
         email_notifier: Arc<EmailNotifier>,
+        calendars: Arc<TradingCalendars>,
+        indicators: IndicatorConfig,
     ) -> Self {
+        let rate_limiter = Arc::new(RateLimiter::new(config));
+        let crypto = config.crypto.enabled.then(|| {
+            Arc::new(
+                CryptoExchangeClient::new(&config.crypto, rate_limiter.clone())
+                    .expect("Failed to create crypto exchange client"),
+            )
+        });
+        let stream = (config.crypto.enabled && config.stream.enabled)
+            .then(|| Arc::new(PriceStream::new(config.crypto.venue, &config.stream)));
+
         Self {
             client: Client::builder()
                 .timeout(Duration::from_secs(config.request_timeout_secs))
                 .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs))
                 .build()
                 .expect("Failed to create HTTP client"),
-            db,
             update_interval: Duration::from_secs(config.update_interval_secs),
-            cache: Arc::new(RwLock::new(HashMap::new())),
-            semaphore: Arc::new(Semaphore::new(config.max_concurrent_requests)),
-            request_count: AtomicU64::new(0),
-            last_reset: AtomicU64::new(Utc::now().timestamp() as u64),
+            cache: Arc::new(QuoteCache::new(config)),
+            rate_limiter,
             email_notifier,
+            crypto,
+            crypto_symbols: Arc::new(RwLock::new(HashMap::new())),
+            crypto_backfilled: Arc::new(RwLock::new(HashSet::new())),
+            stream,
+            alerts_changed: Arc::new(watch::channel(0).0),
+            stream_persist_interval: Duration::from_secs(config.stream.persist_interval_secs),
+            stream_persisted: Arc::new(RwLock::new(HashMap::new())),
+            calendars,
+            health: Arc::new(ProviderHealth::new(&config.circuit_breaker)),
+            validator: PriceValidator::new(&config.validation),
+            fx: Arc::new(FxRateService::new(db.clone(), &config.fx)),
+            intraday: config
+                .intraday
+                .enabled
+                .then(|| Arc::new(IntradayStore::new(db.clone()))),
+            extended_hours_enabled: config.extended_hours_enabled,
+            indicators,
+            indicator_states: Arc::new(RwLock::new(HashMap::new())),
+            float_shares_failures: Arc::new(RwLock::new(HashMap::new())),
+            db,
         }
     }
 
-    #[allow(dead_code)]
-    pub async fn start(&self, config: &PriceFetcherConfig) -> Result<()> {
-        info!("Starting price service...");
+    /// 汇率存储，供接口换算与跨币种预警使用
+    pub fn fx_rates(&self) -> &FxRateService {
+        &self.fx
+    }
+
+    /// 从 Yahoo 获取配置的货币对汇率并写入汇率历史
+    async fn refresh_fx_rates(&self, pairs: &[String]) {
+        for pair in pairs {
+            let Some((base, quote)) = parse_currency_pair(pair) else {
+                warn!("Invalid FX pair in config: {}", pair);
+                continue;
+            };
+            let symbol = format!("{base}{quote}=X");
+            match self
+                .call_provider(PROVIDER_YAHOO, self.fetch_yahoo_price(&symbol))
+                .await
+            {
+                Ok(price) => {
+                    if let Err(e) = self
+                        .fx
+                        .record(&base, &quote, price.price, PROVIDER_YAHOO, None)
+                        .await
+                    {
+                        error!("Failed to save FX rate {}: {}", symbol, e);
+                    }
+                }
+                Err(e) => warn!("Failed to fetch FX rate {}: {}", symbol, e),
+            }
+        }
+    }
+
+    /// 预警新增、修改或删除后调用，推送订阅与市场调度会据此立即更新
+    pub fn notify_alerts_changed(&self) {
+        self.alerts_changed.send_modify(|version| *version += 1);
+    }
+
+    /// 各市场可用的数据源，按优先级排列
+    pub fn market_providers(&self, market: &Market) -> Vec<&'static str> {
+        match market {
+            Market::US => vec![PROVIDER_YAHOO],
+            Market::CN => vec![PROVIDER_SINA, PROVIDER_TENCENT],
+            Market::HK => vec![PROVIDER_TENCENT, PROVIDER_SINA, PROVIDER_YAHOO],
+            Market::Futures | Market::FX => vec![PROVIDER_YAHOO],
+            Market::CnFutures => vec![PROVIDER_SINA],
+            Market::Crypto => match &self.crypto {
+                Some(crypto) => vec![crypto.venue().name(), PROVIDER_YAHOO],
+                None => vec![PROVIDER_YAHOO],
+            },
+        }
+    }
+
+    /// 获取所有数据源的健康状况
+    pub fn provider_health(&self) -> Vec<ProviderHealthSnapshot> {
+        let mut providers: Vec<&str> = Market::ALL
+            .iter()
+            .flat_map(|market| self.market_providers(market))
+            .collect();
+        providers.sort();
+        providers.dedup();
+        self.health.snapshot(&providers)
+    }
+
+    /// 行情推送连接状态，未启用推送时为 None
+    pub async fn stream_status(&self) -> Option<StreamStatus> {
+        Some(self.stream.as_ref()?.status().await)
+    }
+
+    /// 市场是否至少有一个未熔断的数据源
+    pub fn market_has_healthy_source(&self, market: &Market) -> bool {
+        self.market_providers(market)
+            .iter()
+            .any(|provider| self.health.is_healthy(provider))
+    }
+
+    /// 经过熔断器调用数据源，并记录成功率、延迟与错误
+    async fn call_provider<T>(
+        &self,
+        provider: &str,
+        request: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        if !self.health.allow(provider) {
+            return Err(anyhow::anyhow!("Provider {} circuit is open", provider));
+        }
+        // 限流退避期内直接跳过，由调用方改用下一个数据源，而不是排队等待退避结束
+        if let Some(remaining) = self.rate_limiter.backoff_remaining(provider) {
+            return Err(anyhow::anyhow!(
+                "Provider {} is rate limited for another {}s",
+                provider,
+                remaining.as_secs()
+            ));
+        }
+
+        let started = time::Instant::now();
+        let result = request.await;
+        match &result {
+            Ok(_) => self.health.record_success(provider, started.elapsed()),
+            Err(e) => self
+                .health
+                .record_failure(provider, started.elapsed(), &e.to_string()),
+        }
+        result
+    }
+
+    /// 获取各数据源令牌桶的当前状态
+    pub fn rate_limit_snapshot(&self) -> Vec<BucketSnapshot> {
+        self.rate_limiter.snapshot()
+    }
+
+    /// 单个股票市场的调度循环
+    ///
+    /// 开盘期间按 `update_interval_secs` 轮询；收盘（含午间休市）后为尚无收盘后价格的
+    /// 股票获取一次收盘快照，然后休眠到下次开盘。是否需要快照由数据库中最近一次
+    /// 价格的时间判断，因此重启后、或休市期间新增预警时都会正确补抓。
+    async fn run_market_loop(self: Arc<Self>, market: Market, config: Arc<PriceFetcherConfig>) {
+        let label = market.to_str();
+        let mut alerts_changed = self.alerts_changed.subscribe();
 
-        let mut interval = time::interval(self.update_interval);
         loop {
-            interval.tick().await;
-            if let Err(e) = self.update_prices(config).await {
-                error!("Failed to update prices: {}", e);
+            let now = Utc::now();
+            let calendar = self.calendars.for_market(&market);
+
+            if calendar.is_open(now) {
+                match self.market_symbols(&market).await {
+                    Ok(symbols) => {
+                        let cache_ttl = Duration::from_secs(config.cache_ttl_secs);
+                        self.run_update_cycle(label, symbols, &config, cache_ttl)
+                            .await;
+                    }
+                    Err(e) => error!("Error loading {} symbols: {}", label, e),
+                }
+                time::sleep(self.update_interval).await;
+                continue;
+            }
+
+            // 收盘后稍等片刻，确保数据源已给出最终收盘价
+            if let Some(close) = calendar.last_close(now) {
+                let snapshot_at = close + chrono::Duration::seconds(POST_CLOSE_DELAY_SECS);
+                if now < snapshot_at {
+                    time::sleep((snapshot_at - now).to_std().unwrap_or_default()).await;
+                    continue;
+                }
+                if let Err(e) = self.take_close_snapshot(&market, close, &config).await {
+                    error!("Error taking {} post-close snapshot: {}", label, e);
+                }
+            }
+
+            // 盘前盘后只为开启了盘前盘后预警的股票获取行情
+            if let Some(session) = calendar
+                .extended_session(Utc::now())
+                .filter(|_| self.extended_hours_enabled)
+            {
+                match self.extended_hours_symbols(&market).await {
+                    Ok(symbols) if !symbols.is_empty() => {
+                        self.run_extended_cycle(label, session, symbols, &config)
+                            .await;
+                        time::sleep(self.update_interval).await;
+                        continue;
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("Error loading {} extended-hours symbols: {}", label, e),
+                }
+            }
+
+            // 休眠到下次开盘或盘前盘后开始；预警变化时提前醒来为新股票补抓收盘快照
+            let next_open = calendar.next_open(Utc::now());
+            let next_extended_open = calendar
+                .next_extended_open(Utc::now())
+                .filter(|_| self.extended_hours_enabled);
+            let sleep_for = next_open
+                .into_iter()
+                .chain(next_extended_open)
+                .min()
+                .and_then(|wake| (wake - Utc::now()).to_std().ok())
+                .unwrap_or(MAX_CLOSED_SLEEP)
+                .min(MAX_CLOSED_SLEEP);
+            info!(
+                "{} market closed, next open at {:?}, sleeping {}s",
+                label,
+                next_open,
+                sleep_for.as_secs()
+            );
+            tokio::select! {
+                _ = time::sleep(sleep_for) => {}
+                _ = alerts_changed.changed() => {}
             }
-            // 每小时重置请求计数
-            self.check_and_reset_request_count().await;
         }
     }
 
-    #[allow(dead_code)]
-    async fn check_and_reset_request_count(&self) {
-        let now = Utc::now().timestamp() as u64;
-        let last_reset = self.last_reset.load(Ordering::Relaxed);
-        if now - last_reset >= 3600 {
-            // 1小时
-            self.request_count.store(0, Ordering::Relaxed);
-            self.last_reset.store(now, Ordering::Relaxed);
-            info!("Reset request count");
+    /// 为收盘后还没有价格的股票获取一次收盘快照
+    async fn take_close_snapshot(
+        self: &Arc<Self>,
+        market: &Market,
+        close: DateTime<Utc>,
+        config: &Arc<PriceFetcherConfig>,
+    ) -> Result<()> {
+        let mut symbols = Vec::new();
+        for symbol in self.market_symbols(market).await? {
+            let latest = self.latest_live_price_at(&symbol).await?;
+            if latest.is_none_or(|at| at < close) {
+                symbols.push(symbol);
+            }
+        }
+
+        if !symbols.is_empty() {
+            info!(
+                "Taking post-close snapshot for {} {} symbols",
+                symbols.len(),
+                market.display_name()
+            );
+            let label = format!("{} post-close", market.to_str());
+            self.run_update_cycle(&label, symbols, config, Duration::ZERO)
+                .await;
         }
+        Ok(())
     }
 
-    async fn update_prices(&self, config: &PriceFetcherConfig) -> Result<()> {
-        // 获取所有活跃预警的股票代码
-        let symbols = sqlx::query!(
+    /// 最近一次真实行情的写入时间
+    async fn latest_live_price_at(&self, symbol: &str) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT MAX(created_at) as "created_at: chrono::NaiveDateTime"
+            FROM price_history
+            WHERE symbol = ? AND data_quality = 'live'
+            "#,
+            symbol
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(row.created_at.map(|at| at.and_utc()))
+    }
+
+    /// 判断股票所属市场，映射到交易所的交易对一律视为加密货币
+    async fn symbol_market(&self, symbol: &str) -> Market {
+        if self.crypto_venue_symbol(symbol).await.is_some() {
+            Market::Crypto
+        } else {
+            Market::from_symbol(symbol)
+        }
+    }
+
+    /// 指定市场中有活跃预警的股票代码
+    async fn market_symbols(&self, market: &Market) -> Result<Vec<String>> {
+        let mut symbols = Vec::new();
+        for symbol in self.active_alert_symbols().await? {
+            if self.symbol_market(&symbol).await == *market {
+                symbols.push(symbol);
+            }
+        }
+        Ok(symbols)
+    }
+
+    /// 执行一个加密货币价格更新周期
+    async fn update_crypto_prices(
+        self: &Arc<Self>,
+        config: &Arc<PriceFetcherConfig>,
+    ) -> Result<()> {
+        self.refresh_crypto_symbols().await?;
+
+        let mut symbols = Vec::new();
+        for symbol in self.market_symbols(&Market::Crypto).await? {
+            // 没有交易所映射的交易对（如稳定币）仍通过Yahoo轮询
+            if let Some(venue_symbol) = self.crypto_venue_symbol(&symbol).await {
+                // 推送健康的交易对无需轮询
+                if let Some(stream) = &self.stream {
+                    if stream.is_healthy(&venue_symbol).await {
+                        continue;
+                    }
+                }
+                if let Err(e) = self
+                    .backfill_crypto_history(
+                        &symbol,
+                        &venue_symbol,
+                        config.crypto.kline_backfill_days,
+                    )
+                    .await
+                {
+                    warn!("Failed to backfill daily klines for {}: {}", symbol, e);
+                }
+            }
+            symbols.push(symbol);
+        }
+
+        // 加密货币的节奏由独立的更新间隔控制，不使用缓存跳过
+        self.run_update_cycle("Crypto", symbols, config, Duration::ZERO)
+            .await;
+        Ok(())
+    }
+
+    /// 将推送订阅同步为有活跃预警的加密货币交易对
+    async fn refresh_stream_subscriptions(&self) -> Result<()> {
+        let Some(stream) = &self.stream else {
+            return Ok(());
+        };
+
+        let mut venue_symbols = BTreeSet::new();
+        for symbol in self.active_alert_symbols().await? {
+            if let Some(venue_symbol) = self.crypto_venue_symbol(&symbol).await {
+                venue_symbols.insert(venue_symbol);
+            }
+        }
+        stream.set_symbols(venue_symbols);
+        Ok(())
+    }
+
+    /// 处理一笔推送行情：每笔都检查预警，按 `persist_interval_secs` 节流写入价格历史
+    async fn handle_stream_tick(&self, tick: StreamTick) -> Result<()> {
+        let Some(tick_source) = self.crypto.as_ref().map(|crypto| crypto.venue().name()) else {
+            return Ok(());
+        };
+        let symbols: Vec<String> = self
+            .crypto_symbols
+            .read()
+            .await
+            .iter()
+            .filter(|(_, venue_symbol)| **venue_symbol == tick.venue_symbol)
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+
+        for symbol in symbols {
+            let price = StockPrice {
+                symbol: symbol.clone(),
+                price: tick.price,
+                volume: tick.volume.round() as i64,
+                timestamp: Utc::now(),
+                name_en: None,
+                quality: DataQuality::Live,
+                source: tick_source,
+                exchange_time: Some(tick.timestamp),
+                raw_hash: Some(tick.raw_hash.clone()),
+                session: MarketSession::Regular,
+            };
+            if let Validation::Rejected(reason) = self.validate_price(&price, None).await? {
+                warn!(
+                    "Rejected streamed price {} for {}: {}",
+                    price.price, symbol, reason
+                );
+                continue;
+            }
+            self.cache_price(&price).await;
+
+            let persist = self
+                .stream_persisted
+                .read()
+                .await
+                .get(&symbol)
+                .is_none_or(|at| at.elapsed() >= self.stream_persist_interval);
+            if persist {
+                self.save_price(&price, ORIGIN_STREAM).await?;
+                self.record_feed_success(&symbol).await?;
+                self.stream_persisted
+                    .write()
+                    .await
+                    .insert(symbol, time::Instant::now());
+            } else {
+                self.record_tick(&price).await;
+                self.check_alerts(&symbol, price.price, price.session)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 启动推送连接、行情处理任务以及订阅同步任务
+    fn start_stream(self: &Arc<Self>) {
+        let Some(stream) = &self.stream else {
+            return;
+        };
+
+        let (ticks_tx, mut ticks_rx) = mpsc::channel(1024);
+        stream.spawn(ticks_tx);
+
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            while let Some(tick) = ticks_rx.recv().await {
+                if let Err(e) = service.handle_stream_tick(tick).await {
+                    error!("Error handling streamed price: {}", e);
+                }
+            }
+        });
+
+        // 预警变化时立即同步订阅，并定期兜底同步
+        let service = Arc::clone(self);
+        let mut alerts_changed = self.alerts_changed.subscribe();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = service.refresh_stream_subscriptions().await {
+                    error!("Error refreshing stream subscriptions: {}", e);
+                }
+                tokio::select! {
+                    _ = alerts_changed.changed() => {}
+                    _ = time::sleep(Duration::from_secs(30)) => {}
+                }
+            }
+        });
+    }
+
+    /// 获取所有活跃预警的股票代码
+    async fn active_alert_symbols(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query!(
             r#"
             SELECT DISTINCT symbol
             FROM alerts
@@ -141,60 +752,368 @@ impl PriceService {
         .fetch_all(&self.db)
         .await?;
 
+        Ok(rows.into_iter().map(|row| row.symbol).collect())
+    }
+
+    /// 指定市场中开启了盘前盘后预警的股票代码
+    async fn extended_hours_symbols(&self, market: &Market) -> Result<Vec<String>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT symbol
+            FROM alerts
+            WHERE status = 'active' AND extended_hours
+            "#
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut symbols = Vec::new();
+        for row in rows {
+            if self.symbol_market(&row.symbol).await == *market {
+                symbols.push(row.symbol);
+            }
+        }
+        Ok(symbols)
+    }
+
+    /// 股票当前所处的盘前或盘后时段，未启用盘前盘后行情或不在该时段时返回 None
+    async fn current_extended_session(&self, symbol: &str) -> Option<MarketSession> {
+        if !self.extended_hours_enabled {
+            return None;
+        }
+        let market = self.symbol_market(symbol).await;
+        self.calendars
+            .for_market(&market)
+            .extended_session(Utc::now())
+    }
+
+    /// 为开启了盘前盘后预警的股票并发获取盘前盘后行情
+    ///
+    /// 与常规更新周期相同：每个股票在独立任务中获取，请求并发数受 `max_concurrent_requests` 限制，
+    /// 排队与请求都受周期截止时间约束，单个慢股票不会拖住整个时段。
+    async fn run_extended_cycle(
+        self: &Arc<Self>,
+        cycle: &str,
+        session: MarketSession,
+        symbols: Vec<String>,
+        config: &PriceFetcherConfig,
+    ) {
+        let deadline = time::Instant::now() + Duration::from_secs(config.cycle_timeout_secs);
+        let total = symbols.len();
+        let mut tasks = JoinSet::new();
+
+        for symbol in symbols {
+            let service = Arc::clone(self);
+            tasks.spawn(async move {
+                service
+                    .update_extended_symbol(&symbol, session, deadline)
+                    .await
+            });
+        }
+
+        let (mut updated, mut timed_out) = (0, 0);
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok(Some(FetchOutcome::Updated(_))) => updated += 1,
+                Ok(Some(FetchOutcome::TimedOut)) => timed_out += 1,
+                Ok(_) => {}
+                Err(e) => error!("Extended-hours update task failed: {}", e),
+            }
+        }
+        info!(
+            "{} {} cycle: {}/{} symbols updated, timed_out={}",
+            cycle, session, updated, total, timed_out
+        );
+    }
+
+    /// 获取并处理单个股票的盘前盘后行情，该时段还没有成交时返回 None
+    async fn update_extended_symbol(
+        &self,
+        symbol: &str,
+        session: MarketSession,
+        deadline: time::Instant,
+    ) -> Option<FetchOutcome> {
+        let started = time::Instant::now();
+        let fetched = time::timeout_at(
+            deadline,
+            self.call_provider(PROVIDER_YAHOO, self.fetch_yahoo_extended_price(symbol)),
+        )
+        .await;
+
+        match fetched {
+            Ok(Ok(Some(price))) => match self.accept_extended_price(&price, Some(deadline)).await {
+                Ok(true) => Some(FetchOutcome::Updated(started.elapsed())),
+                Ok(false) => Some(FetchOutcome::Rejected),
+                Err(e) => {
+                    error!("Failed to process {} price for {}: {}", session, symbol, e);
+                    Some(FetchOutcome::Failed)
+                }
+            },
+            // 该时段还没有成交
+            Ok(Ok(None)) => None,
+            Ok(Err(e)) => {
+                warn!("Failed to fetch {} price for {}: {}", session, symbol, e);
+                Some(FetchOutcome::Failed)
+            }
+            Err(_) => {
+                warn!(
+                    "{} price fetch for {} exceeded the cycle deadline",
+                    session, symbol
+                );
+                Some(FetchOutcome::TimedOut)
+            }
+        }
+    }
+
+    /// 处理一笔盘前盘后行情：校验后更新缓存、写入日内行情并检查开启了盘前盘后的预警，
+    /// 不写入日线价格历史。被拒绝时返回 false。
+    async fn accept_extended_price(
+        &self,
+        price: &StockPrice,
+        deadline: Option<time::Instant>,
+    ) -> Result<bool> {
+        if let Validation::Rejected(reason) = self.validate_price(price, deadline).await? {
+            warn!(
+                "Rejected {} price {} for {}: {}",
+                price.session, price.price, price.symbol, reason
+            );
+            return Ok(false);
+        }
+        self.cache_price(price).await;
+        self.record_tick(price).await;
+        self.check_alerts(&price.symbol, price.price, price.session)
+            .await?;
+        Ok(true)
+    }
+
+    /// 并发更新一组股票的价格
+    ///
+    /// 每个股票代码在独立任务中获取，同时发出的请求数限制为 `max_concurrent_requests`（见 `RateLimiter::send`）。
+    /// 获取价格（含排队与重试）受周期截止时间约束，每个任务拿到价格后立即保存并检查预警，
+    /// 不必等待其他股票完成。
+    async fn run_update_cycle(
+        self: &Arc<Self>,
+        cycle: &str,
+        symbols: Vec<String>,
+        config: &Arc<PriceFetcherConfig>,
+        cache_ttl: Duration,
+    ) {
+        let cycle_started = time::Instant::now();
+        let deadline = cycle_started + Duration::from_secs(config.cycle_timeout_secs);
+
+        let mut stats = CycleStats::default();
+        let mut tasks = JoinSet::new();
+
         for symbol in symbols {
-            // 检查缓存
-            if let Some(cached) = self.get_cached_price(&symbol.symbol).await {
+            // 检查缓存：只看本实例获取的报价，其他实例写入 Redis 的报价不代替本实例的预警检查
+            if let Some(cached) = self.cache.get_local(&symbol).await {
                 if Utc::now() - cached.timestamp
-                    < chrono::Duration::seconds(config.cache_ttl_secs as i64)
+                    < chrono::Duration::from_std(cache_ttl).unwrap_or_default()
                 {
+                    stats.cached += 1;
                     continue; // 缓存未过期，跳过更新
                 }
             }
 
-            // 获取信号量许可
-            let _permit = self.semaphore.acquire().await?;
+            let service = Arc::clone(self);
+            let config = Arc::clone(config);
+            tasks.spawn(async move {
+                let outcome = service.update_symbol(&symbol, &config, deadline).await;
+                (symbol, outcome)
+            });
+        }
 
-            // 检查请求限制
-            if self.request_count.load(Ordering::Relaxed) >= config.max_requests_per_hour {
-                warn!("Rate limit reached, waiting for next hour");
-                time::sleep(Duration::from_secs(60)).await;
-                continue;
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((symbol, outcome)) => stats.record(symbol, outcome),
+                Err(e) => {
+                    error!("Price update task failed: {}", e);
+                    stats.failed += 1;
+                }
             }
+        }
 
-            match self
-                .fetch_price_with_retry(&symbol.symbol, config.max_retries)
-                .await
-            {
-                Ok(price) => {
-                    self.request_count.fetch_add(1, Ordering::Relaxed);
-                    if let Err(e) = self.save_price(&price).await {
-                        error!("Failed to save price for {}: {}", symbol.symbol, e);
+        stats.log(cycle, cycle_started.elapsed());
+    }
+
+    /// 获取并保存单个股票的价格
+    async fn update_symbol(
+        &self,
+        symbol: &str,
+        config: &PriceFetcherConfig,
+        deadline: time::Instant,
+    ) -> FetchOutcome {
+        let started = time::Instant::now();
+        let fetched = time::timeout_at(
+            deadline,
+            self.fetch_price_with_retry(symbol, config.max_retries),
+        )
+        .await;
+        let latency = started.elapsed();
+        let timed_out = fetched.is_err();
+
+        let error_msg = match fetched {
+            Ok(Ok(price)) => {
+                match self.validate_price(&price, Some(deadline)).await {
+                    Ok(Validation::Accepted) => {}
+                    Ok(Validation::Rejected(reason)) => {
+                        warn!("Rejected price {} for {}: {}", price.price, symbol, reason);
+                        return FetchOutcome::Rejected;
+                    }
+                    Err(e) => {
+                        error!("Failed to validate price for {}: {}", symbol, e);
+                        return FetchOutcome::Failed;
                     }
                 }
-                Err(e) => {
-                    error!("Failed to fetch price for {}: {}", symbol.symbol, e);
-                    // 如果API失败，尝试使用模拟数据作为后备
-                    if let Ok(fallback_price) = self.fetch_fallback_price(&symbol.symbol).await {
-                        warn!("Using fallback price for {}", symbol.symbol);
-                        if let Err(e) = self.save_price(&fallback_price).await {
-                            error!("Failed to save fallback price for {}: {}", symbol.symbol, e);
-                        }
+                self.cache_price(&price).await;
+                if let Err(e) = self.save_price(&price, ORIGIN_POLLER).await {
+                    error!("Failed to save price for {}: {}", symbol, e);
+                    return FetchOutcome::Failed;
+                }
+                if let Err(e) = self.record_feed_success(symbol).await {
+                    error!("Failed to record feed status for {}: {}", symbol, e);
+                }
+                return FetchOutcome::Updated(latency);
+            }
+            Ok(Err(e)) => {
+                error!("Failed to fetch price for {}: {}", symbol, e);
+                e.to_string()
+            }
+            Err(_) => {
+                warn!(
+                    "Price fetch for {} exceeded the cycle deadline after {}ms",
+                    symbol,
+                    latency.as_millis()
+                );
+                "fetch exceeded the update cycle deadline".to_string()
+            }
+        };
+
+        // 记录失败，界面据此显示行情陈旧
+        if let Err(e) = self.record_feed_failure(symbol, &error_msg).await {
+            error!("Failed to record feed status for {}: {}", symbol, e);
+        }
+
+        // 仅在显式启用时写入模拟后备价格，且标记为 synthetic
+        if config.fallback_prices_enabled {
+            match self.fetch_fallback_price(symbol).await {
+                Ok(fallback_price) => {
+                    warn!("Using synthetic fallback price for {}", symbol);
+                    if let Err(e) = self.save_price(&fallback_price, ORIGIN_POLLER).await {
+                        error!("Failed to save fallback price for {}: {}", symbol, e);
                     }
+                    return FetchOutcome::Fallback(latency);
                 }
+                Err(e) => warn!("No fallback price for {}: {}", symbol, e),
             }
         }
 
+        if timed_out {
+            FetchOutcome::TimedOut
+        } else {
+            FetchOutcome::Failed
+        }
+    }
+
+    /// 记录一次成功的真实行情获取
+    async fn record_feed_success(&self, symbol: &str) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+            INSERT INTO price_feed_status (symbol, last_success_at, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(symbol) DO UPDATE SET
+                last_success_at = excluded.last_success_at,
+                last_error = NULL,
+                updated_at = excluded.updated_at
+            "#,
+            symbol,
+            now,
+            now,
+        )
+        .execute(&self.db)
+        .await?;
+
         Ok(())
     }
 
-    async fn get_cached_price(&self, symbol: &str) -> Option<PriceCache> {
-        self.cache.read().await.get(symbol).cloned()
+    /// 记录一次失败的行情获取
+    async fn record_feed_failure(&self, symbol: &str, error_msg: &str) -> Result<()> {
+        let now = Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+            INSERT INTO price_feed_status (symbol, last_failure_at, last_error, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(symbol) DO UPDATE SET
+                last_failure_at = excluded.last_failure_at,
+                last_error = excluded.last_error,
+                updated_at = excluded.updated_at
+            "#,
+            symbol,
+            now,
+            error_msg,
+            now,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
     }
 
-    async fn set_cached_price(&self, symbol: String, price: PriceCache) {
-        self.cache.write().await.insert(symbol, price);
+    /// 行情缓存，轮询、推送与接口共用
+    pub fn quote_cache(&self) -> &QuoteCache {
+        &self.cache
     }
 
+    /// 实时获取单个股票的报价：按市场路由数据源，校验后保存并检查预警
+    pub async fn fetch_live_quote(&self, symbol: &str) -> Result<Quote> {
+        // 美股盘前盘后优先返回盘前盘后成交价，没有成交时返回常规时段价格
+        if let Some(session) = self.current_extended_session(symbol).await {
+            let fetched = self
+                .call_provider(PROVIDER_YAHOO, self.fetch_yahoo_extended_price(symbol))
+                .await;
+            match fetched {
+                Ok(Some(price)) if self.accept_extended_price(&price, None).await? => {
+                    return Ok(price.to_quote());
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to fetch {} price for {}: {}", session, symbol, e),
+            }
+        }
+        let price = self.fetch_price_with_retry(symbol, 1).await;
+        let price = match price {
+            Ok(price) => price,
+            Err(e) => {
+                if let Err(status_err) = self.record_feed_failure(symbol, &e.to_string()).await {
+                    error!(
+                        "Failed to record feed status for {}: {}",
+                        symbol, status_err
+                    );
+                }
+                return Err(e);
+            }
+        };
+
+        if let Validation::Rejected(reason) = self.validate_price(&price, None).await? {
+            return Err(anyhow::anyhow!(
+                "Price {} for {} rejected: {}",
+                price.price,
+                symbol,
+                reason
+            ));
+        }
+        self.cache_price(&price).await;
+        self.save_price(&price, ORIGIN_ON_DEMAND).await?;
+        if let Err(e) = self.record_feed_success(symbol).await {
+            error!("Failed to record feed status for {}: {}", symbol, e);
+        }
+
+        Ok(price.to_quote())
+    }
+
+    /// 带重试地获取价格
+    ///
+    /// 并发名额只在发送请求期间占用，重试前的退避等待与数据源令牌等待都不占名额。
     async fn fetch_price_with_retry(&self, symbol: &str, max_retries: u32) -> Result<StockPrice> {
         let mut retries = 0;
         let mut last_error = None;
@@ -227,28 +1146,88 @@ impl PriceService {
     }
 
     async fn fetch_price(&self, symbol: &str) -> Result<StockPrice> {
+        // 加密货币优先使用交易所行情，失败时回退到Yahoo
+        if let Some(venue_symbol) = self.crypto_venue_symbol(symbol).await {
+            match self.fetch_crypto_price(symbol, &venue_symbol).await {
+                Ok(price) => return Ok(price),
+                Err(e) => warn!(
+                    "Crypto exchange fetch failed for {}, falling back to Yahoo: {}",
+                    symbol, e
+                ),
+            }
+            return self.fetch_us_stock_price(symbol).await;
+        }
+
         // 根据股票代码判断市场类型
-        if self.is_china_stock(symbol) {
-            self.fetch_china_stock_price(symbol).await
-        } else {
-            self.fetch_us_stock_price(symbol).await
+        match Market::from_symbol(symbol) {
+            Market::CN => self.fetch_china_stock_price(symbol).await,
+            Market::HK => self.fetch_hk_stock_price(symbol).await,
+            Market::CnFutures => {
+                self.call_provider(PROVIDER_SINA, self.fetch_china_stock_price_sina(symbol))
+                    .await
+            }
+            Market::US | Market::Futures | Market::FX | Market::Crypto => {
+                self.fetch_us_stock_price(symbol).await
+            }
         }
     }
 
-    // 判断是否为A股
-    fn is_china_stock(&self, symbol: &str) -> bool {
-        symbol.ends_with(".SZ") || symbol.ends_with(".SS")
+    // 获取A股价格（沪深北） - 优先新浪财经，失败或熔断时使用腾讯财经
+    async fn fetch_china_stock_price(&self, symbol: &str) -> Result<StockPrice> {
+        match self
+            .call_provider(PROVIDER_SINA, self.fetch_china_stock_price_sina(symbol))
+            .await
+        {
+            Ok(price) => Ok(price),
+            Err(e) => {
+                warn!("Sina fetch for {} failed, trying Tencent: {}", symbol, e);
+                self.call_provider(
+                    PROVIDER_TENCENT,
+                    self.fetch_china_stock_price_tencent(symbol),
+                )
+                .await
+            }
+        }
     }
 
-    // 获取A股价格 - 使用新浪财经API
-    async fn fetch_china_stock_price(&self, symbol: &str) -> Result<StockPrice> {
+    // 获取港股价格 - 优先腾讯财经，其次新浪财经，都不可用时使用Yahoo
+    async fn fetch_hk_stock_price(&self, symbol: &str) -> Result<StockPrice> {
+        let tencent_error = match self
+            .call_provider(
+                PROVIDER_TENCENT,
+                self.fetch_china_stock_price_tencent(symbol),
+            )
+            .await
+        {
+            Ok(price) => return Ok(price),
+            Err(e) => e,
+        };
+        warn!(
+            "Tencent fetch for {} failed, trying Sina: {}",
+            symbol, tencent_error
+        );
+
+        match self
+            .call_provider(PROVIDER_SINA, self.fetch_china_stock_price_sina(symbol))
+            .await
+        {
+            Ok(price) => Ok(price),
+            Err(e) => {
+                warn!("Sina fetch for {} failed, trying Yahoo: {}", symbol, e);
+                self.fetch_us_stock_price(symbol).await
+            }
+        }
+    }
+
+    // 新浪财经API
+    async fn fetch_china_stock_price_sina(&self, symbol: &str) -> Result<StockPrice> {
         // 转换股票代码格式
         let sina_symbol = self.convert_to_sina_format(symbol);
         let url = format!("https://hq.sinajs.cn/list={sina_symbol}");
 
-        info!("Fetching A-share price for {} from Sina Finance", symbol);
+        info!("Fetching {} price from Sina Finance", symbol);
 
-        let response = self
+        let request = self
             .client
             .get(&url)
             .header("Referer", "https://finance.sina.com.cn")
@@ -256,46 +1235,31 @@ impl PriceService {
                 "User-Agent",
                 "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
             )
-            .timeout(Duration::from_secs(5))
-            .send()
-            .await?;
+            .timeout(Duration::from_secs(5));
 
-        if !response.status().is_success() {
-            // 如果新浪API失败，尝试腾讯API作为备用
-            return self.fetch_china_stock_price_tencent(symbol).await;
+        let (status, text) = self.rate_limiter.send(PROVIDER_SINA, request).await?;
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("HTTP error: {}", status));
         }
 
-        let text = response.text().await?;
         if let Some(stock_price) = self.parse_sina_response(&text, symbol)? {
-            // 更新缓存
-            self.set_cached_price(
-                symbol.to_string(),
-                PriceCache {
-                    price: stock_price.price,
-                    volume: stock_price.volume,
-                    timestamp: stock_price.timestamp,
-                },
-            )
-            .await;
-
             Ok(stock_price)
         } else {
-            // 新浪API解析失败，尝试腾讯API
-            self.fetch_china_stock_price_tencent(symbol).await
+            Err(anyhow::anyhow!(
+                "Failed to parse Sina response for {}",
+                symbol
+            ))
         }
     }
 
-    // 腾讯财经API作为A股数据的备用源
+    // 腾讯财经API，A股的备用源、港股的首选源
     async fn fetch_china_stock_price_tencent(&self, symbol: &str) -> Result<StockPrice> {
         let tencent_symbol = self.convert_to_tencent_format(symbol);
         let url = format!("https://qt.gtimg.cn/q={tencent_symbol}");
 
-        info!(
-            "Fetching A-share price for {} from Tencent Finance (fallback)",
-            symbol
-        );
+        info!("Fetching {} price from Tencent Finance", symbol);
 
-        let response = self
+        let request = self
             .client
             .get(&url)
             .header("Referer", "https://stockapp.finance.qq.com")
@@ -303,27 +1267,14 @@ impl PriceService {
                 "User-Agent",
                 "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
             )
-            .timeout(Duration::from_secs(5))
-            .send()
-            .await?;
+            .timeout(Duration::from_secs(5));
 
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
+        let (status, text) = self.rate_limiter.send(PROVIDER_TENCENT, request).await?;
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("HTTP error: {}", status));
         }
 
-        let text = response.text().await?;
         if let Some(stock_price) = self.parse_tencent_response(&text, symbol)? {
-            // 更新缓存
-            self.set_cached_price(
-                symbol.to_string(),
-                PriceCache {
-                    price: stock_price.price,
-                    volume: stock_price.volume,
-                    timestamp: stock_price.timestamp,
-                },
-            )
-            .await;
-
             Ok(stock_price)
         } else {
             Err(anyhow::anyhow!(
@@ -333,27 +1284,28 @@ impl PriceService {
         }
     }
 
-    // 获取美股价格 - 保持原有的Yahoo Finance API
+    // 获取美股价格 - Yahoo Finance API
     async fn fetch_us_stock_price(&self, symbol: &str) -> Result<StockPrice> {
+        self.call_provider(PROVIDER_YAHOO, self.fetch_yahoo_price(symbol))
+            .await
+    }
+
+    async fn fetch_yahoo_price(&self, symbol: &str) -> Result<StockPrice> {
         let url = format!("https://query1.finance.yahoo.com/v8/finance/chart/{symbol}");
 
-        info!("Fetching US stock price for {} from Yahoo Finance", symbol);
+        info!("Fetching {} price from Yahoo Finance", symbol);
 
-        let response = self
-            .client
-            .get(&url)
-            .header(
-                "User-Agent",
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
-            )
-            .send()
-            .await?;
+        let request = self.client.get(&url).header(
+            "User-Agent",
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
+        );
 
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
+        let (status, text) = self.rate_limiter.send(PROVIDER_YAHOO, request).await?;
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("HTTP error: {}", status));
         }
 
-        let yahoo_response: YahooQuoteResponse = response.json().await?;
+        let yahoo_response: YahooQuoteResponse = serde_json::from_str(&text)?;
 
         if let Some(error) = yahoo_response.chart.error {
             return Err(anyhow::anyhow!(
@@ -384,45 +1336,239 @@ impl PriceService {
             volume,
             timestamp: Utc::now(),
             name_en,
+            quality: DataQuality::Live,
+            source: PROVIDER_YAHOO,
+            exchange_time: result
+                .meta
+                .regular_market_time
+                .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+            raw_hash: Some(raw_response_hash(&text)),
+            session: MarketSession::Regular,
         };
 
-        // 更新缓存
-        self.set_cached_price(
-            symbol.to_string(),
-            PriceCache {
-                price,
-                volume,
-                timestamp: stock_price.timestamp,
-            },
+        Ok(stock_price)
+    }
+
+    /// 获取美股盘前盘后的最新成交价，当前时段还没有成交时返回 None
+    async fn fetch_yahoo_extended_price(&self, symbol: &str) -> Result<Option<StockPrice>> {
+        let url = format!(
+            "https://query1.finance.yahoo.com/v8/finance/chart/{symbol}?interval=1m&range=1d&includePrePost=true"
+        );
+
+        info!(
+            "Fetching {} extended-hours price from Yahoo Finance",
+            symbol
+        );
+
+        let request = self.client.get(&url).header(
+            "User-Agent",
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
+        );
+
+        let (status, text) = self.rate_limiter.send(PROVIDER_YAHOO, request).await?;
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("HTTP error: {}", status));
+        }
+
+        let yahoo_response: YahooQuoteResponse = serde_json::from_str(&text)?;
+        if let Some(error) = yahoo_response.chart.error {
+            return Err(anyhow::anyhow!(
+                "Yahoo Finance error: {} - {}",
+                error.code,
+                error.description
+            ));
+        }
+        let result = yahoo_response
+            .chart
+            .result
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("No data returned for symbol {}", symbol))?;
+
+        let Some((session, price, exchange_time)) = latest_extended_trade(&result) else {
+            return Ok(None);
+        };
+        Ok(Some(StockPrice {
+            symbol: symbol.to_string(),
+            price,
+            volume: result.meta.regular_market_volume.unwrap_or(0),
+            timestamp: Utc::now(),
+            name_en: result.meta.short_name.clone(),
+            quality: DataQuality::Live,
+            source: PROVIDER_YAHOO,
+            exchange_time: Some(exchange_time),
+            raw_hash: Some(raw_response_hash(&text)),
+            session,
+        }))
+    }
+
+    /// 从数据库加载加密货币交易对映射，未配置映射的交易对按交易所命名规则推导
+    async fn refresh_crypto_symbols(&self) -> Result<()> {
+        let Some(crypto) = &self.crypto else {
+            return Ok(());
+        };
+        let venue = crypto.venue();
+        let venue_name = venue.name();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT c.symbol, c.base_symbol, c.quote_symbol, m.venue_symbol as "venue_symbol?"
+            FROM crypto_stocks c
+            LEFT JOIN crypto_symbol_mappings m ON m.symbol = c.symbol AND m.venue = ?
+            WHERE c.status = 'active' AND c.category IS NOT 'Stablecoin'
+            "#,
+            venue_name
         )
-        .await;
+        .fetch_all(&self.db)
+        .await?;
+
+        let mapping: HashMap<String, String> = rows
+            .into_iter()
+            .filter_map(|row| {
+                let venue_symbol = row
+                    .venue_symbol
+                    .or_else(|| venue.default_venue_symbol(&row.base_symbol, &row.quote_symbol))?;
+                Some((row.symbol, venue_symbol))
+            })
+            .collect();
+
+        *self.crypto_symbols.write().await = mapping;
+        Ok(())
+    }
+
+    /// 返回交易所交易对名称，非加密货币或未启用交易所数据源时返回 None
+    async fn crypto_venue_symbol(&self, symbol: &str) -> Option<String> {
+        self.crypto.as_ref()?;
+        self.crypto_symbols.read().await.get(symbol).cloned()
+    }
+
+    // 获取加密货币价格 - 使用交易所行情接口
+    async fn fetch_crypto_price(&self, symbol: &str, venue_symbol: &str) -> Result<StockPrice> {
+        let crypto = self
+            .crypto
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Crypto exchange provider is disabled"))?;
+        let ticker = self
+            .call_provider(crypto.venue().name(), crypto.fetch_ticker(venue_symbol))
+            .await?;
+
+        let stock_price = StockPrice {
+            symbol: symbol.to_string(),
+            price: ticker.last_price,
+            volume: ticker.volume.round() as i64,
+            timestamp: Utc::now(),
+            name_en: None,
+            quality: DataQuality::Live,
+            source: crypto.venue().name(),
+            exchange_time: Some(ticker.timestamp),
+            raw_hash: Some(ticker.raw_hash),
+            session: MarketSession::Regular,
+        };
+
+        Ok(stock_price)
+    }
+
+    /// 用交易所日K线回填缺失的历史价格（每个交易对每次运行只回填一次）
+    async fn backfill_crypto_history(
+        &self,
+        symbol: &str,
+        venue_symbol: &str,
+        days: u32,
+    ) -> Result<()> {
+        if days == 0 || self.crypto_backfilled.read().await.contains(symbol) {
+            return Ok(());
+        }
+        let Some(crypto) = &self.crypto else {
+            return Ok(());
+        };
+
+        let klines = crypto.fetch_daily_klines(venue_symbol, days).await?;
+        let venue_name = crypto.venue().name();
+        let today = Utc::now().date_naive();
+        let created_at = Utc::now().naive_utc();
+        let mut prev_close: Option<f64> = None;
+        let mut inserted = 0u64;
+
+        for kline in klines {
+            let change_percent = prev_close.map(|prev| (kline.close - prev) / prev * 100.0);
+            prev_close = Some(kline.close);
+            // 当日K线尚未收盘，由实时行情写入
+            if kline.date >= today {
+                continue;
+            }
 
-        Ok(stock_price)
+            let volume = kline.volume.round() as i64;
+            let result = sqlx::query!(
+                r#"
+                INSERT INTO price_history (symbol, date, open_price, high_price, low_price, close_price, volume, daily_change_percent, created_at,
+                                           source, fetch_origin, fetched_at, raw_hash)
+                SELECT ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
+                WHERE NOT EXISTS (SELECT 1 FROM price_history WHERE symbol = ? AND date = ?)
+                "#,
+                symbol,
+                kline.date,
+                kline.open,
+                kline.high,
+                kline.low,
+                kline.close,
+                volume,
+                change_percent,
+                created_at,
+                venue_name,
+                ORIGIN_BACKFILL,
+                created_at,
+                kline.raw_hash,
+                symbol,
+                kline.date,
+            )
+            .execute(&self.db)
+            .await?;
+            inserted += result.rows_affected();
+        }
+
+        info!(
+            "Backfilled {} daily bars for {} from {}",
+            inserted,
+            symbol,
+            crypto.venue().name()
+        );
+        self.crypto_backfilled
+            .write()
+            .await
+            .insert(symbol.to_string());
+        Ok(())
     }
 
-    // A股股票代码格式转换
+    // 股票代码格式转换: 000001.SZ -> sz000001, 600036.SH/.SS -> sh600036,
+    // 430047.BJ -> bj430047, 0700.HK -> rt_hk00700（新浪港股实时行情需要 rt_ 前缀），
+    // AU2512.SHF -> nf_AU2512
     fn convert_to_sina_format(&self, symbol: &str) -> String {
-        if symbol.ends_with(".SZ") {
-            format!("sz{}", &symbol[..6])
-        } else if symbol.ends_with(".SS") {
-            format!("sh{}", &symbol[..6])
-        } else {
-            symbol.to_string()
+        match symbol.rsplit_once('.') {
+            Some((code, "HK")) => format!("rt_hk{code:0>5}"),
+            Some((code, "SHF")) => format!("nf_{}", code.to_uppercase()),
+            _ => self.convert_to_tencent_format(symbol),
         }
     }
 
+    // 股票代码格式转换: 与新浪相同，港股为 hk00700
     fn convert_to_tencent_format(&self, symbol: &str) -> String {
-        if symbol.ends_with(".SZ") {
-            format!("sz{}", &symbol[..6])
-        } else if symbol.ends_with(".SS") {
-            format!("sh{}", &symbol[..6])
-        } else {
-            symbol.to_string()
+        match symbol.rsplit_once('.') {
+            Some((code, "SZ")) => format!("sz{code}"),
+            Some((code, "SS" | "SH")) => format!("sh{code}"),
+            Some((code, "BJ")) => format!("bj{code}"),
+            Some((code, "HK")) => format!("hk{code:0>5}"),
+            _ => symbol.to_string(),
         }
     }
 
     // 解析新浪财经API响应
     fn parse_sina_response(&self, text: &str, symbol: &str) -> Result<Option<StockPrice>> {
+        match Market::from_symbol(symbol) {
+            Market::HK => return self.parse_sina_hk_response(text, symbol),
+            Market::CnFutures => return self.parse_sina_futures_response(text, symbol),
+            _ => {}
+        }
+
         // 新浪API返回格式: var hq_str_sz000001="平安银行,27.55,27.25,26.91,27.60,26.20,26.91,26.92,22114263,589824680,..."
         if let Some(start) = text.find('"') {
             if let Some(end) = text.rfind('"') {
@@ -441,12 +1587,23 @@ impl PriceService {
                         .parse()
                         .map_err(|e| anyhow::anyhow!("Failed to parse volume: {}", e))?;
 
+                    // 第30、31个字段为行情日期与时间（北京时间）
+                    let exchange_time = parse_china_exchange_time(
+                        &format!("{} {}", parts[30], parts[31]),
+                        &["%Y-%m-%d %H:%M:%S"],
+                    );
+
                     return Ok(Some(StockPrice {
                         symbol: symbol.to_string(),
                         price: current_price,
                         volume,
                         timestamp: Utc::now(),
                         name_en: Some(name),
+                        quality: DataQuality::Live,
+                        source: PROVIDER_SINA,
+                        exchange_time,
+                        raw_hash: Some(raw_response_hash(text)),
+                        session: MarketSession::Regular,
                     }));
                 }
             }
@@ -454,9 +1611,88 @@ impl PriceService {
         Ok(None)
     }
 
+    // 解析新浪财经港股实时行情
+    fn parse_sina_hk_response(&self, text: &str, symbol: &str) -> Result<Option<StockPrice>> {
+        // 新浪港股返回格式: var hq_str_rt_hk00700="TENCENT,腾讯控股,开盘,昨收,最高,最低,现价,涨跌额,涨跌幅,买一,卖一,成交额,成交量,..."
+        let (Some(start), Some(end)) = (text.find('"'), text.rfind('"')) else {
+            return Ok(None);
+        };
+        let parts: Vec<&str> = text[start + 1..end].split(',').collect();
+        if parts.len() < 13 {
+            return Ok(None);
+        }
+
+        let current_price: f64 = parts[6]
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Failed to parse current price: {}", e))?;
+        let volume: i64 = parts[12]
+            .parse::<f64>()
+            .map_err(|e| anyhow::anyhow!("Failed to parse volume: {}", e))?
+            as i64;
+
+        // 第17、18个字段为行情日期与时间（香港时间）
+        let exchange_time = parts.get(17).zip(parts.get(18)).and_then(|(date, time)| {
+            parse_china_exchange_time(
+                &format!("{date} {time}"),
+                &["%Y/%m/%d %H:%M:%S", "%Y/%m/%d %H:%M"],
+            )
+        });
+
+        Ok(Some(StockPrice {
+            symbol: symbol.to_string(),
+            price: current_price,
+            volume,
+            timestamp: Utc::now(),
+            name_en: Some(parts[0].to_string()),
+            quality: DataQuality::Live,
+            source: PROVIDER_SINA,
+            exchange_time,
+            raw_hash: Some(raw_response_hash(text)),
+            session: MarketSession::Regular,
+        }))
+    }
+
+    // 解析新浪财经国内期货行情
+    fn parse_sina_futures_response(&self, text: &str, symbol: &str) -> Result<Option<StockPrice>> {
+        // 新浪期货返回格式: var hq_str_nf_AU2512="黄金2512,时间,开盘,最高,最低,昨收,买价,卖价,最新价,结算价,昨结算,买量,卖量,持仓量,成交量,..."
+        let (Some(start), Some(end)) = (text.find('"'), text.rfind('"')) else {
+            return Ok(None);
+        };
+        let parts: Vec<&str> = text[start + 1..end].split(',').collect();
+        if parts.len() < 15 {
+            return Ok(None);
+        }
+
+        let current_price: f64 = parts[8]
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Failed to parse current price: {}", e))?;
+        let volume: i64 = parts[14]
+            .parse::<f64>()
+            .map_err(|e| anyhow::anyhow!("Failed to parse volume: {}", e))?
+            as i64;
+
+        // 第1个字段为时间 HHMMSS，第17个字段为交易日期（北京时间）
+        let exchange_time = parts.get(17).and_then(|date| {
+            parse_china_exchange_time(&format!("{} {}", date, parts[1]), &["%Y-%m-%d %H%M%S"])
+        });
+
+        Ok(Some(StockPrice {
+            symbol: symbol.to_string(),
+            price: current_price,
+            volume,
+            timestamp: Utc::now(),
+            name_en: Some(parts[0].to_string()),
+            quality: DataQuality::Live,
+            source: PROVIDER_SINA,
+            exchange_time,
+            raw_hash: Some(raw_response_hash(text)),
+            session: MarketSession::Regular,
+        }))
+    }
+
     // 解析腾讯财经API响应
     fn parse_tencent_response(&self, text: &str, symbol: &str) -> Result<Option<StockPrice>> {
-        // 腾讯API返回格式: v_sz000001="51~平安银行~000001~11.84~11.70~11.84~..."
+        // 腾讯API返回格式: v_sz000001="51~平安银行~000001~11.84~11.70~11.84~..."，港股 v_hk00700 字段位置相同
         if let Some(start) = text.find('"') {
             if let Some(end) = text.rfind('"') {
                 let data_str = &text[start + 1..end];
@@ -467,10 +1703,20 @@ impl PriceService {
                     let current_price: f64 = parts[3]
                         .parse()
                         .map_err(|e| anyhow::anyhow!("Failed to parse current price: {}", e))?;
-                    let volume: i64 = parts[6]
+                    let volume = parts[6]
                         .parse::<f64>()
-                        .map_err(|e| anyhow::anyhow!("Failed to parse volume: {}", e))?
-                        as i64;
+                        .map_err(|e| anyhow::anyhow!("Failed to parse volume: {}", e))?;
+                    // A股成交量单位为手（100股），与新浪的股数统一，港股已是股数
+                    let volume = match Market::from_symbol(symbol) {
+                        Market::CN => volume * 100.0,
+                        _ => volume,
+                    } as i64;
+
+                    // 第30个字段为行情时间：A股 20240105150003，港股 2024/01/05 16:08:09
+                    let exchange_time = parse_china_exchange_time(
+                        parts[30],
+                        &["%Y%m%d%H%M%S", "%Y/%m/%d %H:%M:%S"],
+                    );
 
                     return Ok(Some(StockPrice {
                         symbol: symbol.to_string(),
@@ -478,6 +1724,11 @@ impl PriceService {
                         volume,
                         timestamp: Utc::now(),
                         name_en: Some(name),
+                        quality: DataQuality::Live,
+                        source: PROVIDER_TENCENT,
+                        exchange_time,
+                        raw_hash: Some(raw_response_hash(text)),
+                        session: MarketSession::Regular,
                     }));
                 }
             }
@@ -485,15 +1736,15 @@ impl PriceService {
         Ok(None)
     }
 
-    // 后备方案：使用模拟数据
+    // 后备方案：使用模拟数据（标记为 synthetic，不会触发预警）
     async fn fetch_fallback_price(&self, symbol: &str) -> Result<StockPrice> {
-        // 获取上次价格作为基准
+        // 获取上次真实价格作为基准，没有真实价格时不生成模拟数据
         let last_price = sqlx::query!(
             r#"
             SELECT close_price as price
             FROM price_history
-            WHERE symbol = ?
-            ORDER BY date DESC
+            WHERE symbol = ? AND data_quality = 'live'
+            ORDER BY date DESC, created_at DESC
             LIMIT 1
             "#,
             symbol
@@ -501,7 +1752,7 @@ impl PriceService {
         .fetch_optional(&self.db)
         .await?
         .map(|row| row.price)
-        .unwrap_or(100.0); // 如果没有历史价格，默认从100开始
+        .ok_or_else(|| anyhow::anyhow!("No live price history for {}", symbol))?;
 
         // 生成-1%到+1%的随机变化
         let change_percent = (rand::random::<f64>() - 0.5) * 0.02; // -0.01 到 +0.01
@@ -511,27 +1762,280 @@ impl PriceService {
         Ok(StockPrice {
             symbol: symbol.to_string(),
             price: new_price,
-            volume: (rand::random::<i64>() % 10000) + 1000, // 1000-11000之间的随机成交量
+            volume: (rand::random::<u64>() % 10000) as i64 + 1000, // 1000-11000之间的随机成交量
             timestamp: Utc::now(),
             name_en: Some(format!("{symbol} Corporation")), // 为模拟数据提供一个通用公司名
+            quality: DataQuality::Synthetic,
+            source: SOURCE_SYNTHETIC,
+            exchange_time: None,
+            raw_hash: None,
+            session: MarketSession::Regular,
         })
     }
 
-    async fn save_price(&self, price: &StockPrice) -> Result<()> {
+    /// 写入前校验价格
+    ///
+    /// 相对上一次有效价格偏离过大的行情需要第二个数据源确认；启用A股交叉核对时，
+    /// 新浪与腾讯的价格不一致也会被拒绝。被拒绝的行情写入隔离表。
+    async fn validate_price(
+        &self,
+        price: &StockPrice,
+        deadline: Option<time::Instant>,
+    ) -> Result<Validation> {
+        if !self.validator.enabled() || price.quality != DataQuality::Live {
+            return Ok(Validation::Accepted);
+        }
+
+        let reference = self.last_good_price(&price.symbol).await?;
+        let deviation = reference.map(|reference| deviation_percent(price.price, reference));
+        let cross_check =
+            self.validator.cn_cross_check() && Market::from_symbol(&price.symbol) == Market::CN;
+
+        let verdict = self.validator.check(price.price, reference);
+        if verdict == Verdict::Accept && !cross_check {
+            return Ok(Validation::Accepted);
+        }
+
+        // 向第二个数据源确认，轮询时与主请求共用更新周期的截止时间
+        let alternate = match deadline {
+            Some(deadline) => time::timeout_at(deadline, self.fetch_alternate_price(price))
+                .await
+                .unwrap_or_else(|_| {
+                    Some(Err(anyhow::anyhow!(
+                        "confirmation fetch exceeded the cycle deadline"
+                    )))
+                }),
+            None => self.fetch_alternate_price(price).await,
+        };
+        let confirmation = match alternate {
+            Some(Ok(confirm)) => Some((confirm.source, confirm.price)),
+            Some(Err(e)) => {
+                warn!("Confirmation fetch for {} failed: {}", price.symbol, e);
+                None
+            }
+            None => None,
+        };
+
+        let reason = match (confirmation, &verdict) {
+            (Some((_, confirm_price)), _)
+                if self.validator.confirms(price.price, confirm_price) =>
+            {
+                return Ok(Validation::Accepted);
+            }
+            (Some((confirm_source, confirm_price)), _) => {
+                let reason = format!(
+                    "{} price {} disagrees with {} price {}",
+                    price.source, price.price, confirm_source, confirm_price
+                );
+                self.quarantine_price(
+                    price,
+                    reference,
+                    deviation,
+                    Some((confirm_source, confirm_price)),
+                    &reason,
+                )
+                .await?;
+                return Ok(Validation::Rejected(reason));
+            }
+            // 交叉核对的数据源不可用且价格本身正常时放行
+            (None, Verdict::Accept) => return Ok(Validation::Accepted),
+            (None, Verdict::NeedsConfirmation { deviation_percent }) => format!(
+                "deviates {:.2}% from last good price without confirmation",
+                deviation_percent
+            ),
+        };
+
+        // 开启 repeat_confirm 时，没有第二个数据源可用的价格可由同一数据源短时间内的重复一致价格确认
+        if self.validator.repeat_confirms() {
+            if let Some(previous) = self.recent_quarantined_price(price).await? {
+                if self.validator.confirms(price.price, previous) {
+                    info!(
+                        "Accepting {} price {} for {} after repeated consistent prints",
+                        price.source, price.price, price.symbol
+                    );
+                    return Ok(Validation::Accepted);
+                }
+            }
+        }
+
+        self.quarantine_price(price, reference, deviation, None, &reason)
+            .await?;
+        Ok(Validation::Rejected(reason))
+    }
+
+    /// 上一次有效价格，优先使用内存缓存
+    async fn last_good_price(&self, symbol: &str) -> Result<Option<f64>> {
+        if let Some(cached) = self.cache.get_local(symbol).await {
+            return Ok(Some(cached.price));
+        }
+
+        let row = sqlx::query!(
+            r#"
+            SELECT close_price as price
+            FROM price_history
+            WHERE symbol = ? AND data_quality = 'live'
+            ORDER BY date DESC, created_at DESC
+            LIMIT 1
+            "#,
+            symbol
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(|row| row.price))
+    }
+
+    /// 从另一个数据源获取同一股票的价格用于确认，没有其他数据源时返回 None
+    async fn fetch_alternate_price(&self, price: &StockPrice) -> Option<Result<StockPrice>> {
+        let symbol = price.symbol.as_str();
+        match price.source {
+            // 国内期货只有新浪一个数据源
+            PROVIDER_SINA if Market::from_symbol(symbol) == Market::CnFutures => None,
+            PROVIDER_SINA => Some(
+                self.call_provider(
+                    PROVIDER_TENCENT,
+                    self.fetch_china_stock_price_tencent(symbol),
+                )
+                .await,
+            ),
+            PROVIDER_TENCENT => Some(
+                self.call_provider(PROVIDER_SINA, self.fetch_china_stock_price_sina(symbol))
+                    .await,
+            ),
+            PROVIDER_YAHOO if Market::from_symbol(symbol) == Market::HK => Some(
+                self.call_provider(
+                    PROVIDER_TENCENT,
+                    self.fetch_china_stock_price_tencent(symbol),
+                )
+                .await,
+            ),
+            PROVIDER_YAHOO => {
+                let venue_symbol = self.crypto_venue_symbol(symbol).await?;
+                Some(self.fetch_crypto_price(symbol, &venue_symbol).await)
+            }
+            _ if self.crypto_venue_symbol(symbol).await.is_some() => {
+                Some(self.fetch_us_stock_price(symbol).await)
+            }
+            _ => None,
+        }
+    }
+
+    /// 同一数据源最近被隔离的价格
+    async fn recent_quarantined_price(&self, price: &StockPrice) -> Result<Option<f64>> {
+        let since =
+            (Utc::now() - chrono::Duration::minutes(REPEAT_CONFIRM_WINDOW_MINUTES)).naive_utc();
+        let row = sqlx::query!(
+            r#"
+            SELECT price
+            FROM price_quarantine
+            WHERE symbol = ? AND source = ? AND created_at >= ?
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            price.symbol,
+            price.source,
+            since
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(|row| row.price))
+    }
+
+    /// 写入隔离表
+    async fn quarantine_price(
+        &self,
+        price: &StockPrice,
+        reference: Option<f64>,
+        deviation: Option<f64>,
+        confirmation: Option<(&str, f64)>,
+        reason: &str,
+    ) -> Result<()> {
+        let (confirm_source, confirm_price) = confirmation.unzip();
+        let created_at = Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+            INSERT INTO price_quarantine (symbol, price, source, reference_price, deviation_percent, confirm_source, confirm_price, reason, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            price.symbol,
+            price.price,
+            price.source,
+            reference,
+            deviation,
+            confirm_source,
+            confirm_price,
+            reason,
+            created_at,
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 价格通过校验后更新缓存
+    async fn cache_price(&self, price: &StockPrice) {
+        self.cache.put(price.to_quote()).await;
+    }
+
+    /// 保存价格并记录来源，`origin` 为触发本次写入的方式
+    async fn save_price(&self, price: &StockPrice, origin: &str) -> Result<()> {
         info!(
-            "Saving price for {} ({}): ${:.2}",
+            "Saving {} price for {} ({}) from {} via {}: ${:.2}",
+            price.quality,
             price.symbol,
             price.name_en.as_deref().unwrap_or("Unknown"),
+            price.source,
+            origin,
             price.price
         );
 
-        // 保存价格历史 - 使用当前价格作为所有OHLC值
+        // 保存当天的日线：第一笔价格作为开盘价，之后每次更新最高/最低价，收盘价与成交量取最新一笔。
+        // 真实行情覆盖当天的模拟数据，模拟数据不覆盖真实行情。
         let today = price.timestamp.date_naive();
-        let created_at = price.timestamp.naive_utc();
+        let created_at = Utc::now().naive_utc();
+        let fetched_at = price.timestamp.naive_utc();
+        let exchange_time = price.exchange_time.map(|t| t.naive_utc());
+        // 涨跌幅相对上一交易日的收盘价，涨停判断依赖该值
+        let prev_close = sqlx::query_scalar!(
+            r#"
+            SELECT close_price FROM price_history
+            WHERE symbol = ? AND date < ? AND data_quality = 'live'
+            ORDER BY date DESC
+            LIMIT 1
+            "#,
+            price.symbol,
+            today
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        let change_percent = prev_close
+            .filter(|prev| *prev > 0.0)
+            .map(|prev| (price.price - prev) / prev * 100.0);
         sqlx::query!(
             r#"
-            INSERT OR REPLACE INTO price_history (symbol, date, open_price, high_price, low_price, close_price, volume, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO price_history (symbol, date, open_price, high_price, low_price, close_price, volume, daily_change_percent,
+                                       created_at, data_quality, source, fetch_origin, fetched_at, exchange_time, raw_hash)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(symbol, date) DO UPDATE SET
+                open_price = CASE WHEN price_history.data_quality = excluded.data_quality
+                                  THEN price_history.open_price ELSE excluded.open_price END,
+                high_price = CASE WHEN price_history.data_quality = excluded.data_quality
+                                  THEN MAX(price_history.high_price, excluded.high_price) ELSE excluded.high_price END,
+                low_price = CASE WHEN price_history.data_quality = excluded.data_quality
+                                 THEN MIN(price_history.low_price, excluded.low_price) ELSE excluded.low_price END,
+                close_price = excluded.close_price,
+                volume = excluded.volume,
+                daily_change_percent = excluded.daily_change_percent,
+                created_at = excluded.created_at,
+                data_quality = excluded.data_quality,
+                source = excluded.source,
+                fetch_origin = excluded.fetch_origin,
+                fetched_at = excluded.fetched_at,
+                exchange_time = excluded.exchange_time,
+                raw_hash = excluded.raw_hash
+            WHERE price_history.data_quality = excluded.data_quality OR excluded.data_quality = 'live'
             "#,
             price.symbol,
             today,
@@ -540,33 +2044,133 @@ impl PriceService {
             price.price, // low_price
             price.price, // close_price
             price.volume,
+            change_percent,
             created_at,
+            price.quality,
+            price.source,
+            origin,
+            fetched_at,
+            exchange_time,
+            price.raw_hash,
         )
         .execute(&self.db)
         .await?;
 
-        // 检查并更新相关预警
-        self.check_alerts(&price.symbol, price.price).await?;
+        // 只有真实行情才保存日内行情、检查并更新相关预警
+        if price.quality == DataQuality::Live {
+            self.record_tick(price).await;
+            self.check_alerts(&price.symbol, price.price, price.session)
+                .await?;
+        }
 
         Ok(())
     }
 
-    async fn check_alerts(&self, symbol: &str, current_price: f64) -> Result<()> {
+    /// 写入日内行情，失败只记录日志，不影响价格历史与预警
+    async fn record_tick(&self, price: &StockPrice) {
+        let Some(intraday) = &self.intraday else {
+            return;
+        };
+        if let Err(e) = intraday
+            .record_tick(
+                &price.symbol,
+                price.price,
+                price.volume,
+                price.source,
+                price.session,
+                price.timestamp,
+            )
+            .await
+        {
+            error!("Failed to record intraday tick for {}: {}", price.symbol, e);
+        }
+    }
+
+    /// 检查股票的活跃预警，盘前盘后的价格只检查开启了 `extended_hours` 的预警
+    async fn check_alerts(
+        &self,
+        symbol: &str,
+        current_price: f64,
+        session: MarketSession,
+    ) -> Result<()> {
+        let regular = session == MarketSession::Regular;
         let alerts = sqlx::query!(
             r#"
-            SELECT id, condition, price
+            SELECT id, condition, price, threshold_currency,
+                   indicator as "indicator: AlertIndicator"
             FROM alerts
-            WHERE symbol = ? AND status = 'active'
+            WHERE symbol = ? AND status = 'active' AND (? OR extended_hours)
             "#,
-            symbol
+            symbol,
+            regular
         )
         .fetch_all(&self.db)
         .await?;
 
+        let symbol_currency = Market::symbol_currency(symbol);
+        // 指标预警共用一次计算结果，只在有指标预警时计算
+        let mut indicator_values: Option<Option<IndicatorValues>> = None;
         for alert in alerts {
+            // 指标预警按常规交易时段的日线指标判断
+            let indicator_value = match alert.indicator {
+                Some(_) if !regular => continue,
+                Some(indicator) => {
+                    if indicator_values.is_none() {
+                        let values =
+                            self.latest_indicator_values(symbol)
+                                .await
+                                .unwrap_or_else(|e| {
+                                    error!("Failed to calculate indicators for {}: {}", symbol, e);
+                                    None
+                                });
+                        indicator_values = Some(values);
+                    }
+                    indicator_values
+                        .as_ref()
+                        .and_then(|values| values.as_ref())
+                        .and_then(|values| values.value(indicator))
+                }
+                None => None,
+            };
+
+            // 按其他货币设定的阈值，先把当前价格换算为阈值货币再比较
+            let compare_price = match alert.threshold_currency.as_deref() {
+                _ if alert.indicator.is_some() => match indicator_value {
+                    Some(value) => value,
+                    None => {
+                        warn!(
+                            "Indicator {:?} unavailable for alert {:?}, skipping",
+                            alert.indicator, alert.id
+                        );
+                        continue;
+                    }
+                },
+                Some(currency) if currency != symbol_currency => {
+                    match self
+                        .fx
+                        .convert(current_price, &symbol_currency, currency)
+                        .await
+                    {
+                        Ok(Some(conversion)) => conversion.amount,
+                        Ok(None) => {
+                            warn!(
+                                "No FX rate {}→{} for alert {:?}, skipping",
+                                symbol_currency, currency, alert.id
+                            );
+                            continue;
+                        }
+                        Err(e) => {
+                            error!("Failed to convert price for alert {:?}: {}", alert.id, e);
+                            continue;
+                        }
+                    }
+                }
+                _ => current_price,
+            };
+
             let triggered = match alert.condition.as_str() {
-                "above" => current_price >= alert.price,
-                "below" => current_price <= alert.price,
+                "above" => compare_price >= alert.price,
+                "below" => compare_price <= alert.price,
                 _ => false,
             };
 
@@ -578,9 +2182,16 @@ impl PriceService {
                         continue;
                     }
 
+                    let unit = match alert.indicator {
+                        Some(indicator) => indicator.label(),
+                        None => alert
+                            .threshold_currency
+                            .as_deref()
+                            .unwrap_or(&symbol_currency),
+                    };
                     info!(
-                        "🔔 Alert {} triggered! {} is now ${:.2} (target: {} ${:.2})",
-                        alert_id, symbol, current_price, alert.condition, alert.price
+                        "🔔 Alert {} triggered! {} is now {:.2} {} (target: {} {:.2} {})",
+                        alert_id, symbol, compare_price, unit, alert.condition, alert.price, unit
                     );
 
                     // 获取完整的预警信息并发送邮件通知
@@ -589,7 +2200,7 @@ impl PriceService {
                             info!("Sending email notification for alert {}", alert_id);
                             if let Err(e) = self
                                 .email_notifier
-                                .send_alert_notification(&full_alert, current_price)
+                                .send_alert_notification(&full_alert, compare_price, session)
                                 .await
                             {
                                 error!(
@@ -620,6 +2231,163 @@ impl PriceService {
         Ok(())
     }
 
+    /// 指标预警使用的最新技术指标：前复权日线（含刚保存的最新价格），盘中量比按已开盘时间折算
+    async fn latest_indicator_values(&self, symbol: &str) -> Result<Option<IndicatorValues>> {
+        let analyzer = StrategyAnalyzer::new(self.db.clone(), self.indicators.clone());
+        // 前复权只调整除权日之前的价格，最新一根日线就是原始价格
+        let Some(latest) = analyzer
+            .get_price_history(symbol, 1, PriceAdjustment::Raw)
+            .await?
+            .pop()
+        else {
+            return Ok(None);
+        };
+
+        let cached = self
+            .indicator_states
+            .read()
+            .await
+            .get(symbol)
+            .filter(|state| state.latest_date == latest.date)
+            .map(|state| (state.set.clone(), state.float_shares));
+        let (mut set, float_shares) = match cached {
+            Some(cached) => cached,
+            None => {
+                // 新的一天：用最新日线之前的前复权日线重新预热，公司行动的变化也在此时生效
+                let prices = analyzer
+                    .get_price_history(symbol, 250, PriceAdjustment::Forward)
+                    .await?;
+                let mut set = IndicatorSet::new(&self.indicators);
+                for price in prices.iter().filter(|p| p.date < latest.date) {
+                    set.update(price.into());
+                }
+                let float_shares = self.float_shares(symbol).await;
+                self.indicator_states.write().await.insert(
+                    symbol.to_string(),
+                    IndicatorState {
+                        latest_date: latest.date.clone(),
+                        set: set.clone(),
+                        float_shares,
+                    },
+                );
+                (set, float_shares)
+            }
+        };
+
+        let snapshot = set.update((&latest).into()).unwrap_or_default();
+        let mut values = IndicatorValues::new(&latest, snapshot, float_shares);
+        self.adjust_intraday_volume_ratio(symbol, &mut values);
+        Ok(Some(values))
+    }
+
+    /// 当日K线的成交量尚未走完，量比按已完成的交易时间占比折算
+    pub fn adjust_intraday_volume_ratio(&self, symbol: &str, values: &mut IndicatorValues) {
+        let now = Utc::now();
+        if values.date != now.date_naive().to_string() {
+            return;
+        }
+        let progress = self
+            .calendars
+            .for_market(&Market::from_symbol(symbol))
+            .session_progress(now);
+        if let Some(progress) = progress.filter(|p| *p > 0.0 && *p < 1.0) {
+            values.volume_ratio = values.volume_ratio.map(|ratio| ratio / progress);
+        }
+    }
+
+    /// A股流通股本（股）：cn_stocks 中未记录或已过期时从腾讯行情获取，获取失败时使用已记录的值
+    pub async fn float_shares(&self, symbol: &str) -> Option<f64> {
+        if Market::from_symbol(symbol) != Market::CN {
+            return None;
+        }
+
+        let key = cn_stocks_symbol(symbol);
+        let stored = sqlx::query!(
+            "SELECT float_shares, float_shares_updated_at FROM cn_stocks WHERE symbol = ?",
+            key
+        )
+        .fetch_optional(&self.db)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Failed to load float shares for {}: {}", symbol, e);
+            None
+        });
+        let stored_shares = stored.as_ref().and_then(|row| row.float_shares);
+        let fresh = stored
+            .as_ref()
+            .and_then(|row| row.float_shares_updated_at)
+            .is_some_and(|at| {
+                Utc::now().naive_utc() - at < chrono::Duration::days(FLOAT_SHARES_MAX_AGE_DAYS)
+            });
+        if stored_shares.is_some() && fresh {
+            return stored_shares;
+        }
+        // 最近获取失败过的股票等到重试时间再请求
+        if let Some((_, retry_at)) = self.float_shares_failures.read().await.get(&key) {
+            if time::Instant::now() < *retry_at {
+                return stored_shares;
+            }
+        }
+
+        match self.fetch_tencent_float_shares(symbol).await {
+            Ok(shares) => {
+                self.float_shares_failures.write().await.remove(&key);
+                let now = Utc::now().naive_utc();
+                if let Err(e) = sqlx::query!(
+                    "UPDATE cn_stocks SET float_shares = ?, float_shares_updated_at = ? WHERE symbol = ?",
+                    shares,
+                    now,
+                    key
+                )
+                .execute(&self.db)
+                .await
+                {
+                    error!("Failed to save float shares for {}: {}", symbol, e);
+                }
+                Some(shares)
+            }
+            Err(e) => {
+                let mut failures = self.float_shares_failures.write().await;
+                let (count, retry_at) = failures.entry(key).or_insert((0, time::Instant::now()));
+                *count += 1;
+                let delay = FLOAT_SHARES_RETRY_BASE
+                    .saturating_mul(1 << (*count - 1).min(10))
+                    .min(FLOAT_SHARES_RETRY_MAX);
+                *retry_at = time::Instant::now() + delay;
+                warn!(
+                    "Failed to fetch float shares for {}, retrying in {}s: {}",
+                    symbol,
+                    delay.as_secs(),
+                    e
+                );
+                stored_shares
+            }
+        }
+    }
+
+    async fn fetch_tencent_float_shares(&self, symbol: &str) -> Result<f64> {
+        let url = format!(
+            "https://qt.gtimg.cn/q={}",
+            self.convert_to_tencent_format(symbol)
+        );
+        let request = self
+            .client
+            .get(&url)
+            .header("Referer", "https://stockapp.finance.qq.com")
+            .header(
+                "User-Agent",
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
+            )
+            .timeout(Duration::from_secs(5));
+
+        let (status, text) = self.rate_limiter.send(PROVIDER_TENCENT, request).await?;
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("HTTP error: {}", status));
+        }
+        parse_tencent_float_shares(&text)
+            .ok_or_else(|| anyhow::anyhow!("No float shares in Tencent response"))
+    }
+
     // 添加获取完整Alert信息的方法
     async fn get_alert_by_id(&self, alert_id: i64) -> Result<Option<crate::models::Alert>> {
         let alert = sqlx::query_as!(
@@ -628,7 +2396,8 @@ impl PriceService {
             SELECT id as "id!", symbol, condition as "condition: crate::models::AlertCondition", 
                    price, status as "status: crate::models::AlertStatus", 
                    created_at, updated_at, triggered_at, notification_email,
-                   COALESCE(user_id, 'default') as "user_id!"
+                   COALESCE(user_id, 'default') as "user_id!", threshold_currency, extended_hours,
+                   indicator as "indicator: crate::models::AlertIndicator"
             FROM alerts
             WHERE id = ?
             "#,
@@ -655,18 +2424,55 @@ impl PriceService {
         .await?;
 
         info!("Alert {} has been triggered", alert_id);
+        self.notify_alerts_changed();
         Ok(())
     }
 
     pub async fn start_price_updater(self: Arc<Self>, config: Arc<PriceFetcherConfig>) {
+        // 先加载加密货币映射，保证股票市场调度从一开始就不处理加密货币
+        if let Err(e) = self.refresh_crypto_symbols().await {
+            error!("Failed to load crypto symbol mappings: {}", e);
+        }
+
+        self.start_stream();
+
+        // 加密货币全天候交易，按独立间隔持续轮询
+        let service = self.clone();
+        let crypto_config = config.clone();
+        let interval = Duration::from_secs(config.crypto.update_interval_secs);
         tokio::spawn(async move {
             loop {
-                if let Err(e) = self.update_prices(&config).await {
-                    error!("Error updating prices: {}", e);
+                if let Err(e) = service.update_crypto_prices(&crypto_config).await {
+                    error!("Error updating crypto prices: {}", e);
                 }
-                time::sleep(self.update_interval).await;
+                time::sleep(interval).await;
             }
         });
+
+        // 汇率按独立间隔刷新，用于跨币种预警与价格换算
+        if config.fx.enabled && !config.fx.pairs.is_empty() {
+            let service = self.clone();
+            let fx_config = config.fx.clone();
+            tokio::spawn(async move {
+                let interval = Duration::from_secs(fx_config.update_interval_secs);
+                loop {
+                    service.refresh_fx_rates(&fx_config.pairs).await;
+                    time::sleep(interval).await;
+                }
+            });
+        }
+
+        // 过期的日内行情降采样为K线后清理
+        if let Some(intraday) = &self.intraday {
+            intraday
+                .clone()
+                .start_retention_loop(config.intraday.clone());
+        }
+
+        // 股票、期货与外汇市场按各自的交易时段独立调度
+        for market in Market::ALL.into_iter().filter(|m| *m != Market::Crypto) {
+            tokio::spawn(self.clone().run_market_loop(market, config.clone()));
+        }
     }
 }
 
@@ -678,10 +2484,316 @@ impl Clone for PriceService {
             db: self.db.clone(),
             update_interval: self.update_interval,
             cache: self.cache.clone(),
-            semaphore: self.semaphore.clone(),
-            request_count: AtomicU64::new(self.request_count.load(Ordering::Relaxed)),
-            last_reset: AtomicU64::new(self.last_reset.load(Ordering::Relaxed)),
+            rate_limiter: self.rate_limiter.clone(),
             email_notifier: self.email_notifier.clone(),
+            crypto: self.crypto.clone(),
+            crypto_symbols: self.crypto_symbols.clone(),
+            crypto_backfilled: self.crypto_backfilled.clone(),
+            stream: self.stream.clone(),
+            alerts_changed: self.alerts_changed.clone(),
+            stream_persist_interval: self.stream_persist_interval,
+            stream_persisted: self.stream_persisted.clone(),
+            calendars: self.calendars.clone(),
+            health: self.health.clone(),
+            validator: self.validator.clone(),
+            fx: self.fx.clone(),
+            intraday: self.intraday.clone(),
+            extended_hours_enabled: self.extended_hours_enabled,
+            indicators: self.indicators.clone(),
+            indicator_states: self.indicator_states.clone(),
+            float_shares_failures: self.float_shares_failures.clone(),
+        }
+    }
+}
+
+/// 腾讯行情第72个字段为A股流通股本（股）
+fn parse_tencent_float_shares(text: &str) -> Option<f64> {
+    let start = text.find('"')?;
+    let end = text.rfind('"')?;
+    text.get(start + 1..end)?
+        .split('~')
+        .nth(72)?
+        .parse::<f64>()
+        .ok()
+        .filter(|shares| *shares > 0.0)
+}
+
+/// Yahoo 分钟K线中最近一笔成交，落在盘前或盘后时段时返回其时段、价格与时间
+fn latest_extended_trade(result: &YahooResult) -> Option<(MarketSession, f64, DateTime<Utc>)> {
+    let periods = result.meta.current_trading_period.as_ref()?;
+    let closes = &result.indicators.as_ref()?.quote.first()?.close;
+    let (time, price) = result
+        .timestamp
+        .iter()
+        .zip(closes)
+        .rev()
+        .find_map(|(time, close)| close.map(|close| (*time, close)))?;
+
+    let within = |period: &YahooTradingPeriod| period.start <= time && time < period.end;
+    let session = if within(&periods.pre) {
+        MarketSession::PreMarket
+    } else if within(&periods.post) {
+        MarketSession::AfterHours
+    } else {
+        return None;
+    };
+    Some((session, price, DateTime::from_timestamp(time, 0)?))
+}
+
+/// 解析新浪、腾讯返回的行情时间（北京/香港时间，UTC+8），依次尝试各格式
+fn parse_china_exchange_time(value: &str, formats: &[&str]) -> Option<DateTime<Utc>> {
+    let offset = FixedOffset::east_opt(8 * 3600)?;
+    formats
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value.trim(), format).ok())
+        .and_then(|local| local.and_local_timezone(offset).single())
+        .map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EmailConfig;
+
+    #[test]
+    fn test_parse_china_exchange_time() {
+        let expected = DateTime::parse_from_rfc3339("2025-01-06T07:00:03Z").unwrap();
+        assert_eq!(
+            parse_china_exchange_time("20250106150003", &["%Y%m%d%H%M%S"]),
+            Some(expected.with_timezone(&Utc))
+        );
+        assert_eq!(
+            parse_china_exchange_time(
+                "2025/01/06 15:00:03",
+                &["%Y%m%d%H%M%S", "%Y/%m/%d %H:%M:%S"]
+            ),
+            Some(expected.with_timezone(&Utc))
+        );
+        assert_eq!(parse_china_exchange_time("", &["%Y%m%d%H%M%S"]), None);
+    }
+
+    #[test]
+    fn test_parse_tencent_float_shares() {
+        let mut fields = vec!["0"; 80];
+        fields[72] = "19405546950.00";
+        let text = format!("v_sz000001=\"{}\";", fields.join("~"));
+        assert_eq!(parse_tencent_float_shares(&text), Some(19405546950.0));
+
+        fields[72] = "";
+        let text = format!("v_sz000001=\"{}\";", fields.join("~"));
+        assert_eq!(parse_tencent_float_shares(&text), None);
+    }
+
+    #[test]
+    fn test_latest_extended_trade() {
+        let response = |closes: &str| -> YahooQuoteResponse {
+            serde_json::from_str(&format!(
+                r#"{{"chart": {{"error": null, "result": [{{
+                    "meta": {{"symbol": "AAPL", "regularMarketPrice": 200.0,
+                        "currentTradingPeriod": {{
+                            "pre": {{"start": 1000, "end": 2000}},
+                            "post": {{"start": 3000, "end": 4000}}}}}},
+                    "timestamp": [1500, 2500, 3500],
+                    "indicators": {{"quote": [{{"close": {closes}}}]}}}}]}}}}"#
+            ))
+            .unwrap()
+        };
+
+        let trade = latest_extended_trade(&response("[199.0, 200.0, 210.5]").chart.result[0]);
+        assert_eq!(
+            trade,
+            Some((
+                MarketSession::AfterHours,
+                210.5,
+                DateTime::from_timestamp(3500, 0).unwrap()
+            ))
+        );
+        // 最近一笔成交在常规时段：盘后还没有成交
+        assert_eq!(
+            latest_extended_trade(&response("[199.0, 200.0, null]").chart.result[0]),
+            None
+        );
+    }
+
+    async fn price_service() -> PriceService {
+        // 内存数据库每个连接相互独立，测试只使用一个连接
+        let db = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&db).await.unwrap();
+        let config: PriceFetcherConfig = toml::from_str(
+            r#"
+            update_interval_secs = 30
+            cache_ttl_secs = 30
+            max_retries = 3
+            max_concurrent_requests = 5
+            max_requests_per_hour = 2000
+            request_timeout_secs = 10
+            pool_idle_timeout_secs = 30
+            [crypto]
+            enabled = false
+            "#,
+        )
+        .unwrap();
+        let email: EmailConfig = toml::from_str("enabled = false").unwrap();
+        PriceService::new(
+            db,
+            &config,
+            Arc::new(EmailNotifier::new(email).unwrap()),
+            Arc::new(TradingCalendars::builtin()),
+            IndicatorConfig::default(),
+        )
+    }
+
+    fn stock_price(symbol: &str, price: f64, volume: i64, quality: DataQuality) -> StockPrice {
+        StockPrice {
+            symbol: symbol.to_string(),
+            price,
+            volume,
+            timestamp: Utc::now(),
+            name_en: None,
+            quality,
+            source: "sina",
+            exchange_time: None,
+            raw_hash: None,
+            session: MarketSession::Regular,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_price_upserts_daily_bar() {
+        let service = price_service().await;
+        let yesterday = Utc::now().date_naive() - chrono::Days::new(1);
+        sqlx::query(
+            "INSERT INTO price_history (symbol, date, open_price, high_price, low_price, close_price, volume) \
+             VALUES ('600000.SH', ?, 10, 10, 10, 10, 1000)",
+        )
+        .bind(yesterday)
+        .execute(&service.db)
+        .await
+        .unwrap();
+
+        for (price, volume) in [(10.0, 100), (12.0, 200), (9.0, 300), (11.0, 400)] {
+            service
+                .save_price(
+                    &stock_price("600000.SH", price, volume, DataQuality::Live),
+                    ORIGIN_POLLER,
+                )
+                .await
+                .unwrap();
+        }
+        // 模拟数据不覆盖当天的真实行情
+        service
+            .save_price(
+                &stock_price("600000.SH", 50.0, 500, DataQuality::Synthetic),
+                ORIGIN_POLLER,
+            )
+            .await
+            .unwrap();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT open_price, high_price, low_price, close_price, volume, daily_change_percent,
+                   data_quality as "data_quality: DataQuality"
+            FROM price_history WHERE symbol = '600000.SH' AND date > ?
+            "#,
+            yesterday
+        )
+        .fetch_all(&service.db)
+        .await
+        .unwrap();
+        assert_eq!(rows.len(), 1);
+        let bar = &rows[0];
+        assert_eq!(
+            (
+                bar.open_price,
+                bar.high_price,
+                bar.low_price,
+                bar.close_price
+            ),
+            (10.0, 12.0, 9.0, 11.0)
+        );
+        assert_eq!(bar.volume, 400);
+        assert_eq!(bar.data_quality, DataQuality::Live);
+        // 相对昨收 10 元上涨 10%
+        assert!((bar.daily_change_percent.unwrap() - 10.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_indicator_state_follows_latest_bar() {
+        let service = price_service().await;
+        let today = Utc::now().date_naive();
+        for day in 1..=60u64 {
+            let close = 100.0 + (day as f64 / 4.0).sin() * 5.0;
+            sqlx::query(
+                "INSERT INTO price_history (symbol, date, open_price, high_price, low_price, close_price, volume) \
+                 VALUES ('AAPL', ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(today - chrono::Days::new(61 - day))
+            .bind(close)
+            .bind(close + 1.0)
+            .bind(close - 1.0)
+            .bind(close)
+            .bind(1_000 + day as i64 * 10)
+            .execute(&service.db)
+            .await
+            .unwrap();
+        }
+        let analyzer = StrategyAnalyzer::new(service.db.clone(), IndicatorConfig::default());
+
+        // 同一天多次更新：缓存的指标状态只输入最新一根日线，结果与完整重算一致
+        for (price, volume) in [(101.0, 300), (104.0, 900), (99.0, 1_500)] {
+            service
+                .save_price(
+                    &stock_price("AAPL", price, volume, DataQuality::Live),
+                    ORIGIN_POLLER,
+                )
+                .await
+                .unwrap();
+            let values = service
+                .latest_indicator_values("AAPL")
+                .await
+                .unwrap()
+                .unwrap();
+
+            let prices = analyzer
+                .get_price_history("AAPL", 250, PriceAdjustment::Raw)
+                .await
+                .unwrap();
+            let mut expected = analyzer.calculate_indicators(&prices, None).pop().unwrap();
+            service.adjust_intraday_volume_ratio("AAPL", &mut expected);
+            assert_eq!(values.date, today.to_string());
+            assert_eq!(values.close, price);
+            assert_eq!(
+                serde_json::to_value(&values).unwrap(),
+                serde_json::to_value(&expected).unwrap()
+            );
+        }
+        let states = service.indicator_states.read().await;
+        assert_eq!(states["AAPL"].latest_date, today.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_single_source_repeats_do_not_confirm() {
+        let service = price_service().await;
+        let yahoo = |price| StockPrice {
+            source: PROVIDER_YAHOO,
+            ..stock_price("AAPL", price, 1_000, DataQuality::Live)
+        };
+        service.cache_price(&yahoo(100.0)).await;
+
+        // 美股没有第二个数据源，卡住的数据源重复同一个错误价格也不能确认
+        for _ in 0..2 {
+            let validation = service.validate_price(&yahoo(10.0), None).await.unwrap();
+            assert!(matches!(validation, Validation::Rejected(_)));
         }
+        let quarantined: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM price_quarantine WHERE symbol = 'AAPL'")
+                .fetch_one(&service.db)
+                .await
+                .unwrap();
+        assert_eq!(quarantined, 2);
     }
 }
@@ -0,0 +1,268 @@
+use crate::config::FxProviderConfig;
+use crate::models::FxRate;
+use anyhow::Result;
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+/// 交叉换算使用的中间货币
+const PIVOT_CURRENCY: &str = "USD";
+
+/// 汇率来源：手工录入
+pub const FX_SOURCE_MANUAL: &str = "manual";
+
+/// 一次货币换算的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FxConversion {
+    pub from: String,
+    pub to: String,
+    pub rate: f64,
+    pub amount: f64,
+    /// 参与换算的汇率中最早的时间
+    pub as_of: Option<NaiveDateTime>,
+}
+
+/// 汇率存储：保存每次获取或录入的汇率，换算时使用未过期的最新汇率
+pub struct FxRateService {
+    db: SqlitePool,
+    max_rate_age: chrono::Duration,
+}
+
+/// 货币代码统一为大写，USDT 与美元挂钩按 USD 处理
+pub fn normalize_currency(currency: &str) -> String {
+    match currency.trim().to_uppercase().as_str() {
+        "USDT" => PIVOT_CURRENCY.to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// 解析 USDCNY、USD/CNY 或 USDCNY=X 形式的货币对
+pub fn parse_currency_pair(pair: &str) -> Option<(String, String)> {
+    let pair = pair.trim().to_uppercase();
+    let pair: String = pair
+        .trim_end_matches("=X")
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .collect();
+    (pair.len() == 6).then(|| (pair[..3].to_string(), pair[3..].to_string()))
+}
+
+impl FxRateService {
+    pub fn new(db: SqlitePool, config: &FxProviderConfig) -> Self {
+        Self {
+            db,
+            max_rate_age: chrono::Duration::hours(config.max_rate_age_hours),
+        }
+    }
+
+    /// 记录一条汇率
+    pub async fn record(
+        &self,
+        base_currency: &str,
+        quote_currency: &str,
+        rate: f64,
+        source: &str,
+        as_of: Option<NaiveDateTime>,
+    ) -> Result<FxRate> {
+        if !rate.is_finite() || rate <= 0.0 {
+            anyhow::bail!("汇率必须大于0");
+        }
+        let base = normalize_currency(base_currency);
+        let quote = normalize_currency(quote_currency);
+        if base.len() != 3 || quote.len() != 3 || base == quote {
+            anyhow::bail!("无效的货币对: {}/{}", base_currency, quote_currency);
+        }
+        let as_of = as_of.unwrap_or_else(|| Utc::now().naive_utc());
+
+        let row = sqlx::query_as!(
+            FxRate,
+            r#"
+            INSERT INTO fx_rates (base_currency, quote_currency, rate, source, as_of)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING base_currency, quote_currency, rate, source, as_of
+            "#,
+            base,
+            quote,
+            rate,
+            source,
+            as_of
+        )
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(row)
+    }
+
+    /// 每个货币对的最新汇率
+    pub async fn latest_rates(&self) -> Result<Vec<FxRate>> {
+        let rows = sqlx::query_as!(
+            FxRate,
+            r#"
+            SELECT base_currency, quote_currency, rate, source, as_of
+            FROM fx_rates AS r
+            WHERE id = (
+                SELECT id FROM fx_rates
+                WHERE base_currency = r.base_currency AND quote_currency = r.quote_currency
+                ORDER BY as_of DESC, id DESC
+                LIMIT 1
+            )
+            ORDER BY base_currency, quote_currency
+            "#
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// 货币对的汇率历史，按时间倒序
+    pub async fn history(
+        &self,
+        base_currency: &str,
+        quote_currency: &str,
+        limit: i64,
+    ) -> Result<Vec<FxRate>> {
+        let base = normalize_currency(base_currency);
+        let quote = normalize_currency(quote_currency);
+        let rows = sqlx::query_as!(
+            FxRate,
+            r#"
+            SELECT base_currency, quote_currency, rate, source, as_of
+            FROM fx_rates
+            WHERE base_currency = ? AND quote_currency = ?
+            ORDER BY as_of DESC, id DESC
+            LIMIT ?
+            "#,
+            base,
+            quote,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// 将金额从一种货币换算为另一种，没有可用汇率时返回 None
+    pub async fn convert(&self, amount: f64, from: &str, to: &str) -> Result<Option<FxConversion>> {
+        let from = normalize_currency(from);
+        let to = normalize_currency(to);
+        let Some((rate, as_of)) = self.rate(&from, &to).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(FxConversion {
+            from,
+            to,
+            rate,
+            amount: amount * rate,
+            as_of,
+        }))
+    }
+
+    /// 1单位 from 折合多少 to：优先直接汇率，其次反向汇率，最后经 USD 交叉换算
+    async fn rate(&self, from: &str, to: &str) -> Result<Option<(f64, Option<NaiveDateTime>)>> {
+        if from == to {
+            return Ok(Some((1.0, None)));
+        }
+        if let Some((rate, as_of)) = self.pair_rate(from, to).await? {
+            return Ok(Some((rate, Some(as_of))));
+        }
+        if from == PIVOT_CURRENCY || to == PIVOT_CURRENCY {
+            return Ok(None);
+        }
+
+        let first = self.pair_rate(from, PIVOT_CURRENCY).await?;
+        let second = self.pair_rate(PIVOT_CURRENCY, to).await?;
+        Ok(first
+            .zip(second)
+            .map(|((r1, t1), (r2, t2))| (r1 * r2, Some(t1.min(t2)))))
+    }
+
+    /// 直接或反向货币对的最新有效汇率
+    async fn pair_rate(&self, from: &str, to: &str) -> Result<Option<(f64, NaiveDateTime)>> {
+        let not_before = Utc::now().naive_utc() - self.max_rate_age;
+        let row = sqlx::query!(
+            r#"
+            SELECT base_currency, rate, as_of
+            FROM fx_rates
+            WHERE ((base_currency = ? AND quote_currency = ?)
+                OR (base_currency = ? AND quote_currency = ?))
+              AND as_of >= ?
+            ORDER BY as_of DESC, id DESC
+            LIMIT 1
+            "#,
+            from,
+            to,
+            to,
+            from,
+            not_before
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(|row| {
+            let rate = if row.base_currency == from {
+                row.rate
+            } else {
+                1.0 / row.rate
+            };
+            (rate, row.as_of)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn service() -> FxRateService {
+        // 内存数据库每个连接相互独立，测试只使用一个连接
+        let db = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&db).await.unwrap();
+        FxRateService::new(db, &FxProviderConfig::default())
+    }
+
+    #[test]
+    fn test_parse_currency_pair() {
+        let usd_cny = Some(("USD".to_string(), "CNY".to_string()));
+        assert_eq!(parse_currency_pair("USDCNY"), usd_cny);
+        assert_eq!(parse_currency_pair("usd/cny"), usd_cny);
+        assert_eq!(parse_currency_pair("USDCNY=X"), usd_cny);
+        assert_eq!(parse_currency_pair("USD"), None);
+    }
+
+    #[tokio::test]
+    async fn test_direct_inverse_and_cross_conversion() {
+        let fx = service().await;
+        fx.record("USD", "CNY", 7.2, "yahoo", None).await.unwrap();
+        fx.record("USD", "HKD", 7.8, "yahoo", None).await.unwrap();
+
+        let direct = fx.convert(100.0, "USD", "CNY").await.unwrap().unwrap();
+        assert!((direct.amount - 720.0).abs() < 1e-9);
+
+        let inverse = fx.convert(720.0, "CNY", "USDT").await.unwrap().unwrap();
+        assert!((inverse.amount - 100.0).abs() < 1e-9);
+
+        let cross = fx.convert(78.0, "HKD", "CNY").await.unwrap().unwrap();
+        assert!((cross.amount - 72.0).abs() < 1e-9);
+
+        assert!(fx.convert(1.0, "EUR", "CNY").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stale_rates_are_ignored() {
+        let fx = service().await;
+        let old = Utc::now().naive_utc() - chrono::Duration::days(30);
+        fx.record("USD", "CNY", 7.0, FX_SOURCE_MANUAL, Some(old))
+            .await
+            .unwrap();
+
+        assert!(fx.convert(1.0, "USD", "CNY").await.unwrap().is_none());
+        assert_eq!(fx.history("USD", "CNY", 10).await.unwrap().len(), 1);
+    }
+}
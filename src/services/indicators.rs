@@ -0,0 +1,777 @@
+use crate::config::IndicatorConfig;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// 流式技术指标：每输入一个新值以 O(1)（均摊）更新并返回最新指标值，预热期内为 None。
+/// 批量计算就是依次输入全部历史数据，因此批量与增量结果完全一致。
+/// 指标预警为每个股票保留状态、逐根输入新日线；策略分析、评分与回测在整段日线上用 `batch`/`last` 一次性计算
+pub trait Indicator {
+    type Input: Copy;
+    type Output;
+
+    fn update(&mut self, input: Self::Input) -> Option<Self::Output>;
+
+    /// 依次输入全部数据，返回每个位置的指标值
+    fn batch(&mut self, inputs: &[Self::Input]) -> Vec<Option<Self::Output>> {
+        inputs.iter().map(|input| self.update(*input)).collect()
+    }
+
+    /// 依次输入全部数据，只返回最后一个指标值
+    fn last(&mut self, inputs: &[Self::Input]) -> Option<Self::Output> {
+        inputs.iter().fold(None, |_, input| self.update(*input))
+    }
+}
+
+/// 单根K线，用于需要最高最低价或成交量的指标
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+/// MACD 指标值
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MacdPoint {
+    /// 快线 EMA 与慢线 EMA 之差
+    pub dif: f64,
+    /// DIF 的 EMA
+    pub dea: f64,
+    /// 柱状值，按国内习惯为 (DIF - DEA) * 2
+    pub histogram: f64,
+}
+
+/// 布林带指标值
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BollingerPoint {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+    /// 带宽：(上轨 - 下轨) / 中轨
+    pub width: f64,
+}
+
+/// KDJ 指标值
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct KdjPoint {
+    pub k: f64,
+    pub d: f64,
+    /// 3K - 2D
+    pub j: f64,
+}
+
+/// 定长滑动窗口，维护窗口内的和与平方和
+#[derive(Debug, Clone)]
+struct Window {
+    period: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl Window {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            values: VecDeque::with_capacity(period + 1),
+            sum: 0.0,
+            sum_sq: 0.0,
+        }
+    }
+
+    /// 加入新值并移出最旧的值，返回窗口是否已满
+    fn push(&mut self, value: f64) -> bool {
+        if self.period == 0 {
+            return false;
+        }
+        self.values.push_back(value);
+        self.sum += value;
+        self.sum_sq += value * value;
+        if self.values.len() > self.period {
+            if let Some(old) = self.values.pop_front() {
+                self.sum -= old;
+                self.sum_sq -= old * old;
+            }
+        }
+        self.values.len() == self.period
+    }
+
+    fn mean(&self) -> f64 {
+        self.sum / self.period as f64
+    }
+
+    /// 总体方差，浮点误差可能使其略小于 0
+    fn variance(&self) -> f64 {
+        let mean = self.mean();
+        (self.sum_sq / self.period as f64 - mean * mean).max(0.0)
+    }
+}
+
+/// 简单移动平均
+#[derive(Debug, Clone)]
+pub struct Sma {
+    window: Window,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            window: Window::new(period),
+        }
+    }
+}
+
+impl Indicator for Sma {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push(value).then(|| self.window.mean())
+    }
+}
+
+/// 总体标准差
+#[derive(Debug, Clone)]
+pub struct StdDev {
+    window: Window,
+}
+
+impl StdDev {
+    pub fn new(period: usize) -> Self {
+        Self {
+            window: Window::new(period),
+        }
+    }
+}
+
+impl Indicator for StdDev {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        self.window
+            .push(value)
+            .then(|| self.window.variance().sqrt())
+    }
+}
+
+/// 指数移动平均，以前 `period` 个值的简单平均作为初值
+#[derive(Debug, Clone)]
+pub struct Ema {
+    period: usize,
+    alpha: f64,
+    seed_count: usize,
+    seed_sum: f64,
+    current: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period,
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed_count: 0,
+            seed_sum: 0.0,
+            current: None,
+        }
+    }
+}
+
+impl Indicator for Ema {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        match self.current {
+            Some(current) => {
+                self.current = Some(self.alpha * value + (1.0 - self.alpha) * current);
+            }
+            None => {
+                self.seed_count += 1;
+                self.seed_sum += value;
+                if self.seed_count == self.period {
+                    self.current = Some(self.seed_sum / self.period as f64);
+                }
+            }
+        }
+        self.current
+    }
+}
+
+/// Wilder 平滑：以前 `period` 个值的简单平均作为初值，之后按 1/`period` 的权重更新
+#[derive(Debug, Clone)]
+struct Wilder {
+    period: usize,
+    seed_count: usize,
+    seed_sum: f64,
+    current: Option<f64>,
+}
+
+impl Wilder {
+    fn new(period: usize) -> Self {
+        Self {
+            period,
+            seed_count: 0,
+            seed_sum: 0.0,
+            current: None,
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        let period = self.period as f64;
+        match self.current {
+            Some(current) => self.current = Some((current * (period - 1.0) + value) / period),
+            None => {
+                self.seed_count += 1;
+                self.seed_sum += value;
+                if self.seed_count == self.period {
+                    self.current = Some(self.seed_sum / period);
+                }
+            }
+        }
+        self.current
+    }
+}
+
+/// RSI（Wilder 平滑），需要 `period + 1` 个收盘价才有第一个值
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    prev_close: Option<f64>,
+    gain: Wilder,
+    loss: Wilder,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self {
+            prev_close: None,
+            gain: Wilder::new(period),
+            loss: Wilder::new(period),
+        }
+    }
+}
+
+impl Indicator for Rsi {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, close: f64) -> Option<f64> {
+        let change = close - self.prev_close.replace(close)?;
+        let avg_gain = self.gain.update(change.max(0.0));
+        let avg_loss = self.loss.update((-change).max(0.0));
+        Some(rsi_value(avg_gain?, avg_loss?))
+    }
+}
+
+fn rsi_value(avg_gain: f64, avg_loss: f64) -> f64 {
+    if avg_loss == 0.0 {
+        if avg_gain == 0.0 {
+            50.0
+        } else {
+            100.0
+        }
+    } else {
+        100.0 - 100.0 / (1.0 + avg_gain / avg_loss)
+    }
+}
+
+/// MACD，DEA 需要 `slow + signal - 1` 个收盘价才有第一个值
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+        }
+    }
+}
+
+impl Indicator for Macd {
+    type Input = f64;
+    type Output = MacdPoint;
+
+    fn update(&mut self, close: f64) -> Option<MacdPoint> {
+        let fast = self.fast.update(close);
+        let slow = self.slow.update(close);
+        // DEA 从第一个有效 DIF 开始计算
+        let dif = fast? - slow?;
+        let dea = self.signal.update(dif)?;
+        Some(MacdPoint {
+            dif,
+            dea,
+            histogram: (dif - dea) * 2.0,
+        })
+    }
+}
+
+/// 布林带：中轨为 `period` 日简单平均，上下轨为中轨加减 `std_dev` 倍总体标准差
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    middle: Sma,
+    std_dev: StdDev,
+    multiplier: f64,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, std_dev: f64) -> Self {
+        Self {
+            middle: Sma::new(period),
+            std_dev: StdDev::new(period),
+            multiplier: std_dev,
+        }
+    }
+}
+
+impl Indicator for Bollinger {
+    type Input = f64;
+    type Output = BollingerPoint;
+
+    fn update(&mut self, close: f64) -> Option<BollingerPoint> {
+        let middle = self.middle.update(close);
+        let band = self.multiplier * self.std_dev.update(close)?;
+        let middle = middle?;
+        Some(BollingerPoint {
+            upper: middle + band,
+            middle,
+            lower: middle - band,
+            width: if middle != 0.0 {
+                2.0 * band / middle
+            } else {
+                0.0
+            },
+        })
+    }
+}
+
+/// 滑动窗口最值，单调队列保证均摊 O(1)
+#[derive(Debug, Clone)]
+struct Extremum {
+    period: usize,
+    max: bool,
+    index: usize,
+    candidates: VecDeque<(usize, f64)>,
+}
+
+impl Extremum {
+    fn new(period: usize, max: bool) -> Self {
+        Self {
+            period,
+            max,
+            index: 0,
+            candidates: VecDeque::with_capacity(period),
+        }
+    }
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        if self.period == 0 {
+            return None;
+        }
+        // 被新值支配的旧值不可能再成为最值
+        while let Some(&(_, last)) = self.candidates.back() {
+            let dominated = if self.max {
+                last <= value
+            } else {
+                last >= value
+            };
+            if !dominated {
+                break;
+            }
+            self.candidates.pop_back();
+        }
+        self.candidates.push_back((self.index, value));
+        while let Some(&(index, _)) = self.candidates.front() {
+            if index + self.period > self.index {
+                break;
+            }
+            self.candidates.pop_front();
+        }
+        self.index += 1;
+
+        if self.index >= self.period {
+            self.candidates.front().map(|(_, value)| *value)
+        } else {
+            None
+        }
+    }
+}
+
+/// 最近 `period` 个值中的最大值
+#[derive(Debug, Clone)]
+pub struct Highest(Extremum);
+
+impl Highest {
+    pub fn new(period: usize) -> Self {
+        Self(Extremum::new(period, true))
+    }
+}
+
+impl Indicator for Highest {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        self.0.update(value)
+    }
+}
+
+/// 最近 `period` 个值中的最小值
+#[derive(Debug, Clone)]
+pub struct Lowest(Extremum);
+
+impl Lowest {
+    pub fn new(period: usize) -> Self {
+        Self(Extremum::new(period, false))
+    }
+}
+
+impl Indicator for Lowest {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        self.0.update(value)
+    }
+}
+
+/// 平均真实波幅（Wilder 平滑），第一根K线的真实波幅为最高价减最低价
+#[derive(Debug, Clone)]
+pub struct Atr {
+    prev_close: Option<f64>,
+    average: Wilder,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self {
+            prev_close: None,
+            average: Wilder::new(period),
+        }
+    }
+}
+
+impl Indicator for Atr {
+    type Input = Candle;
+    type Output = f64;
+
+    fn update(&mut self, candle: Candle) -> Option<f64> {
+        let range = candle.high - candle.low;
+        let true_range = match self.prev_close.replace(candle.close) {
+            Some(prev) => range
+                .max((candle.high - prev).abs())
+                .max((candle.low - prev).abs()),
+            None => range,
+        };
+        self.average.update(true_range)
+    }
+}
+
+/// KDJ：RSV 取最近 `period` 根K线的最高最低价，K、D 为 RSV、K 的移动平均（权重 1/`k_smooth`、1/`d_smooth`），初值为 50
+#[derive(Debug, Clone)]
+pub struct Kdj {
+    highest: Highest,
+    lowest: Lowest,
+    k_smooth: usize,
+    d_smooth: usize,
+    k: f64,
+    d: f64,
+}
+
+impl Kdj {
+    pub fn new(period: usize, k_smooth: usize, d_smooth: usize) -> Self {
+        Self {
+            highest: Highest::new(period),
+            lowest: Lowest::new(period),
+            k_smooth,
+            d_smooth,
+            k: 50.0,
+            d: 50.0,
+        }
+    }
+}
+
+impl Indicator for Kdj {
+    type Input = Candle;
+    type Output = KdjPoint;
+
+    fn update(&mut self, candle: Candle) -> Option<KdjPoint> {
+        let high = self.highest.update(candle.high);
+        let low = self.lowest.update(candle.low);
+        if self.k_smooth == 0 || self.d_smooth == 0 {
+            return None;
+        }
+        let (high, low) = (high?, low?);
+        // 区间无波动时 RSV 取中值
+        let rsv = if high > low {
+            (candle.close - low) / (high - low) * 100.0
+        } else {
+            50.0
+        };
+        self.k = (self.k * (self.k_smooth - 1) as f64 + rsv) / self.k_smooth as f64;
+        self.d = (self.d * (self.d_smooth - 1) as f64 + self.k) / self.d_smooth as f64;
+        Some(KdjPoint {
+            k: self.k,
+            d: self.d,
+            j: 3.0 * self.k - 2.0 * self.d,
+        })
+    }
+}
+
+/// 量比：当根成交量与之前 `period` 根平均成交量之比
+#[derive(Debug, Clone)]
+pub struct VolumeRatio {
+    average: Sma,
+    previous: Option<f64>,
+}
+
+impl VolumeRatio {
+    pub fn new(period: usize) -> Self {
+        Self {
+            average: Sma::new(period),
+            previous: None,
+        }
+    }
+}
+
+impl Indicator for VolumeRatio {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, volume: f64) -> Option<f64> {
+        let ratio = self
+            .previous
+            .filter(|average| *average > 0.0)
+            .map(|average| volume / average);
+        self.previous = self.average.update(volume);
+        ratio
+    }
+}
+
+/// 单根K线上按配置计算的一组指标值
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IndicatorSnapshot {
+    pub rsi: Option<f64>,
+    pub macd: Option<MacdPoint>,
+    pub bollinger: Option<BollingerPoint>,
+    pub kdj: Option<KdjPoint>,
+    pub atr: Option<f64>,
+    pub volume_ratio: Option<f64>,
+}
+
+/// 按 `[strategy.indicators]` 配置组合的指标，策略分析、预警与图表共用；
+/// 预警检查在 `PriceService` 中按股票缓存它的状态，只输入最新一根日线
+#[derive(Debug, Clone)]
+pub struct IndicatorSet {
+    rsi: Rsi,
+    macd: Macd,
+    bollinger: Bollinger,
+    kdj: Kdj,
+    atr: Atr,
+    volume_ratio: VolumeRatio,
+}
+
+impl IndicatorSet {
+    pub fn new(params: &IndicatorConfig) -> Self {
+        Self {
+            rsi: Rsi::new(params.rsi_period),
+            macd: Macd::new(params.macd_fast, params.macd_slow, params.macd_signal),
+            bollinger: Bollinger::new(params.boll_period, params.boll_std_dev),
+            kdj: Kdj::new(params.kdj_period, params.kdj_k_smooth, params.kdj_d_smooth),
+            atr: Atr::new(params.atr_period),
+            volume_ratio: VolumeRatio::new(params.volume_ratio_period),
+        }
+    }
+}
+
+impl Indicator for IndicatorSet {
+    type Input = Candle;
+    type Output = IndicatorSnapshot;
+
+    fn update(&mut self, candle: Candle) -> Option<IndicatorSnapshot> {
+        Some(IndicatorSnapshot {
+            rsi: self.rsi.update(candle.close),
+            macd: self.macd.update(candle.close),
+            bollinger: self.bollinger.update(candle.close),
+            kdj: self.kdj.update(candle),
+            atr: self.atr.update(candle),
+            volume_ratio: self.volume_ratio.update(candle.volume),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candles() -> Vec<Candle> {
+        (0..300)
+            .map(|i| {
+                let x = i as f64;
+                let close = 100.0 + 10.0 * (x / 7.0).sin() + 0.05 * x;
+                Candle {
+                    high: close + 1.0 + (x / 3.0).cos().abs(),
+                    low: close - 1.0 - (x / 5.0).sin().abs(),
+                    close,
+                    volume: 1000.0 + 500.0 * (x / 11.0).cos(),
+                }
+            })
+            .collect()
+    }
+
+    /// 批量计算，与从中途保存的状态继续增量计算的结果必须完全一致
+    fn assert_batch_matches_incremental<I>(mut indicator: I, inputs: &[I::Input])
+    where
+        I: Indicator + Clone,
+        I::Output: PartialEq + std::fmt::Debug,
+    {
+        let batch = indicator.clone().batch(inputs);
+        let (head, tail) = inputs.split_at(inputs.len() / 3);
+        let mut incremental: Vec<_> = head.iter().map(|i| indicator.update(*i)).collect();
+        let mut resumed = indicator.clone();
+        incremental.extend(tail.iter().map(|i| resumed.update(*i)));
+        assert_eq!(batch, incremental);
+    }
+
+    #[test]
+    fn test_batch_matches_incremental() {
+        let candles = candles();
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        let volumes: Vec<f64> = candles.iter().map(|c| c.volume).collect();
+
+        assert_batch_matches_incremental(Sma::new(20), &closes);
+        assert_batch_matches_incremental(Ema::new(12), &closes);
+        assert_batch_matches_incremental(StdDev::new(20), &closes);
+        assert_batch_matches_incremental(Highest::new(9), &closes);
+        assert_batch_matches_incremental(Lowest::new(9), &closes);
+        assert_batch_matches_incremental(Rsi::new(14), &closes);
+        assert_batch_matches_incremental(Macd::new(12, 26, 9), &closes);
+        assert_batch_matches_incremental(Bollinger::new(20, 2.0), &closes);
+        assert_batch_matches_incremental(Atr::new(14), &candles);
+        assert_batch_matches_incremental(Kdj::new(9, 3, 3), &candles);
+        assert_batch_matches_incremental(VolumeRatio::new(5), &volumes);
+    }
+
+    #[test]
+    fn test_rolling_windows_match_naive() {
+        let candles = candles();
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        let period = 20;
+        let (sma, std_dev) = (
+            Sma::new(period).batch(&closes),
+            StdDev::new(period).batch(&closes),
+        );
+        let (highest, lowest) = (
+            Highest::new(period).batch(&closes),
+            Lowest::new(period).batch(&closes),
+        );
+
+        assert!(sma[..period - 1].iter().all(Option::is_none));
+        for (i, window) in closes.windows(period).enumerate() {
+            let i = i + period - 1;
+            let mean = window.iter().sum::<f64>() / period as f64;
+            let variance = window.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / period as f64;
+            assert!((sma[i].unwrap() - mean).abs() < 1e-9);
+            assert!((std_dev[i].unwrap() - variance.sqrt()).abs() < 1e-6);
+            assert_eq!(highest[i], window.iter().copied().reduce(f64::max));
+            assert_eq!(lowest[i], window.iter().copied().reduce(f64::min));
+        }
+    }
+
+    #[test]
+    fn test_atr() {
+        let candle = |high, low, close| Candle {
+            high,
+            low,
+            close,
+            volume: 0.0,
+        };
+        // 真实波幅依次为 2、3（跳空高开取 |最高 - 昨收|）、4
+        let values = Atr::new(2).batch(&[
+            candle(11.0, 9.0, 10.0),
+            candle(13.0, 12.0, 12.5),
+            candle(14.0, 10.0, 11.0),
+        ]);
+        assert_eq!(values, vec![None, Some(2.5), Some(3.25)]);
+    }
+
+    #[test]
+    fn test_rsi_and_bollinger() {
+        // 单边上涨 RSI 为 100，单边下跌为 0
+        let rising: Vec<f64> = (1..=20).map(f64::from).collect();
+        assert_eq!(Rsi::new(14).batch(&rising)[13], None);
+        assert_eq!(Rsi::new(14).batch(&rising)[14], Some(100.0));
+        let falling: Vec<f64> = rising.iter().rev().copied().collect();
+        assert_eq!(Rsi::new(14).batch(&falling)[19], Some(0.0));
+
+        // 收盘价恒定时带宽为 0
+        let flat = vec![10.0; 20];
+        let band = Bollinger::new(20, 2.0).batch(&flat)[19].unwrap();
+        assert_eq!(
+            (band.upper, band.middle, band.lower, band.width),
+            (10.0, 10.0, 10.0, 0.0)
+        );
+
+        let band = Bollinger::new(2, 2.0).batch(&[1.0, 3.0])[1].unwrap();
+        assert_eq!((band.upper, band.lower), (4.0, 0.0));
+    }
+
+    #[test]
+    fn test_kdj_and_volume_ratio() {
+        // 收在区间最高价时 RSV 为 100，K、D 由 50 逐步逼近
+        let candles: Vec<Candle> = [(10.0, 9.5), (11.0, 11.0), (12.0, 12.0)]
+            .iter()
+            .map(|&(high, close)| Candle {
+                high,
+                low: 9.0,
+                close,
+                volume: 0.0,
+            })
+            .collect();
+        let points = Kdj::new(2, 3, 3).batch(&candles);
+        assert_eq!(points[0], None);
+        let first = points[1].unwrap();
+        assert!((first.k - 200.0 / 3.0).abs() < 1e-9);
+        assert!((first.d - 500.0 / 9.0).abs() < 1e-9);
+        assert!((first.j - (3.0 * first.k - 2.0 * first.d)).abs() < 1e-9);
+        assert!(points[2].unwrap().k > first.k);
+
+        let ratios = VolumeRatio::new(2).batch(&[100.0, 200.0, 300.0, 0.0, 0.0, 50.0]);
+        assert_eq!(&ratios[..3], &[None, None, Some(2.0)]);
+        assert_eq!(ratios[4], Some(0.0));
+        assert_eq!(ratios[5], None);
+    }
+
+    #[test]
+    fn test_macd_warm_up_and_cross() {
+        // 加速下跌后反转上涨：DIF 由负转正，DEA 滞后，最终柱状值为正
+        let mut closes: Vec<f64> = (0..40).map(|i| 100.0 - 0.05 * (i * i) as f64).collect();
+        closes.extend((0..40).map(|i| 20.0 + 2.0 * i as f64));
+        let points = Macd::new(12, 26, 9).batch(&closes);
+
+        assert!(points[..33].iter().all(Option::is_none));
+        assert!(points[33].is_some());
+        assert!(points[39].unwrap().dif < 0.0);
+        // 反转后出现一次 DIF 上穿 DEA
+        let crosses = points[34..]
+            .windows(2)
+            .filter(|w| w[0].unwrap().histogram <= 0.0 && w[1].unwrap().histogram > 0.0)
+            .count();
+        assert_eq!(crosses, 1);
+        let last = points[79].unwrap();
+        assert!(last.dif > 0.0 && last.histogram > 0.0);
+        assert!((last.histogram - (last.dif - last.dea) * 2.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,428 @@
+use crate::config::IntradayConfig;
+use crate::models::MarketSession;
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// K线周期，以秒计，必须能整除一天，保证每个周期都落在同一个UTC日内
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BarInterval(i64);
+
+impl BarInterval {
+    pub const MINUTE: BarInterval = BarInterval(60);
+    pub const FIVE_MINUTES: BarInterval = BarInterval(300);
+    pub const HOUR: BarInterval = BarInterval(3600);
+    pub const DAY: BarInterval = BarInterval(86400);
+
+    pub fn seconds(self) -> i64 {
+        self.0
+    }
+
+    /// 所在周期的起始时间
+    fn bucket_start(self, time: NaiveDateTime) -> NaiveDateTime {
+        let secs = time.and_utc().timestamp();
+        DateTime::from_timestamp(secs - secs.rem_euclid(self.0), 0)
+            .expect("bucket start is within range")
+            .naive_utc()
+    }
+}
+
+impl FromStr for BarInterval {
+    type Err = anyhow::Error;
+
+    /// 解析 1m、5m、15m、1h、4h、1d 形式的周期
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let unit_len = value.chars().last().map_or(0, char::len_utf8);
+        let (count, unit) = value.split_at(value.len() - unit_len);
+        let count: i64 = count
+            .parse()
+            .map_err(|_| anyhow::anyhow!("无效的K线周期: {}", value))?;
+        let unit_secs = match unit {
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            _ => anyhow::bail!("无效的K线周期: {}", value),
+        };
+        let secs = count * unit_secs;
+        if count <= 0 || secs > 86400 || 86400 % secs != 0 {
+            anyhow::bail!("K线周期必须能整除一天: {}", value);
+        }
+        Ok(BarInterval(secs))
+    }
+}
+
+impl fmt::Display for BarInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            secs if secs % 86400 == 0 => write!(f, "{}d", secs / 86400),
+            secs if secs % 3600 == 0 => write!(f, "{}h", secs / 3600),
+            secs => write!(f, "{}m", secs / 60),
+        }
+    }
+}
+
+impl Serialize for BarInterval {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BarInterval {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// 原始行情过期后降采样保存的周期，查询其他周期时由能整除它的最大周期合成
+const STORED_INTERVALS: [BarInterval; 3] = [
+    BarInterval::FIVE_MINUTES,
+    BarInterval::HOUR,
+    BarInterval::DAY,
+];
+
+/// 一根K线
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceBar {
+    /// 周期起始时间（UTC）
+    pub start: NaiveDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// 数据源在周期结束时报告的成交量（A股、美股为当日累计，加密货币为24小时滚动）
+    pub volume: i64,
+    /// 合成该K线的原始行情笔数
+    pub tick_count: i64,
+}
+
+impl PriceBar {
+    fn from_tick(time: NaiveDateTime, price: f64, volume: i64) -> Self {
+        Self {
+            start: time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume,
+            tick_count: 1,
+        }
+    }
+}
+
+/// 将按时间升序的行情或K线合成指定周期的K线
+pub fn aggregate_bars(
+    items: impl IntoIterator<Item = PriceBar>,
+    interval: BarInterval,
+) -> Vec<PriceBar> {
+    let mut bars: Vec<PriceBar> = Vec::new();
+    for item in items {
+        let start = interval.bucket_start(item.start);
+        match bars.last_mut() {
+            Some(bar) if bar.start == start => {
+                bar.high = bar.high.max(item.high);
+                bar.low = bar.low.min(item.low);
+                bar.close = item.close;
+                bar.volume = item.volume;
+                bar.tick_count += item.tick_count;
+            }
+            _ => bars.push(PriceBar { start, ..item }),
+        }
+    }
+    bars
+}
+
+/// 日内行情存储：保存每一笔真实行情，按保留策略降采样为 5m/1h/1d K线后清理
+pub struct IntradayStore {
+    db: SqlitePool,
+}
+
+impl IntradayStore {
+    pub fn new(db: SqlitePool) -> Self {
+        Self { db }
+    }
+
+    /// 记录一笔行情
+    pub async fn record_tick(
+        &self,
+        symbol: &str,
+        price: f64,
+        volume: i64,
+        source: &str,
+        session: MarketSession,
+        fetched_at: DateTime<Utc>,
+    ) -> Result<()> {
+        let fetched_at = fetched_at.naive_utc();
+        sqlx::query!(
+            r#"
+            INSERT INTO price_ticks (symbol, price, volume, source, session, fetched_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+            symbol,
+            price,
+            volume,
+            source,
+            session,
+            fetched_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 查询 [from, to) 内指定周期的K线
+    ///
+    /// 近期数据由原始行情合成；原始行情清理后，由能整除该周期的最大已保存周期合成，
+    /// 不存在这样的周期（如 1m）时较早的区间没有数据。
+    pub async fn bars(
+        &self,
+        symbol: &str,
+        interval: BarInterval,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<Vec<PriceBar>> {
+        let mut items = Vec::new();
+
+        let stored = STORED_INTERVALS
+            .into_iter()
+            .filter(|stored| interval.seconds() % stored.seconds() == 0)
+            .max();
+        if let Some(stored) = stored {
+            let stored = stored.to_string();
+            let rows = sqlx::query!(
+                r#"
+                SELECT bucket_start, open_price, high_price, low_price, close_price, volume, tick_count
+                FROM price_bars
+                WHERE symbol = ? AND interval = ? AND bucket_start >= ? AND bucket_start < ?
+                ORDER BY bucket_start
+                "#,
+                symbol,
+                stored,
+                from,
+                to
+            )
+            .fetch_all(&self.db)
+            .await?;
+            items.extend(rows.into_iter().map(|row| PriceBar {
+                start: row.bucket_start,
+                open: row.open_price,
+                high: row.high_price,
+                low: row.low_price,
+                close: row.close_price,
+                volume: row.volume,
+                tick_count: row.tick_count,
+            }));
+        }
+
+        let ticks = sqlx::query!(
+            r#"
+            SELECT price, volume, fetched_at
+            FROM price_ticks
+            WHERE symbol = ? AND fetched_at >= ? AND fetched_at < ?
+            ORDER BY fetched_at, id
+            "#,
+            symbol,
+            from,
+            to
+        )
+        .fetch_all(&self.db)
+        .await?;
+        items.extend(
+            ticks
+                .into_iter()
+                .map(|tick| PriceBar::from_tick(tick.fetched_at, tick.price, tick.volume)),
+        );
+
+        // 已保存的K线只覆盖已清理的区间，早于所有原始行情，合并后仍按时间升序
+        Ok(aggregate_bars(items, interval))
+    }
+
+    /// 将早于 `retention_days` 天（按UTC日对齐）的原始行情降采样为 5m/1h/1d K线并删除，返回删除的行情数
+    pub async fn downsample(&self, retention_days: i64) -> Result<u64> {
+        let cutoff = (Utc::now().date_naive() - Duration::days(retention_days))
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight is valid");
+
+        let symbols = sqlx::query_scalar!(
+            "SELECT DISTINCT symbol FROM price_ticks WHERE fetched_at < ?",
+            cutoff
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut purged = 0;
+        for symbol in symbols {
+            let mut tx = self.db.begin().await?;
+            let ticks = sqlx::query!(
+                r#"
+                SELECT price, volume, fetched_at
+                FROM price_ticks
+                WHERE symbol = ? AND fetched_at < ?
+                ORDER BY fetched_at, id
+                "#,
+                symbol,
+                cutoff
+            )
+            .fetch_all(&mut *tx)
+            .await?;
+            let ticks: Vec<PriceBar> = ticks
+                .into_iter()
+                .map(|tick| PriceBar::from_tick(tick.fetched_at, tick.price, tick.volume))
+                .collect();
+
+            for interval in STORED_INTERVALS {
+                let interval_name = interval.to_string();
+                for bar in aggregate_bars(ticks.iter().cloned(), interval) {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO price_bars (symbol, interval, bucket_start, open_price, high_price, low_price, close_price, volume, tick_count)
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                        ON CONFLICT(symbol, interval, bucket_start) DO UPDATE SET
+                            high_price = MAX(high_price, excluded.high_price),
+                            low_price = MIN(low_price, excluded.low_price),
+                            close_price = excluded.close_price,
+                            volume = excluded.volume,
+                            tick_count = tick_count + excluded.tick_count
+                        "#,
+                        symbol,
+                        interval_name,
+                        bar.start,
+                        bar.open,
+                        bar.high,
+                        bar.low,
+                        bar.close,
+                        bar.volume,
+                        bar.tick_count
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+
+            let deleted = sqlx::query!(
+                "DELETE FROM price_ticks WHERE symbol = ? AND fetched_at < ?",
+                symbol,
+                cutoff
+            )
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            purged += deleted.rows_affected();
+        }
+
+        Ok(purged)
+    }
+
+    /// 按 `downsample_interval_secs` 定期执行保留策略
+    pub fn start_retention_loop(self: Arc<Self>, config: IntradayConfig) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                config.downsample_interval_secs,
+            ));
+            loop {
+                interval.tick().await;
+                match self.downsample(config.retention_days).await {
+                    Ok(0) => {}
+                    Ok(count) => info!("已将 {} 笔过期日内行情降采样为K线", count),
+                    Err(e) => error!("日内行情降采样失败: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn store() -> IntradayStore {
+        // 内存数据库每个连接相互独立，测试只使用一个连接
+        let db = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&db).await.unwrap();
+        IntradayStore::new(db)
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(
+            "5m".parse::<BarInterval>().unwrap(),
+            BarInterval::FIVE_MINUTES
+        );
+        assert_eq!("4h".parse::<BarInterval>().unwrap().seconds(), 4 * 3600);
+        assert_eq!("1d".parse::<BarInterval>().unwrap().to_string(), "1d");
+        assert!("7m".parse::<BarInterval>().is_err());
+        assert!("2d".parse::<BarInterval>().is_err());
+        assert!("m".parse::<BarInterval>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_downsample_keeps_queries_consistent() {
+        let store = store().await;
+        let day = Utc::now() - Duration::days(10);
+        let day = day.date_naive().and_hms_opt(1, 30, 0).unwrap().and_utc();
+        for (minute, price) in [(0, 10.0), (2, 12.0), (4, 9.0), (6, 11.0), (65, 13.0)] {
+            store
+                .record_tick(
+                    "600000.SH",
+                    price,
+                    100 + minute,
+                    "sina",
+                    MarketSession::Regular,
+                    day + Duration::minutes(minute),
+                )
+                .await
+                .unwrap();
+        }
+
+        let from = day.naive_utc() - Duration::days(1);
+        let to = Utc::now().naive_utc();
+        let before = store
+            .bars("600000.SH", "15m".parse().unwrap(), from, to)
+            .await
+            .unwrap();
+        assert_eq!(before.len(), 2);
+        assert_eq!(
+            (
+                before[0].open,
+                before[0].high,
+                before[0].low,
+                before[0].close
+            ),
+            (10.0, 12.0, 9.0, 11.0)
+        );
+
+        assert_eq!(store.downsample(7).await.unwrap(), 5);
+        let after = store
+            .bars("600000.SH", "15m".parse().unwrap(), from, to)
+            .await
+            .unwrap();
+        assert_eq!(after, before);
+
+        let daily = store
+            .bars("600000.SH", BarInterval::DAY, from, to)
+            .await
+            .unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(
+            (daily[0].close, daily[0].volume, daily[0].tick_count),
+            (13.0, 165, 5)
+        );
+        assert!(store
+            .bars("600000.SH", BarInterval::MINUTE, from, to)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,10 +1,36 @@
 // Services module
+pub mod backtest;
+pub mod calendar;
+pub mod corporate_actions;
+pub mod crypto;
 pub mod db;
 pub mod email;
 pub mod fetcher;
+pub mod fx;
+pub mod indicators;
+pub mod intraday;
+pub mod provider_health;
+pub mod quotes;
+pub mod rate_limiter;
+pub mod redis_cache;
+pub mod scoring;
 pub mod strategy_analyzer;
+pub mod strategy_params;
+pub mod strategy_scanner;
+pub mod stream;
+pub mod validation;
 
+pub use backtest::*;
+pub use calendar::*;
+pub use corporate_actions::*;
+pub use crypto::*;
 pub use db::*;
 pub use email::*;
 pub use fetcher::*;
+pub use fx::*;
+pub use intraday::*;
+pub use quotes::*;
+pub use scoring::*;
 pub use strategy_analyzer::*;
+pub use strategy_params::*;
+pub use strategy_scanner::*;
@@ -0,0 +1,270 @@
+use crate::config::CircuitBreakerConfig;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// 探测请求被取消（如周期超时）时，超过该时间后允许发起新的探测
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 熔断器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// 正常放行
+    Closed,
+    /// 熔断中，冷却期内跳过该数据源
+    Open,
+    /// 冷却期结束，放行一个探测请求
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct ProviderStats {
+    // 最近 window 次请求的结果与耗时
+    recent: VecDeque<(bool, Duration)>,
+    consecutive_failures: u32,
+    state: CircuitState,
+    open_until: Option<Instant>,
+    probe_started: Option<Instant>,
+    last_error: Option<String>,
+    last_error_at: Option<DateTime<Utc>>,
+    last_success_at: Option<DateTime<Utc>>,
+}
+
+impl ProviderStats {
+    fn new() -> Self {
+        Self {
+            recent: VecDeque::new(),
+            consecutive_failures: 0,
+            state: CircuitState::Closed,
+            open_until: None,
+            probe_started: None,
+            last_error: None,
+            last_error_at: None,
+            last_success_at: None,
+        }
+    }
+
+    fn push(&mut self, success: bool, latency: Duration, window: usize) {
+        self.recent.push_back((success, latency));
+        while self.recent.len() > window.max(1) {
+            self.recent.pop_front();
+        }
+    }
+}
+
+/// 单个数据源的健康状况
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderHealthSnapshot {
+    pub provider: String,
+    pub state: CircuitState,
+    pub healthy: bool,
+    /// 最近请求的成功率，尚无请求时为 None
+    pub success_rate: Option<f64>,
+    pub avg_latency_ms: Option<u64>,
+    pub requests: usize,
+    pub consecutive_failures: u32,
+    pub cooldown_remaining_secs: u64,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    pub last_success_at: Option<DateTime<Utc>>,
+}
+
+/// 数据源健康统计与熔断器
+///
+/// 连续失败达到 `failure_threshold` 次后熔断，冷却 `cooldown_secs` 秒内直接跳过该数据源；
+/// 冷却结束后放行一个探测请求，成功则恢复，失败则重新熔断。
+pub struct ProviderHealth {
+    providers: Mutex<HashMap<String, ProviderStats>>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    window: usize,
+}
+
+impl ProviderHealth {
+    pub fn new(config: &CircuitBreakerConfig) -> Self {
+        Self {
+            providers: Mutex::new(HashMap::new()),
+            failure_threshold: config.failure_threshold.max(1),
+            cooldown: Duration::from_secs(config.cooldown_secs),
+            window: config.window,
+        }
+    }
+
+    fn with_stats<T>(&self, provider: &str, f: impl FnOnce(&mut ProviderStats) -> T) -> T {
+        let mut providers = self
+            .providers
+            .lock()
+            .expect("provider health lock poisoned");
+        f(providers
+            .entry(provider.to_string())
+            .or_insert_with(ProviderStats::new))
+    }
+
+    /// 是否允许向该数据源发送请求；冷却结束时转为半开并放行一个探测请求
+    pub fn allow(&self, provider: &str) -> bool {
+        let now = Instant::now();
+        self.with_stats(provider, |stats| match stats.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                if stats.open_until.is_some_and(|until| now < until) {
+                    return false;
+                }
+                info!("Circuit for provider {} half-open, probing", provider);
+                stats.state = CircuitState::HalfOpen;
+                stats.probe_started = Some(now);
+                true
+            }
+            CircuitState::HalfOpen => {
+                if stats
+                    .probe_started
+                    .is_some_and(|started| now.saturating_duration_since(started) < PROBE_TIMEOUT)
+                {
+                    return false;
+                }
+                stats.probe_started = Some(now);
+                true
+            }
+        })
+    }
+
+    pub fn record_success(&self, provider: &str, latency: Duration) {
+        let window = self.window;
+        self.with_stats(provider, |stats| {
+            stats.push(true, latency, window);
+            stats.consecutive_failures = 0;
+            stats.last_success_at = Some(Utc::now());
+            stats.probe_started = None;
+            if stats.state != CircuitState::Closed {
+                info!(
+                    "Circuit for provider {} closed after successful probe",
+                    provider
+                );
+                stats.state = CircuitState::Closed;
+                stats.open_until = None;
+            }
+        });
+    }
+
+    pub fn record_failure(&self, provider: &str, latency: Duration, error: &str) {
+        let now = Instant::now();
+        let (window, threshold, cooldown) = (self.window, self.failure_threshold, self.cooldown);
+        self.with_stats(provider, |stats| {
+            stats.push(false, latency, window);
+            stats.consecutive_failures += 1;
+            stats.last_error = Some(error.to_string());
+            stats.last_error_at = Some(Utc::now());
+            stats.probe_started = None;
+
+            let trip = stats.state == CircuitState::HalfOpen
+                || (stats.state == CircuitState::Closed
+                    && stats.consecutive_failures >= threshold);
+            if trip {
+                warn!(
+                    "Circuit for provider {} opened after {} consecutive failures, cooling down {}s: {}",
+                    provider,
+                    stats.consecutive_failures,
+                    cooldown.as_secs(),
+                    error
+                );
+                stats.state = CircuitState::Open;
+                stats.open_until = Some(now + cooldown);
+            }
+        });
+    }
+
+    /// 数据源当前是否可用（未熔断或冷却已结束）
+    pub fn is_healthy(&self, provider: &str) -> bool {
+        let now = Instant::now();
+        self.with_stats(provider, |stats| {
+            stats.state != CircuitState::Open || stats.open_until.is_none_or(|until| now >= until)
+        })
+    }
+
+    /// 获取指定数据源的健康状况
+    pub fn snapshot(&self, providers: &[&str]) -> Vec<ProviderHealthSnapshot> {
+        let now = Instant::now();
+        providers
+            .iter()
+            .map(|&provider| {
+                self.with_stats(provider, |stats| {
+                    let requests = stats.recent.len();
+                    let successes = stats.recent.iter().filter(|(ok, _)| *ok).count();
+                    let total_latency: Duration = stats.recent.iter().map(|(_, d)| *d).sum();
+                    let cooldown_remaining = stats
+                        .open_until
+                        .filter(|_| stats.state == CircuitState::Open)
+                        .map(|until| until.saturating_duration_since(now))
+                        .unwrap_or_default();
+
+                    ProviderHealthSnapshot {
+                        provider: provider.to_string(),
+                        state: stats.state,
+                        healthy: cooldown_remaining.is_zero(),
+                        success_rate: (requests > 0).then(|| successes as f64 / requests as f64),
+                        avg_latency_ms: (requests > 0)
+                            .then(|| (total_latency / requests as u32).as_millis() as u64),
+                        requests,
+                        consecutive_failures: stats.consecutive_failures,
+                        cooldown_remaining_secs: cooldown_remaining.as_secs(),
+                        last_error: stats.last_error.clone(),
+                        last_error_at: stats.last_error_at,
+                        last_success_at: stats.last_success_at,
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health(cooldown_secs: u64) -> ProviderHealth {
+        ProviderHealth::new(&CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown_secs,
+            window: 10,
+        })
+    }
+
+    #[test]
+    fn test_circuit_opens_after_consecutive_failures() {
+        let health = health(60);
+
+        assert!(health.allow("sina"));
+        health.record_failure("sina", Duration::from_millis(5000), "timeout");
+        assert!(health.allow("sina"));
+        health.record_failure("sina", Duration::from_millis(5000), "timeout");
+
+        assert!(!health.allow("sina"));
+        assert!(!health.is_healthy("sina"));
+        let snapshot = &health.snapshot(&["sina"])[0];
+        assert_eq!(snapshot.state, CircuitState::Open);
+        assert_eq!(snapshot.success_rate, Some(0.0));
+        assert_eq!(snapshot.last_error.as_deref(), Some("timeout"));
+    }
+
+    #[test]
+    fn test_half_open_allows_single_probe() {
+        let health = health(0);
+        health.record_failure("sina", Duration::ZERO, "boom");
+        health.record_failure("sina", Duration::ZERO, "boom");
+
+        // 冷却结束后只放行一个探测请求
+        assert!(health.allow("sina"));
+        assert!(!health.allow("sina"));
+
+        // 探测失败重新熔断，探测成功恢复
+        health.record_failure("sina", Duration::ZERO, "still down");
+        assert!(health.allow("sina"));
+        health.record_success("sina", Duration::from_millis(100));
+        assert_eq!(health.snapshot(&["sina"])[0].state, CircuitState::Closed);
+        assert!(health.allow("sina"));
+    }
+}
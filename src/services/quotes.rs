@@ -0,0 +1,214 @@
+use crate::config::PriceFetcherConfig;
+use crate::models::{DataQuality, MarketSession};
+use crate::services::fetcher::PriceService;
+use crate::services::redis_cache::RedisCache;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures_util::future::{BoxFuture, FutureExt, Shared};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tracing::error;
+
+/// 数据库中最近一次保存的价格未记录数据源时使用
+pub const QUOTE_SOURCE_HISTORY: &str = "history";
+
+/// 一笔行情报价
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quote {
+    pub symbol: String,
+    pub price: f64,
+    pub volume: i64,
+    pub timestamp: DateTime<Utc>,
+    /// 产生该价格的数据源
+    pub source: String,
+    pub name_en: Option<String>,
+    /// 行情所属交易时段，美股盘前盘后为 pre_market / after_hours
+    #[serde(default)]
+    pub session: MarketSession,
+    #[serde(default)]
+    pub data_quality: DataQuality,
+    /// 实时获取失败时从数据库回退得到的报价
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// 行情缓存：轮询、推送与接口共用的本地缓存，可选 Redis 供多实例共享
+pub struct QuoteCache {
+    local: RwLock<HashMap<String, Quote>>,
+    redis: Option<Arc<RedisCache>>,
+    ttl: Duration,
+}
+
+impl QuoteCache {
+    pub fn new(config: &PriceFetcherConfig) -> Self {
+        let redis = config.quote_cache.redis_url.as_deref().and_then(|url| {
+            RedisCache::new(url, &config.quote_cache.key_prefix)
+                .map_err(|e| {
+                    error!(
+                        "Invalid Redis quote cache URL, using local cache only: {}",
+                        e
+                    )
+                })
+                .ok()
+                .map(Arc::new)
+        });
+
+        Self {
+            local: RwLock::new(HashMap::new()),
+            redis,
+            ttl: Duration::from_secs(config.cache_ttl_secs),
+        }
+    }
+
+    /// 本实例最近一次获取的报价，不论是否过期
+    pub async fn get_local(&self, symbol: &str) -> Option<Quote> {
+        self.local.read().await.get(symbol).cloned()
+    }
+
+    /// 未过期的报价：先查本地，再查 Redis
+    pub async fn get(&self, symbol: &str) -> Option<Quote> {
+        if let Some(quote) = self.get_local(symbol).await.filter(|q| self.is_fresh(q)) {
+            return Some(quote);
+        }
+
+        let redis = self.redis.as_ref()?;
+        let quote: Quote = serde_json::from_slice(&redis.get(symbol).await?).ok()?;
+        if !self.is_fresh(&quote) {
+            return None;
+        }
+        self.local
+            .write()
+            .await
+            .insert(symbol.to_string(), quote.clone());
+        Some(quote)
+    }
+
+    /// 更新本地缓存；写入 Redis 放到后台任务，Redis 变慢或不可用时不拖慢轮询
+    pub async fn put(&self, quote: Quote) {
+        if let Some(redis) = &self.redis {
+            match serde_json::to_vec(&quote) {
+                Ok(value) => {
+                    let (redis, symbol, ttl) = (Arc::clone(redis), quote.symbol.clone(), self.ttl);
+                    tokio::spawn(async move { redis.set(&symbol, &value, ttl).await });
+                }
+                Err(e) => error!("Failed to serialize quote for {}: {}", quote.symbol, e),
+            }
+        }
+        self.local.write().await.insert(quote.symbol.clone(), quote);
+    }
+
+    fn is_fresh(&self, quote: &Quote) -> bool {
+        Utc::now() - quote.timestamp < chrono::Duration::from_std(self.ttl).unwrap_or_default()
+    }
+}
+
+type InflightFetch = Shared<BoxFuture<'static, Result<Quote, Arc<anyhow::Error>>>>;
+
+/// 统一行情服务：接口处理器通过它获取报价
+///
+/// 缓存未过期时直接返回；否则经 `PriceService` 按市场路由获取、校验、保存并检查预警。
+/// 同一股票的并发请求合并为一次获取。
+pub struct QuoteService {
+    price_service: Arc<PriceService>,
+    db: SqlitePool,
+    inflight: Mutex<HashMap<String, InflightFetch>>,
+}
+
+impl QuoteService {
+    pub fn new(price_service: Arc<PriceService>, db: SqlitePool) -> Self {
+        Self {
+            price_service,
+            db,
+            inflight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 获取最新报价
+    pub async fn get_quote(&self, symbol: &str) -> Result<Quote> {
+        if let Some(quote) = self.price_service.quote_cache().get(symbol).await {
+            return Ok(quote);
+        }
+
+        let fetch = {
+            let mut inflight = self.inflight.lock().await;
+            inflight
+                .entry(symbol.to_string())
+                .or_insert_with(|| {
+                    let price_service = self.price_service.clone();
+                    let symbol = symbol.to_string();
+                    async move {
+                        price_service
+                            .fetch_live_quote(&symbol)
+                            .await
+                            .map_err(Arc::new)
+                    }
+                    .boxed()
+                    .shared()
+                })
+                .clone()
+        };
+
+        let result = fetch.clone().await;
+
+        let mut inflight = self.inflight.lock().await;
+        if inflight.get(symbol).is_some_and(|f| f.ptr_eq(&fetch)) {
+            inflight.remove(symbol);
+        }
+
+        result.map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    /// 数据库中最近一次保存的行情，优先真实行情，没有时才返回模拟价格
+    pub async fn latest_stored(&self, symbol: &str) -> Result<Option<Quote>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT close_price, volume, created_at, source,
+                   data_quality as "data_quality: DataQuality"
+            FROM price_history
+            WHERE symbol = ?
+            ORDER BY data_quality = 'live' DESC, date DESC, created_at DESC
+            LIMIT 1
+            "#,
+            symbol
+        )
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(row.map(|row| Quote {
+            symbol: symbol.to_string(),
+            price: row.close_price,
+            volume: row.volume,
+            timestamp: to_utc(row.created_at),
+            source: row
+                .source
+                .unwrap_or_else(|| QUOTE_SOURCE_HISTORY.to_string()),
+            name_en: None,
+            session: MarketSession::Regular,
+            data_quality: row.data_quality,
+            stale: true,
+        }))
+    }
+
+    /// 优先实时报价，获取失败时回退到数据库中最近一次保存的价格
+    pub async fn get_quote_or_stored(&self, symbol: &str) -> Result<Option<Quote>> {
+        match self.get_quote(symbol).await {
+            Ok(quote) => Ok(Some(quote)),
+            Err(e) => {
+                tracing::warn!(
+                    "Live quote for {} unavailable, using history: {}",
+                    symbol,
+                    e
+                );
+                self.latest_stored(symbol).await
+            }
+        }
+    }
+}
+
+fn to_utc(timestamp: NaiveDateTime) -> DateTime<Utc> {
+    DateTime::from_naive_utc_and_offset(timestamp, Utc)
+}
@@ -0,0 +1,334 @@
+use crate::config::{PriceFetcherConfig, ProviderRateLimitConfig};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::time::{self, Instant};
+use tracing::{info, warn};
+
+/// 429 后的初始退避时间
+const BASE_BACKOFF: Duration = Duration::from_secs(30);
+/// 退避时间上限
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// 令牌桶 - 按小时预算匀速补充令牌，允许不超过容量的突发请求
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+    backoff_until: Option<Instant>,
+    consecutive_throttles: u32,
+    throttled_total: u64,
+}
+
+impl TokenBucket {
+    fn new(config: &ProviderRateLimitConfig, now: Instant) -> Self {
+        let capacity = config.burst.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: config.requests_per_hour as f64 / 3600.0,
+            last_refill: now,
+            backoff_until: None,
+            consecutive_throttles: 0,
+            throttled_total: 0,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// 限流退避的剩余时间，不在退避期时为 None
+    fn backoff_remaining(&self, now: Instant) -> Option<Duration> {
+        self.backoff_until
+            .filter(|until| now < *until)
+            .map(|until| until - now)
+    }
+
+    /// 尝试取出一个令牌，失败时返回需要等待的时间
+    fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(until) = self.backoff_until {
+            if now < until {
+                return Err(until - now);
+            }
+            self.backoff_until = None;
+        }
+
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+
+        if self.refill_per_sec <= 0.0 {
+            // 预算为0时不会补充令牌，按最大退避时间重试
+            return Err(MAX_BACKOFF);
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - self.tokens) / self.refill_per_sec,
+        ))
+    }
+
+    /// 记录一次限流响应，返回本次退避时间
+    fn throttle(&mut self, now: Instant, retry_after: Option<Duration>) -> Duration {
+        self.consecutive_throttles += 1;
+        self.throttled_total += 1;
+        let backoff = retry_after.unwrap_or_else(|| {
+            BASE_BACKOFF
+                .saturating_mul(2u32.saturating_pow(self.consecutive_throttles - 1))
+                .min(MAX_BACKOFF)
+        });
+        self.backoff_until = Some(now + backoff);
+        // 限流期间已补充的令牌作废，避免退避结束后立刻突发
+        self.tokens = 0.0;
+        self.last_refill = now;
+        backoff
+    }
+}
+
+/// 单个数据源的令牌桶状态
+#[derive(Debug, Clone, Serialize)]
+pub struct BucketSnapshot {
+    pub provider: String,
+    pub tokens: f64,
+    pub capacity: f64,
+    pub requests_per_hour: f64,
+    pub backoff_remaining_secs: u64,
+    pub throttled_total: u64,
+}
+
+/// 按数据源划分的限流器
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    /// 所有数据源共用的并发请求名额，只在发送请求期间持有
+    concurrency: Semaphore,
+    default_limit: ProviderRateLimitConfig,
+    limits: HashMap<String, ProviderRateLimitConfig>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &PriceFetcherConfig) -> Self {
+        Self {
+            buckets: Mutex::new(HashMap::new()),
+            concurrency: Semaphore::new(config.max_concurrent_requests),
+            default_limit: ProviderRateLimitConfig {
+                requests_per_hour: config.max_requests_per_hour,
+                burst: config.max_concurrent_requests as u32,
+            },
+            limits: config.rate_limits.clone(),
+        }
+    }
+
+    fn with_bucket<T>(&self, provider: &str, f: impl FnOnce(&mut TokenBucket, Instant) -> T) -> T {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+        let bucket = buckets.entry(provider.to_string()).or_insert_with(|| {
+            let limit = self.limits.get(provider).unwrap_or(&self.default_limit);
+            TokenBucket::new(limit, now)
+        });
+        f(bucket, now)
+    }
+
+    /// 数据源处于限流退避期时返回剩余时间
+    pub fn backoff_remaining(&self, provider: &str) -> Option<Duration> {
+        self.with_bucket(provider, |bucket, now| bucket.backoff_remaining(now))
+    }
+
+    /// 等待直到该数据源有可用令牌
+    ///
+    /// 令牌不足时排队等待，而不是丢弃请求；处于限流退避期时立即返回错误，由调用方改用其他数据源。
+    pub async fn acquire(&self, provider: &str) -> anyhow::Result<()> {
+        loop {
+            let wait = self.with_bucket(provider, |bucket, now| {
+                if let Some(remaining) = bucket.backoff_remaining(now) {
+                    return Err(anyhow::anyhow!(
+                        "{} is rate limited, backing off for another {}s",
+                        provider,
+                        remaining.as_secs()
+                    ));
+                }
+                Ok(bucket.try_take(now).err())
+            })?;
+            match wait {
+                None => return Ok(()),
+                Some(wait) => time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// 数据源返回 429 / "too many requests" 时调用，进入退避期
+    pub fn throttle(&self, provider: &str, retry_after: Option<Duration>) {
+        let backoff = self.with_bucket(provider, |bucket, now| bucket.throttle(now, retry_after));
+        warn!(
+            "Provider {} is rate limiting us, backing off for {}s",
+            provider,
+            backoff.as_secs()
+        );
+    }
+
+    /// 请求成功后清除连续限流计数
+    pub fn record_success(&self, provider: &str) {
+        self.with_bucket(provider, |bucket, _| {
+            if bucket.consecutive_throttles > 0 {
+                info!("Provider {} recovered from rate limiting", provider);
+                bucket.consecutive_throttles = 0;
+            }
+        });
+    }
+
+    /// 在数据源令牌桶允许后发送请求，识别 429 / "too many requests" 并进入退避
+    ///
+    /// 先取令牌再占用并发名额，等待令牌时不占名额，一个被限流的数据源不会拖住其他数据源的请求。
+    pub async fn send(
+        &self,
+        provider: &str,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<(reqwest::StatusCode, String)> {
+        self.acquire(provider).await?;
+        let _permit = self.concurrency.acquire().await?;
+
+        let response = request.send().await?;
+        let status = response.status();
+        let retry_after = retry_after(response.headers());
+        let body = response.text().await?;
+
+        if is_rate_limited(status, &body) {
+            self.throttle(provider, retry_after);
+            return Err(anyhow::anyhow!(
+                "{} rate limited the request ({})",
+                provider,
+                status
+            ));
+        }
+        if status.is_success() {
+            self.record_success(provider);
+        }
+
+        Ok((status, body))
+    }
+
+    /// 获取所有数据源当前的令牌桶状态
+    pub fn snapshot(&self) -> Vec<BucketSnapshot> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().expect("rate limiter lock poisoned");
+
+        // 确保已配置但尚未使用的数据源也能显示
+        for (provider, limit) in &self.limits {
+            buckets
+                .entry(provider.clone())
+                .or_insert_with(|| TokenBucket::new(limit, now));
+        }
+
+        let mut snapshots: Vec<BucketSnapshot> = buckets
+            .iter_mut()
+            .map(|(provider, bucket)| {
+                bucket.refill(now);
+                BucketSnapshot {
+                    provider: provider.clone(),
+                    tokens: (bucket.tokens * 100.0).floor() / 100.0,
+                    capacity: bucket.capacity,
+                    requests_per_hour: bucket.refill_per_sec * 3600.0,
+                    backoff_remaining_secs: bucket
+                        .backoff_until
+                        .map(|until| until.saturating_duration_since(now).as_secs())
+                        .unwrap_or(0),
+                    throttled_total: bucket.throttled_total,
+                }
+            })
+            .collect();
+        snapshots.sort_by(|a, b| a.provider.cmp(&b.provider));
+        snapshots
+    }
+}
+
+/// 判断响应是否为限流响应
+pub fn is_rate_limited(status: reqwest::StatusCode, body: &str) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || body.to_lowercase().contains("too many requests")
+}
+
+/// 解析 Retry-After 响应头（仅支持秒数格式）
+pub fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(requests_per_hour: u64, burst: u32) -> (TokenBucket, Instant) {
+        let now = Instant::now();
+        let config = ProviderRateLimitConfig {
+            requests_per_hour,
+            burst,
+        };
+        (TokenBucket::new(&config, now), now)
+    }
+
+    fn limiter(requests_per_hour: u64, burst: u32) -> RateLimiter {
+        RateLimiter {
+            buckets: Mutex::new(HashMap::new()),
+            concurrency: Semaphore::new(1),
+            default_limit: ProviderRateLimitConfig {
+                requests_per_hour,
+                burst,
+            },
+            limits: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_bucket_allows_burst_then_waits_for_refill() {
+        let (mut bucket, now) = bucket(3600, 2);
+
+        assert!(bucket.try_take(now).is_ok());
+        assert!(bucket.try_take(now).is_ok());
+        let wait = bucket.try_take(now).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(1));
+
+        assert!(bucket.try_take(now + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_throttle_backs_off_exponentially_and_honours_retry_after() {
+        let (mut bucket, now) = bucket(3600, 5);
+
+        assert_eq!(bucket.throttle(now, None), BASE_BACKOFF);
+        assert_eq!(bucket.throttle(now, None), BASE_BACKOFF * 2);
+        assert_eq!(
+            bucket.throttle(now, Some(Duration::from_secs(7))),
+            Duration::from_secs(7)
+        );
+        assert_eq!(
+            bucket.try_take(now + Duration::from_secs(2)).unwrap_err(),
+            Duration::from_secs(5)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_acquire_skips_provider_in_backoff() {
+        let limiter = limiter(3600, 1);
+        limiter.throttle("yahoo", Some(Duration::from_secs(60)));
+
+        // 退避中的数据源立即返回错误，其他数据源不受影响
+        assert!(limiter.acquire("yahoo").await.is_err());
+        assert!(limiter.backoff_remaining("yahoo").is_some());
+        assert!(limiter.acquire("sina").await.is_ok());
+        assert_eq!(limiter.backoff_remaining("sina"), None);
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::Result;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use redis::AsyncCommands;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// 建立连接与单次命令的超时，超时按缓存未命中处理，不阻塞行情获取
+const REDIS_TIMEOUT: Duration = Duration::from_millis(500);
+/// 连接或命令失败后暂停访问 Redis 的时间，期间读写直接按未命中处理
+const REDIS_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+struct ConnectionState {
+    conn: Option<ConnectionManager>,
+    /// 在此之前不访问 Redis：失败后的退避，或另一个调用者正在建立连接
+    paused_until: Option<Instant>,
+}
+
+/// 多实例部署时共享的行情缓存，只使用 GET / SET EX
+///
+/// 使用自动重连的多路复用连接，并发的读写互不等待；连接或命令失败后暂停访问
+/// `REDIS_RETRY_AFTER`，Redis 不可用时缓存读写立即返回。
+pub struct RedisCache {
+    client: redis::Client,
+    key_prefix: String,
+    state: Mutex<ConnectionState>,
+}
+
+impl RedisCache {
+    /// `url` 格式为 `redis://[[user]:password@]host[:port][/db]`，此时不建立连接
+    pub fn new(url: &str, key_prefix: &str) -> Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+            key_prefix: key_prefix.to_string(),
+            state: Mutex::new(ConnectionState::default()),
+        })
+    }
+
+    /// 读取缓存，出错时记录日志并视为未命中
+    pub async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let key = format!("{}{}", self.key_prefix, key);
+        let mut conn = self.connection().await?;
+        match conn.get::<_, Option<Vec<u8>>>(&key).await {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Redis GET {} failed: {}", key, e);
+                self.pause();
+                None
+            }
+        }
+    }
+
+    /// 写入缓存并设置过期时间，出错时只记录日志
+    pub async fn set(&self, key: &str, value: &[u8], ttl: Duration) {
+        let key = format!("{}{}", self.key_prefix, key);
+        let Some(mut conn) = self.connection().await else {
+            return;
+        };
+        if let Err(e) = conn
+            .set_ex::<_, _, ()>(&key, value, ttl.as_secs().max(1))
+            .await
+        {
+            warn!("Redis SET {} failed: {}", key, e);
+            self.pause();
+        }
+    }
+
+    /// 获取共享连接，首次使用时建立；暂停期间返回 None
+    async fn connection(&self) -> Option<ConnectionManager> {
+        {
+            let mut state = self.state.lock().expect("redis cache lock poisoned");
+            if state
+                .paused_until
+                .is_some_and(|until| Instant::now() < until)
+            {
+                return None;
+            }
+            if let Some(conn) = &state.conn {
+                return Some(conn.clone());
+            }
+            // 建立连接期间其他调用者直接按未命中处理，连接失败时保持暂停
+            state.paused_until = Some(Instant::now() + REDIS_RETRY_AFTER);
+        }
+
+        let config = ConnectionManagerConfig::new()
+            .set_connection_timeout(REDIS_TIMEOUT)
+            .set_response_timeout(REDIS_TIMEOUT)
+            .set_number_of_retries(1);
+        match ConnectionManager::new_with_config(self.client.clone(), config).await {
+            Ok(conn) => {
+                info!("Connected to Redis quote cache");
+                let mut state = self.state.lock().expect("redis cache lock poisoned");
+                state.paused_until = None;
+                state.conn = Some(conn.clone());
+                Some(conn)
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to connect to Redis quote cache, retrying in {}s: {}",
+                    REDIS_RETRY_AFTER.as_secs(),
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// 命令失败后暂停访问，连接由 `ConnectionManager` 在后台重连
+    fn pause(&self) {
+        self.state
+            .lock()
+            .expect("redis cache lock poisoned")
+            .paused_until = Some(Instant::now() + REDIS_RETRY_AFTER);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_non_redis_url() {
+        assert!(RedisCache::new("redis://:secret@cache.local/2", "q:").is_ok());
+        assert!(RedisCache::new("http://localhost", "q:").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_redis_is_skipped_after_failure() {
+        let cache = RedisCache::new("redis://127.0.0.1:1/", "q:").unwrap();
+        assert_eq!(cache.get("AAPL").await, None);
+
+        // 连接失败后在暂停期内不再尝试连接，读写立即返回
+        let started = Instant::now();
+        assert_eq!(cache.get("AAPL").await, None);
+        cache.set("AAPL", b"{}", Duration::from_secs(30)).await;
+        assert!(started.elapsed() < Duration::from_millis(50));
+        assert!(cache.state.lock().unwrap().paused_until.is_some());
+    }
+}
@@ -0,0 +1,285 @@
+use crate::config::ScoringConfig;
+use crate::services::indicators::{Indicator, Sma, StdDev, VolumeRatio};
+use crate::services::{PriceData, StrategySignal};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// 风险等级，按近期日收益率波动划分
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "低风险",
+            RiskLevel::Medium => "中风险",
+            RiskLevel::High => "高风险",
+        }
+    }
+}
+
+/// 操作建议
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Recommendation {
+    Buy,
+    Hold,
+    Sell,
+    Watch,
+}
+
+impl Recommendation {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Recommendation::Buy => "买入",
+            Recommendation::Hold => "持有",
+            Recommendation::Sell => "卖出",
+            Recommendation::Watch => "观望",
+        }
+    }
+}
+
+/// 单只股票某个交易日的综合评分，对应 strategy_analysis 表的一行
+#[derive(Debug, Clone, Serialize)]
+pub struct CompositeScore {
+    pub symbol: String,
+    /// 评分所用最新K线的日期
+    pub analysis_date: NaiveDate,
+    pub total_signals: i64,
+    pub avg_signal_strength: f64,
+    /// 综合评分 0-100，50 为中性
+    pub composite_score: f64,
+    pub risk_level: RiskLevel,
+    pub recommendation: Recommendation,
+    pub key_points: Vec<String>,
+}
+
+/// 综合当日信号、均线趋势、波动率与量能计算评分
+///
+/// 以 50 分为中性：信号 ±25、趋势 ±25、波动率 -10~+5、量能 ±10，合计后截断到 0-100。
+/// `prices` 须为按日期升序、每个交易日一根的日线（见 `StrategyAnalyzer::get_price_history`）。
+pub fn composite_score(
+    symbol: &str,
+    prices: &[PriceData],
+    signals: &[StrategySignal],
+    config: &ScoringConfig,
+) -> Option<CompositeScore> {
+    let current = prices.last()?;
+    let analysis_date = NaiveDate::parse_from_str(&current.date, "%Y-%m-%d").ok()?;
+    let mut key_points = Vec::new();
+
+    // 1. 信号：看多/看空信号按强度加减分
+    let net: f64 = signals
+        .iter()
+        .map(|s| s.bias() as f64 * s.signal_strength() as f64)
+        .sum();
+    let signal_score = (net * 2.5).clamp(-25.0, 25.0);
+    let bullish = signals.iter().filter(|s| s.bias() > 0).count();
+    let bearish = signals.iter().filter(|s| s.bias() < 0).count();
+    if !signals.is_empty() {
+        key_points.push(format!(
+            "当日信号{}个（看多{}、看空{}）",
+            signals.len(),
+            bullish,
+            bearish
+        ));
+    }
+    let avg_signal_strength = if signals.is_empty() {
+        0.0
+    } else {
+        signals
+            .iter()
+            .map(|s| s.signal_strength() as f64)
+            .sum::<f64>()
+            / signals.len() as f64
+    };
+
+    // 2. 趋势：收盘价相对 MA60/120/250 的位置与均线排列
+    let closes: Vec<f64> = prices.iter().map(|p| p.close).collect();
+    let mas: Vec<(usize, Option<f64>)> = [60, 120, 250]
+        .into_iter()
+        .map(|period| (period, Sma::new(period).last(&closes)))
+        .collect();
+    let mut trend_score = 0.0;
+    let (mut above, mut below) = (Vec::new(), Vec::new());
+    for &(period, ma) in &mas {
+        match ma {
+            Some(ma) if current.close >= ma => {
+                trend_score += 5.0;
+                above.push(format!("MA{period}"));
+            }
+            Some(_) => {
+                trend_score -= 5.0;
+                below.push(format!("MA{period}"));
+            }
+            None => {}
+        }
+    }
+    if !above.is_empty() {
+        key_points.push(format!("收盘价位于{}上方", above.join("/")));
+    }
+    if !below.is_empty() {
+        key_points.push(format!("收盘价位于{}下方", below.join("/")));
+    }
+    if let [(_, Some(ma60)), (_, Some(ma120)), (_, Some(ma250))] = mas[..] {
+        if ma60 > ma120 && ma120 > ma250 {
+            trend_score += 10.0;
+            key_points.push("MA60/120/250多头排列".to_string());
+        } else if ma60 < ma120 && ma120 < ma250 {
+            trend_score -= 10.0;
+            key_points.push("MA60/120/250空头排列".to_string());
+        }
+    }
+
+    // 3. 波动率：最近 lookback_period 根K线日收益率的标准差
+    let returns: Vec<f64> = prices
+        .windows(2)
+        .filter(|w| w[0].close > 0.0)
+        .map(|w| (w[1].close / w[0].close - 1.0) * 100.0)
+        .collect();
+    let period = config.lookback_period.max(2);
+    let volatility = StdDev::new(period.min(returns.len())).last(&returns);
+    let (risk_level, volatility_score) = match volatility {
+        Some(v) if v >= config.high_volatility_percent => (RiskLevel::High, -10.0),
+        Some(v) if v <= config.low_volatility_percent => (RiskLevel::Low, 5.0),
+        _ => (RiskLevel::Medium, 0.0),
+    };
+    if let Some(v) = volatility {
+        key_points.push(format!("日波动率{:.2}%，{}", v, risk_level.label()));
+    }
+
+    // 4. 量能：放量上涨加分，放量下跌减分
+    let volumes: Vec<f64> = prices.iter().map(|p| p.volume as f64).collect();
+    let volume_ratio = VolumeRatio::new(period).last(&volumes);
+    let previous_close = prices
+        .len()
+        .checked_sub(2)
+        .map(|i| prices[i].close)
+        .unwrap_or(current.close);
+    let volume_score = match volume_ratio {
+        Some(ratio) if ratio >= 1.5 && current.close > previous_close => {
+            key_points.push(format!("放量上涨，量比{:.2}", ratio));
+            10.0
+        }
+        Some(ratio) if ratio >= 1.5 && current.close < previous_close => {
+            key_points.push(format!("放量下跌，量比{:.2}", ratio));
+            -10.0
+        }
+        _ => 0.0,
+    };
+
+    let score =
+        (50.0 + signal_score + trend_score + volatility_score + volume_score).clamp(0.0, 100.0);
+    let composite_score = (score * 10.0).round() / 10.0;
+    let recommendation = if composite_score >= config.buy_score {
+        if risk_level == RiskLevel::High {
+            Recommendation::Watch
+        } else {
+            Recommendation::Buy
+        }
+    } else if composite_score <= config.sell_score {
+        Recommendation::Sell
+    } else if composite_score >= 50.0 {
+        Recommendation::Hold
+    } else {
+        Recommendation::Watch
+    };
+
+    Some(CompositeScore {
+        symbol: symbol.to_string(),
+        analysis_date,
+        total_signals: signals.len() as i64,
+        avg_signal_strength,
+        composite_score,
+        risk_level,
+        recommendation,
+        key_points,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::TechnicalSignal;
+    use chrono::Days;
+
+    fn bars(closes: &[f64], last_volume: u64) -> Vec<PriceData> {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, &close)| PriceData {
+                date: (start + Days::new(i as u64)).format("%Y-%m-%d").to_string(),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: if i + 1 == closes.len() {
+                    last_volume
+                } else {
+                    1_000
+                },
+                change_percent: None,
+            })
+            .collect()
+    }
+
+    fn technical(name: &str, strength: u8) -> StrategySignal {
+        StrategySignal::TechnicalIndicator(TechnicalSignal {
+            symbol: "600000.SH".to_string(),
+            indicator_name: name.to_string(),
+            value: 0.0,
+            price: 10.0,
+            signal_strength: strength,
+            description: name.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_uptrend_with_bullish_signals_is_buy() {
+        // 缓慢上涨：均线多头排列、低波动、最后一根放量上涨
+        let closes: Vec<f64> = (0..260).map(|i| 10.0 + i as f64 * 0.02).collect();
+        let prices = bars(&closes, 3_000);
+        let signals = vec![technical("MACD金叉", 4), technical("量比放大", 3)];
+
+        let score =
+            composite_score("600000.SH", &prices, &signals, &ScoringConfig::default()).unwrap();
+        // 50 + 信号10 + 趋势25 + 低波动5 + 放量上涨10
+        assert_eq!(score.composite_score, 100.0);
+        assert_eq!(score.risk_level, RiskLevel::Low);
+        assert_eq!(score.recommendation, Recommendation::Buy);
+        assert_eq!(score.total_signals, 2);
+        assert_eq!(score.avg_signal_strength, 3.5);
+        assert_eq!(
+            score.analysis_date,
+            NaiveDate::from_ymd_opt(2024, 9, 16).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_volatile_downtrend_is_sell() {
+        // 下跌且每日涨跌 ±5% 交替：高风险、均线空头排列
+        let mut close = 100.0;
+        let closes: Vec<f64> = (0..260)
+            .map(|i| {
+                close *= if i % 2 == 0 { 1.05 } else { 0.94 };
+                close
+            })
+            .collect();
+        let prices = bars(&closes, 1_000);
+        let signals = vec![technical("KDJ死叉", 3)];
+
+        let score =
+            composite_score("600000.SH", &prices, &signals, &ScoringConfig::default()).unwrap();
+        assert_eq!(score.risk_level, RiskLevel::High);
+        assert_eq!(score.recommendation, Recommendation::Sell);
+        assert!(score.composite_score <= 10.0);
+    }
+}
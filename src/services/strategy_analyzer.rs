@@ -1,4 +1,14 @@
+use crate::config::{IndicatorConfig, ScoringConfig, StrategyParams};
+use crate::models::AlertIndicator;
+use crate::services::indicators::{
+    BollingerPoint, Candle, Indicator, IndicatorSet, IndicatorSnapshot, KdjPoint, MacdPoint, Sma,
+};
+use crate::services::{
+    adjust_prices, composite_score, BarInterval, CompositeScore, CorporateActionService,
+    IntradayStore, ParamSet, PriceAdjustment, Recommendation, RiskLevel,
+};
 use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use tracing::{info, warn};
@@ -6,6 +16,8 @@ use tracing::{info, warn};
 /// 策略分析器 - 实现YF扬帆顶底战法
 pub struct StrategyAnalyzer {
     db: SqlitePool,
+    indicators: IndicatorConfig,
+    params: StrategyParams,
 }
 
 /// 价格数据结构
@@ -20,6 +32,17 @@ pub struct PriceData {
     pub change_percent: Option<f64>,
 }
 
+impl From<&PriceData> for Candle {
+    fn from(price: &PriceData) -> Self {
+        Candle {
+            high: price.high,
+            low: price.low,
+            close: price.close,
+            volume: price.volume as f64,
+        }
+    }
+}
+
 /// 策略信号类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -29,6 +52,87 @@ pub enum StrategySignal {
     TechnicalIndicator(TechnicalSignal),
 }
 
+impl StrategySignal {
+    /// 保存到 strategy_signals.strategy_type 的类型名
+    pub fn strategy_type(&self) -> &'static str {
+        match self {
+            StrategySignal::LimitUpPullback(_) => "limit_up_pullback",
+            StrategySignal::BottomBreakout(_) => "bottom_breakout",
+            StrategySignal::TechnicalIndicator(_) => "technical_indicator",
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        match self {
+            StrategySignal::LimitUpPullback(s) => &s.symbol,
+            StrategySignal::BottomBreakout(s) => &s.symbol,
+            StrategySignal::TechnicalIndicator(s) => &s.symbol,
+        }
+    }
+
+    pub fn signal_strength(&self) -> u8 {
+        match self {
+            StrategySignal::LimitUpPullback(s) => s.signal_strength,
+            StrategySignal::BottomBreakout(s) => s.signal_strength,
+            StrategySignal::TechnicalIndicator(s) => s.signal_strength,
+        }
+    }
+
+    /// 触发价格：回踩价、突破价或技术指标信号当日收盘价
+    pub fn trigger_price(&self) -> f64 {
+        match self {
+            StrategySignal::LimitUpPullback(s) => s.pullback_price,
+            StrategySignal::BottomBreakout(s) => s.breakout_price,
+            StrategySignal::TechnicalIndicator(s) => s.price,
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            StrategySignal::LimitUpPullback(s) => &s.description,
+            StrategySignal::BottomBreakout(s) => &s.description,
+            StrategySignal::TechnicalIndicator(s) => &s.description,
+        }
+    }
+
+    /// 信号有效天数：技术指标信号只当天有效，形态信号保留数日
+    pub fn valid_days(&self) -> i64 {
+        match self {
+            StrategySignal::LimitUpPullback(_) => 3,
+            StrategySignal::BottomBreakout(_) => 5,
+            StrategySignal::TechnicalIndicator(_) => 1,
+        }
+    }
+
+    /// 信号方向：1 看多，-1 看空，0 中性（收口、量能与换手类信号）
+    pub fn bias(&self) -> i8 {
+        match self {
+            StrategySignal::LimitUpPullback(_) | StrategySignal::BottomBreakout(_) => 1,
+            StrategySignal::TechnicalIndicator(s) => match s.indicator_name.as_str() {
+                "均线多头" | "放量上涨" | "RSI超卖" | "MACD金叉" | "MACD底背离"
+                | "布林向上开口" | "布林上轨突破" | "KDJ金叉" | "KDJ超卖" => 1,
+                "RSI超买" | "MACD死叉" | "MACD顶背离" | "布林向下开口" | "布林下轨跌破"
+                | "KDJ死叉" | "KDJ超买" => -1,
+                _ => 0,
+            },
+        }
+    }
+}
+
+/// 数据库中保存的策略信号
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredSignal {
+    pub id: i64,
+    pub symbol: String,
+    pub signal: StrategySignal,
+    /// 产生信号的参数组名称与当时生效的参数
+    pub param_set: Option<String>,
+    pub params: Option<serde_json::Value>,
+    pub created_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub is_active: bool,
+}
+
 /// 涨停回踩信号
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LimitUpPullbackSignal {
@@ -62,10 +166,68 @@ pub struct TechnicalSignal {
     pub symbol: String,
     pub indicator_name: String,
     pub value: f64,
+    /// 信号触发时的收盘价
+    pub price: f64,
     pub signal_strength: u8,
     pub description: String,
 }
 
+/// 单根K线的技术指标值，预热期内为 None
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorValues {
+    pub date: String,
+    pub close: f64,
+    pub rsi: Option<f64>,
+    pub macd: Option<MacdPoint>,
+    pub bollinger: Option<BollingerPoint>,
+    pub kdj: Option<KdjPoint>,
+    /// 平均真实波幅，与价格同单位
+    pub atr: Option<f64>,
+    pub volume_ratio: Option<f64>,
+    /// 换手率（%），没有流通股本时为 None
+    pub turnover_rate: Option<f64>,
+}
+
+impl IndicatorValues {
+    /// 由K线与该K线上的指标值组成，换手率需要流通股本（股）
+    pub fn new(price: &PriceData, snapshot: IndicatorSnapshot, float_shares: Option<f64>) -> Self {
+        Self {
+            date: price.date.clone(),
+            close: price.close,
+            rsi: snapshot.rsi,
+            macd: snapshot.macd,
+            bollinger: snapshot.bollinger,
+            kdj: snapshot.kdj,
+            atr: snapshot.atr,
+            volume_ratio: snapshot.volume_ratio,
+            turnover_rate: float_shares
+                .filter(|shares| *shares > 0.0)
+                .map(|shares| price.volume as f64 / shares * 100.0),
+        }
+    }
+
+    /// 预警使用的指标取值，布林带宽与换手率为百分比
+    pub fn value(&self, indicator: AlertIndicator) -> Option<f64> {
+        match indicator {
+            AlertIndicator::KdjK => self.kdj.map(|p| p.k),
+            AlertIndicator::KdjD => self.kdj.map(|p| p.d),
+            AlertIndicator::KdjJ => self.kdj.map(|p| p.j),
+            AlertIndicator::BollWidth => self.bollinger.map(|b| b.width * 100.0),
+            AlertIndicator::VolumeRatio => self.volume_ratio,
+            AlertIndicator::TurnoverRate => self.turnover_rate,
+        }
+    }
+}
+
+/// cn_stocks 中的A股代码，上交所统一为 .SH 后缀
+pub fn cn_stocks_symbol(symbol: &str) -> String {
+    let symbol = symbol.to_uppercase();
+    match symbol.strip_suffix(".SS") {
+        Some(code) => format!("{code}.SH"),
+        None => symbol,
+    }
+}
+
 /// 移动平均数据
 #[derive(Debug, Clone)]
 pub struct MovingAverages {
@@ -88,16 +250,62 @@ pub struct LimitUpInfo {
 }
 
 impl StrategyAnalyzer {
-    pub fn new(db: SqlitePool) -> Self {
-        Self { db }
+    pub fn new(db: SqlitePool, indicators: IndicatorConfig) -> Self {
+        Self {
+            db,
+            indicators,
+            params: StrategyParams::default(),
+        }
     }
 
-    /// 分析指定股票的策略信号
-    pub async fn analyze_symbol(&self, symbol: &str) -> Result<Vec<StrategySignal>> {
-        info!("开始分析股票策略信号: {}", symbol);
+    /// 使用指定的形态策略参数（默认使用内置默认值）
+    pub fn with_params(mut self, params: StrategyParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// 股票生效的形态策略参数组
+    pub fn param_set(&self, symbol: &str) -> ParamSet {
+        self.params.resolve(symbol)
+    }
+
+    /// 按指定复权方式分析股票的策略信号
+    pub async fn analyze_symbol_with(
+        &self,
+        symbol: &str,
+        adjustment: PriceAdjustment,
+    ) -> Result<Vec<StrategySignal>> {
+        info!("开始分析股票策略信号: {} ({:?})", symbol, adjustment);
 
         // 获取历史价格数据
-        let prices = self.get_price_history(symbol, 250).await?;
+        let prices = self.get_price_history(symbol, 250, adjustment).await?;
+        self.analyze_prices(symbol, &prices).await
+    }
+
+    /// 分析前复权价格的策略信号，并据此计算最新交易日的综合评分
+    pub async fn analyze_and_score(
+        &self,
+        symbol: &str,
+        scoring: &ScoringConfig,
+    ) -> Result<(Vec<StrategySignal>, Option<CompositeScore>)> {
+        let prices = self
+            .get_price_history(symbol, 250, PriceAdjustment::Forward)
+            .await?;
+        let signals = self.analyze_prices(symbol, &prices).await?;
+        let score = if prices.len() < 60 {
+            None
+        } else {
+            composite_score(symbol, &prices, &signals, scoring)
+        };
+        Ok((signals, score))
+    }
+
+    /// 基于已加载的日线分析策略信号
+    async fn analyze_prices(
+        &self,
+        symbol: &str,
+        prices: &[PriceData],
+    ) -> Result<Vec<StrategySignal>> {
         if prices.len() < 60 {
             warn!("股票 {} 历史数据不足，无法进行策略分析", symbol);
             return Ok(vec![]);
@@ -106,17 +314,20 @@ impl StrategyAnalyzer {
         let mut signals = Vec::new();
 
         // 1. 涨停回踩分析
-        if let Some(signal) = self.analyze_limit_up_pullback(&prices).await? {
+        if let Some(signal) = self.analyze_limit_up_pullback(symbol, prices).await? {
             signals.push(StrategySignal::LimitUpPullback(signal));
         }
 
         // 2. 底部突破分析
-        if let Some(signal) = self.analyze_bottom_breakout(&prices).await? {
+        if let Some(signal) = self.analyze_bottom_breakout(symbol, prices).await? {
             signals.push(StrategySignal::BottomBreakout(signal));
         }
 
         // 3. 技术指标分析
-        let tech_signals = self.analyze_technical_indicators(&prices).await?;
+        let float_shares = self.float_shares(symbol).await?;
+        let tech_signals = self
+            .analyze_technical_indicators(symbol, prices, float_shares)
+            .await?;
         for signal in tech_signals {
             signals.push(StrategySignal::TechnicalIndicator(signal));
         }
@@ -125,15 +336,37 @@ impl StrategyAnalyzer {
         Ok(signals)
     }
 
-    /// 获取历史价格数据
-    async fn get_price_history(&self, symbol: &str, days: i32) -> Result<Vec<PriceData>> {
+    /// 获取最近 `days` 根日线，前复权时按公司行动调整除权日前的价格与成交量
+    ///
+    /// 同一天有多行价格时合并为一根日线：开盘取第一笔，最高/最低取极值，收盘、成交量与涨跌幅取最后一笔。
+    pub async fn get_price_history(
+        &self,
+        symbol: &str,
+        days: i32,
+        adjustment: PriceAdjustment,
+    ) -> Result<Vec<PriceData>> {
         let rows = sqlx::query!(
             r#"
-            SELECT date, open_price, high_price, low_price, close_price, 
-                   volume, daily_change_percent
-            FROM price_history 
-            WHERE symbol = ? 
-            ORDER BY date DESC 
+            SELECT date as "date!: NaiveDate",
+                   open_price as "open_price!: f64",
+                   high_price as "high_price!: f64",
+                   low_price as "low_price!: f64",
+                   close_price as "close_price!: f64",
+                   volume as "volume!: i64",
+                   daily_change_percent as "daily_change_percent: f64"
+            FROM (
+                SELECT date, close_price, volume, daily_change_percent,
+                       FIRST_VALUE(open_price) OVER day AS open_price,
+                       MAX(high_price) OVER day AS high_price,
+                       MIN(low_price) OVER day AS low_price,
+                       ROW_NUMBER() OVER (PARTITION BY date ORDER BY id DESC) AS latest
+                FROM price_history
+                WHERE symbol = ? AND data_quality = 'live'
+                WINDOW day AS (PARTITION BY date ORDER BY id
+                               ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING)
+            )
+            WHERE latest = 1
+            ORDER BY date DESC
             LIMIT ?
             "#,
             symbol,
@@ -157,24 +390,133 @@ impl StrategyAnalyzer {
 
         // 反转数组，让最新数据在最后
         prices.reverse();
+
+        if adjustment == PriceAdjustment::Forward {
+            let actions = CorporateActionService::new(self.db.clone())
+                .list(symbol)
+                .await?;
+            adjust_prices(&mut prices, &actions);
+        }
+
         Ok(prices)
     }
 
+    /// A股流通股本（股），未记录时为 None
+    pub async fn float_shares(&self, symbol: &str) -> Result<Option<f64>> {
+        let symbol = cn_stocks_symbol(symbol);
+        let float_shares = sqlx::query_scalar!(
+            "SELECT float_shares FROM cn_stocks WHERE symbol = ?",
+            symbol
+        )
+        .fetch_optional(&self.db)
+        .await?
+        .flatten();
+
+        Ok(float_shares)
+    }
+
+    /// 最近 `days` 个交易日的技术指标，额外读取更早的数据用于指标预热
+    pub async fn get_indicator_values(
+        &self,
+        symbol: &str,
+        days: i32,
+        adjustment: PriceAdjustment,
+        float_shares: Option<f64>,
+    ) -> Result<Vec<IndicatorValues>> {
+        let prices = self
+            .get_price_history(symbol, days.saturating_add(250), adjustment)
+            .await?;
+        let mut values = self.calculate_indicators(&prices, float_shares);
+        let skip = values.len().saturating_sub(days.max(0) as usize);
+        Ok(values.split_off(skip))
+    }
+
+    /// 最近 `count` 根指定周期日内K线的技术指标，额外读取更早的K线用于指标预热
+    ///
+    /// 日内K线的成交量是数据源报告的累计值，不计算量比与换手率。
+    pub async fn get_bar_indicator_values(
+        &self,
+        symbol: &str,
+        interval: BarInterval,
+        count: i32,
+    ) -> Result<Vec<IndicatorValues>> {
+        let bars = self
+            .get_bars(symbol, interval, count.saturating_add(250) as i64)
+            .await?;
+        let mut values = self.calculate_indicators(&bars, None);
+        for value in &mut values {
+            value.volume_ratio = None;
+        }
+        let skip = values.len().saturating_sub(count.max(0) as usize);
+        Ok(values.split_off(skip))
+    }
+
+    /// 按配置的周期计算每根K线的技术指标，换手率需要流通股本（股）
+    pub fn calculate_indicators(
+        &self,
+        prices: &[PriceData],
+        float_shares: Option<f64>,
+    ) -> Vec<IndicatorValues> {
+        let candles: Vec<Candle> = prices.iter().map(Candle::from).collect();
+        let snapshots = IndicatorSet::new(&self.indicators).batch(&candles);
+        prices
+            .iter()
+            .zip(snapshots)
+            .map(|(price, snapshot)| {
+                IndicatorValues::new(price, snapshot.unwrap_or_default(), float_shares)
+            })
+            .collect()
+    }
+
+    /// 获取最近 `count` 根指定周期的日内K线，日期为周期起始时间（UTC），涨跌幅相对上一根K线
+    pub async fn get_bars(
+        &self,
+        symbol: &str,
+        interval: BarInterval,
+        count: i64,
+    ) -> Result<Vec<PriceData>> {
+        let to = Utc::now().naive_utc();
+        let from = to - chrono::Duration::seconds(interval.seconds() * count);
+        let bars = IntradayStore::new(self.db.clone())
+            .bars(symbol, interval, from, to)
+            .await?;
+
+        let mut prev_close: Option<f64> = None;
+        Ok(bars
+            .into_iter()
+            .map(|bar| {
+                let change_percent = prev_close.map(|prev| (bar.close - prev) / prev * 100.0);
+                prev_close = Some(bar.close);
+                PriceData {
+                    date: bar.start.to_string(),
+                    open: bar.open,
+                    high: bar.high,
+                    low: bar.low,
+                    close: bar.close,
+                    volume: bar.volume.max(0) as u64,
+                    change_percent,
+                }
+            })
+            .collect())
+    }
+
     /// 涨停回踩分析
-    async fn analyze_limit_up_pullback(
+    pub(crate) async fn analyze_limit_up_pullback(
         &self,
+        symbol: &str,
         prices: &[PriceData],
     ) -> Result<Option<LimitUpPullbackSignal>> {
-        if prices.len() < 10 {
+        let params = self.params.resolve(symbol).params;
+        if prices.len() < params.limit_up_lookback_days.max(2) {
             return Ok(None);
         }
 
-        // 1. 寻找近10天内的涨停
+        // 1. 寻找最近 limit_up_lookback_days 根K线内的涨停
         let mut limit_up_info = None;
-        for (_i, price) in prices.iter().enumerate().rev().take(10) {
+        for price in prices.iter().rev().take(params.limit_up_lookback_days) {
             if let Some(change_percent) = price.change_percent {
-                // A股涨停阈值 9.8%，美股可以设置为自定义阈值
-                if change_percent >= 9.8 {
+                // 涨停阈值按市场与板块配置，如主板 9.8%、创业板 19.8%
+                if change_percent >= params.limit_up_percent {
                     limit_up_info = Some(LimitUpInfo {
                         date: price.date.clone(),
                         price: price.high,
@@ -205,7 +547,7 @@ impl StrategyAnalyzer {
 
         // 3. 检查缩量
         let volume_shrink_ratio = current_price.volume as f64 / limit_up.volume as f64;
-        if volume_shrink_ratio > 0.5 {
+        if volume_shrink_ratio > params.pullback_volume_ratio {
             return Ok(None); // 没有明显缩量
         }
 
@@ -219,7 +561,7 @@ impl StrategyAnalyzer {
 
         // 5. 计算信号强度
         let mut strength = 3u8; // 基础强度
-        if volume_shrink_ratio < 0.3 {
+        if volume_shrink_ratio < params.deep_shrink_ratio {
             strength += 1;
         } // 深度缩量加分
         if current_price.close > support_level {
@@ -227,7 +569,7 @@ impl StrategyAnalyzer {
         } // 仍在支撑位上方加分
 
         let signal = LimitUpPullbackSignal {
-            symbol: prices[0].date.clone(), // 这里应该传入symbol，临时用date代替
+            symbol: symbol.to_string(),
             signal_strength: strength.min(5),
             limit_up_date: limit_up.date.clone(),
             limit_up_price: limit_up.price,
@@ -248,14 +590,16 @@ impl StrategyAnalyzer {
     }
 
     /// 底部突破分析
-    async fn analyze_bottom_breakout(
+    pub(crate) async fn analyze_bottom_breakout(
         &self,
+        symbol: &str,
         prices: &[PriceData],
     ) -> Result<Option<BottomBreakoutSignal>> {
         if prices.len() < 120 {
             return Ok(None);
         }
 
+        let params = self.params.resolve(symbol).params;
         let current = prices.last().unwrap();
         let mas = self.calculate_moving_averages(prices);
 
@@ -267,38 +611,38 @@ impl StrategyAnalyzer {
             return Ok(None);
         }
 
-        let close_to_ma120 = (current.close - ma120).abs() / ma120 < 0.15;
-        let close_to_ma250 = (current.close - ma250).abs() / ma250 < 0.15;
+        let proximity = params.ma_proximity_percent / 100.0;
+        let close_to_ma120 = (current.close - ma120).abs() / ma120 < proximity;
+        let close_to_ma250 = (current.close - ma250).abs() / ma250 < proximity;
 
         if !close_to_ma120 || !close_to_ma250 {
             return Ok(None);
         }
 
         // 2. 检查放量突破
-        let avg_volume = self.calculate_average_volume(prices, 30);
+        let avg_volume = self.calculate_average_volume(prices, params.volume_average_days);
         let volume_ratio = current.volume as f64 / avg_volume;
 
-        if volume_ratio < 1.5 {
+        if volume_ratio < params.breakout_volume_ratio {
             return Ok(None); // 没有明显放量
         }
 
-        // 3. 计算阻力位（60日高点）
-        let resistance_level = prices
+        // 3. 计算阻力位（当前K线之前 resistance_lookback_days 根的高点）
+        let resistance_level = prices[..prices.len() - 1]
             .iter()
             .rev()
-            .take(60)
+            .take(params.resistance_lookback_days)
             .map(|p| p.high)
             .fold(0.0, f64::max);
 
         // 4. 检查是否突破阻力位
-        if current.high <= resistance_level * 1.02 {
-            // 需要突破2%以上
+        if current.high <= resistance_level * (1.0 + params.breakout_percent / 100.0) {
             return Ok(None);
         }
 
         // 5. 计算信号强度
         let mut strength = 3u8;
-        if volume_ratio > 2.0 {
+        if volume_ratio > params.strong_volume_ratio {
             strength += 1;
         }
         if current.close > resistance_level {
@@ -306,7 +650,7 @@ impl StrategyAnalyzer {
         }
 
         let signal = BottomBreakoutSignal {
-            symbol: current.date.clone(), // 临时用date代替symbol
+            symbol: symbol.to_string(),
             signal_strength: strength.min(5),
             breakout_price: current.close,
             volume_ratio,
@@ -325,7 +669,9 @@ impl StrategyAnalyzer {
     /// 技术指标分析
     async fn analyze_technical_indicators(
         &self,
+        symbol: &str,
         prices: &[PriceData],
+        float_shares: Option<f64>,
     ) -> Result<Vec<TechnicalSignal>> {
         let mut signals = Vec::new();
         let mas = self.calculate_moving_averages(prices);
@@ -335,7 +681,8 @@ impl StrategyAnalyzer {
         if let (Some(ma5), Some(ma10), Some(ma20)) = (mas.ma5, mas.ma10, mas.ma20) {
             if ma5 > ma10 && ma10 > ma20 && current.close > ma5 {
                 signals.push(TechnicalSignal {
-                    symbol: current.date.clone(),
+                    symbol: symbol.to_string(),
+                    price: current.close,
                     indicator_name: "均线多头".to_string(),
                     value: (current.close - ma20) / ma20 * 100.0,
                     signal_strength: 4,
@@ -350,7 +697,8 @@ impl StrategyAnalyzer {
 
         if volume_ratio > 1.5 && current.change_percent.unwrap_or(0.0) > 2.0 {
             signals.push(TechnicalSignal {
-                symbol: current.date.clone(),
+                symbol: symbol.to_string(),
+                price: current.close,
                 indicator_name: "放量上涨".to_string(),
                 value: volume_ratio,
                 signal_strength: 3,
@@ -362,97 +710,662 @@ impl StrategyAnalyzer {
             });
         }
 
+        // 3. RSI、MACD、布林带
+        let values = self.calculate_indicators(prices, float_shares);
+        signals.extend(self.rsi_signal(symbol, &values));
+        signals.extend(self.macd_cross_signal(symbol, &values));
+        signals.extend(self.macd_divergence_signal(symbol, &values));
+        signals.extend(self.bollinger_signals(symbol, &values));
+
+        // 4. KDJ、量比、换手率
+        signals.extend(self.kdj_signals(symbol, &values));
+        signals.extend(self.volume_ratio_signal(symbol, &values));
+        signals.extend(self.turnover_signal(symbol, &values));
+
         Ok(signals)
     }
 
-    /// 计算移动平均线
-    fn calculate_moving_averages(&self, prices: &[PriceData]) -> MovingAverages {
-        MovingAverages {
-            ma5: self.calculate_sma(prices, 5),
-            ma10: self.calculate_sma(prices, 10),
-            ma20: self.calculate_sma(prices, 20),
-            ma60: self.calculate_sma(prices, 60),
-            ma120: self.calculate_sma(prices, 120),
-            ma250: self.calculate_sma(prices, 250),
-        }
+    /// RSI 超买超卖
+    fn rsi_signal(&self, symbol: &str, values: &[IndicatorValues]) -> Option<TechnicalSignal> {
+        let params = &self.indicators;
+        let current = values.last()?;
+        let rsi = current.rsi?;
+
+        let (name, extreme, description) = if rsi >= params.rsi_overbought {
+            (
+                "RSI超买",
+                rsi >= (params.rsi_overbought + 100.0) / 2.0,
+                format!(
+                    "RSI({})为{:.1}，高于超买线{:.0}，注意回调风险",
+                    params.rsi_period, rsi, params.rsi_overbought
+                ),
+            )
+        } else if rsi <= params.rsi_oversold {
+            (
+                "RSI超卖",
+                rsi <= params.rsi_oversold / 2.0,
+                format!(
+                    "RSI({})为{:.1}，低于超卖线{:.0}，可能出现反弹",
+                    params.rsi_period, rsi, params.rsi_oversold
+                ),
+            )
+        } else {
+            return None;
+        };
+
+        Some(TechnicalSignal {
+            symbol: symbol.to_string(),
+            price: current.close,
+            indicator_name: name.to_string(),
+            value: rsi,
+            signal_strength: if extreme { 4 } else { 3 },
+            description,
+        })
     }
 
-    /// 计算简单移动平均
-    fn calculate_sma(&self, prices: &[PriceData], period: usize) -> Option<f64> {
-        if prices.len() < period {
+    /// MACD 金叉死叉：最新一根K线 DIF 穿越 DEA，零轴上方金叉、零轴下方死叉更强
+    fn macd_cross_signal(
+        &self,
+        symbol: &str,
+        values: &[IndicatorValues],
+    ) -> Option<TechnicalSignal> {
+        let [prev, current] = values.get(values.len().checked_sub(2)?..)? else {
+            return None;
+        };
+        let (prev_macd, macd) = (prev.macd?, current.macd?);
+
+        let (name, strong, position) = if prev_macd.dif <= prev_macd.dea && macd.dif > macd.dea {
+            ("MACD金叉", macd.dif > 0.0, "上穿")
+        } else if prev_macd.dif >= prev_macd.dea && macd.dif < macd.dea {
+            ("MACD死叉", macd.dif < 0.0, "下穿")
+        } else {
+            return None;
+        };
+
+        Some(TechnicalSignal {
+            symbol: symbol.to_string(),
+            price: current.close,
+            indicator_name: name.to_string(),
+            value: macd.dif,
+            signal_strength: if strong { 4 } else { 3 },
+            description: format!(
+                "DIF({:.3}){}DEA({:.3})，位于零轴{}",
+                macd.dif,
+                position,
+                macd.dea,
+                if macd.dif >= 0.0 { "上方" } else { "下方" }
+            ),
+        })
+    }
+
+    /// MACD 背离：比较最近两段K线，价格创新高而 DIF 高点降低为顶背离，反之为底背离
+    fn macd_divergence_signal(
+        &self,
+        symbol: &str,
+        values: &[IndicatorValues],
+    ) -> Option<TechnicalSignal> {
+        let lookback = self.indicators.macd_divergence_lookback;
+        let window = values.get(values.len().checked_sub(lookback * 2)?..)?;
+        if lookback == 0 || window.iter().any(|v| v.macd.is_none()) {
             return None;
         }
+        let (previous, recent) = window.split_at(lookback);
+
+        let max = |vs: &[IndicatorValues], f: fn(&IndicatorValues) -> f64| {
+            vs.iter().map(f).fold(f64::MIN, f64::max)
+        };
+        let min = |vs: &[IndicatorValues], f: fn(&IndicatorValues) -> f64| {
+            vs.iter().map(f).fold(f64::MAX, f64::min)
+        };
+        let close = |v: &IndicatorValues| v.close;
+        let dif = |v: &IndicatorValues| v.macd.map_or(0.0, |m| m.dif);
+        let current = recent.last()?;
 
-        let sum: f64 = prices.iter().rev().take(period).map(|p| p.close).sum();
+        let (name, description) = if max(recent, close) > max(previous, close)
+            && max(recent, dif) < max(previous, dif)
+        {
+            (
+                "MACD顶背离",
+                format!(
+                    "近{}根K线价格创新高{:.2}，DIF高点由{:.3}降至{:.3}，上涨动能减弱",
+                    lookback,
+                    max(recent, close),
+                    max(previous, dif),
+                    max(recent, dif)
+                ),
+            )
+        } else if min(recent, close) < min(previous, close) && min(recent, dif) > min(previous, dif)
+        {
+            (
+                "MACD底背离",
+                format!(
+                    "近{}根K线价格创新低{:.2}，DIF低点由{:.3}升至{:.3}，下跌动能减弱",
+                    lookback,
+                    min(recent, close),
+                    min(previous, dif),
+                    min(recent, dif)
+                ),
+            )
+        } else {
+            return None;
+        };
 
-        Some(sum / period as f64)
+        Some(TechnicalSignal {
+            symbol: symbol.to_string(),
+            price: current.close,
+            indicator_name: name.to_string(),
+            value: dif(current),
+            signal_strength: 3,
+            description,
+        })
     }
 
-    /// 计算平均成交量
-    fn calculate_average_volume(&self, prices: &[PriceData], period: usize) -> f64 {
-        if prices.len() < period {
-            return prices.iter().map(|p| p.volume as f64).sum::<f64>() / prices.len() as f64;
+    /// 布林带收口与突破
+    fn bollinger_signals(&self, symbol: &str, values: &[IndicatorValues]) -> Vec<TechnicalSignal> {
+        let mut signals = Vec::new();
+        let Some(current) = values.last() else {
+            return signals;
+        };
+        let Some(band) = current.bollinger else {
+            return signals;
+        };
+
+        // 带宽降到最近 boll_squeeze_lookback 根K线的最低值
+        let lookback = self.indicators.boll_squeeze_lookback;
+        let last = values.len() - 1;
+        if self.is_bollinger_squeeze(values, last) {
+            signals.push(TechnicalSignal {
+                symbol: symbol.to_string(),
+                price: current.close,
+                indicator_name: "布林收口".to_string(),
+                value: band.width * 100.0,
+                signal_strength: 2,
+                description: format!(
+                    "布林带宽{:.2}%，为近{}根K线最窄，可能即将选择方向",
+                    band.width * 100.0,
+                    lookback
+                ),
+            });
         }
 
-        let sum: f64 = prices
-            .iter()
+        // 收口后5根K线内带宽首次扩大到 boll_expand_ratio 倍
+        let ratio = self.indicators.boll_expand_ratio;
+        let squeeze = (last.saturating_sub(5)..last)
             .rev()
-            .take(period)
-            .map(|p| p.volume as f64)
-            .sum();
+            .find(|&i| self.is_bollinger_squeeze(values, i))
+            .and_then(|i| values[i].bollinger);
+        let previous_width = last
+            .checked_sub(1)
+            .and_then(|i| values[i].bollinger)
+            .map(|b| b.width);
+        if let (Some(squeeze), Some(previous_width)) = (squeeze, previous_width) {
+            let threshold = squeeze.width * ratio;
+            if ratio > 1.0 && band.width >= threshold && previous_width < threshold {
+                let upward = current.close >= band.middle;
+                signals.push(TechnicalSignal {
+                    symbol: symbol.to_string(),
+                    price: current.close,
+                    indicator_name: if upward {
+                        "布林向上开口"
+                    } else {
+                        "布林向下开口"
+                    }
+                    .to_string(),
+                    value: band.width * 100.0,
+                    signal_strength: 3,
+                    description: format!(
+                        "布林带宽由收口时的{:.2}%扩大至{:.2}%，收盘价位于中轨{}，{}",
+                        squeeze.width * 100.0,
+                        band.width * 100.0,
+                        if upward { "上方" } else { "下方" },
+                        if upward {
+                            "可能开启上涨行情"
+                        } else {
+                            "可能开启下跌行情"
+                        }
+                    ),
+                });
+            }
+        }
+
+        if current.close > band.upper {
+            signals.push(TechnicalSignal {
+                symbol: symbol.to_string(),
+                price: current.close,
+                indicator_name: "布林上轨突破".to_string(),
+                value: current.close,
+                signal_strength: 3,
+                description: format!("收盘价{:.2}突破布林上轨{:.2}", current.close, band.upper),
+            });
+        } else if current.close < band.lower {
+            signals.push(TechnicalSignal {
+                symbol: symbol.to_string(),
+                price: current.close,
+                indicator_name: "布林下轨跌破".to_string(),
+                value: current.close,
+                signal_strength: 3,
+                description: format!("收盘价{:.2}跌破布林下轨{:.2}", current.close, band.lower),
+            });
+        }
 
-        sum / period as f64
+        signals
     }
 
-    /// 保存策略信号到数据库
-    pub async fn save_signal(&self, symbol: &str, signal: &StrategySignal) -> Result<()> {
-        let (strategy_type, signal_strength, description, key_levels_json) = match signal {
-            StrategySignal::LimitUpPullback(s) => (
-                "limit_up_pullback",
-                s.signal_strength,
-                s.description.clone(),
-                serde_json::to_string(&s.key_levels)?,
-            ),
-            StrategySignal::BottomBreakout(s) => (
-                "bottom_breakout",
-                s.signal_strength,
-                s.description.clone(),
-                serde_json::to_string(&s.key_levels)?,
-            ),
-            StrategySignal::TechnicalIndicator(s) => (
-                "technical_indicator",
-                s.signal_strength,
-                s.description.clone(),
-                "[]".to_string(),
+    /// 第 `index` 根K线的布林带宽是否为最近 boll_squeeze_lookback 根K线的最低值
+    fn is_bollinger_squeeze(&self, values: &[IndicatorValues], index: usize) -> bool {
+        let lookback = self.indicators.boll_squeeze_lookback;
+        if lookback < 2 || index + 1 < lookback {
+            return false;
+        }
+        let Some(width) = values[index].bollinger.map(|b| b.width) else {
+            return false;
+        };
+        values[index + 1 - lookback..=index]
+            .iter()
+            .all(|v| v.bollinger.is_some_and(|b| width <= b.width))
+    }
+
+    /// KDJ 金叉死叉与 J 值超买超卖
+    fn kdj_signals(&self, symbol: &str, values: &[IndicatorValues]) -> Vec<TechnicalSignal> {
+        let params = &self.indicators;
+        let mut signals = Vec::new();
+        let [.., prev, current] = values else {
+            return signals;
+        };
+        let (Some(prev_kdj), Some(kdj)) = (prev.kdj, current.kdj) else {
+            return signals;
+        };
+
+        if prev_kdj.k <= prev_kdj.d && kdj.k > kdj.d {
+            let low = kdj.d < params.kdj_oversold;
+            signals.push(TechnicalSignal {
+                symbol: symbol.to_string(),
+                price: current.close,
+                indicator_name: "KDJ金叉".to_string(),
+                value: kdj.k,
+                signal_strength: if low { 4 } else { 3 },
+                description: format!(
+                    "K值{:.1}上穿D值{:.1}{}",
+                    kdj.k,
+                    kdj.d,
+                    if low { "，低位金叉" } else { "" }
+                ),
+            });
+        } else if prev_kdj.k >= prev_kdj.d && kdj.k < kdj.d {
+            let high = kdj.d > params.kdj_overbought;
+            signals.push(TechnicalSignal {
+                symbol: symbol.to_string(),
+                price: current.close,
+                indicator_name: "KDJ死叉".to_string(),
+                value: kdj.k,
+                signal_strength: if high { 4 } else { 3 },
+                description: format!(
+                    "K值{:.1}下穿D值{:.1}{}",
+                    kdj.k,
+                    kdj.d,
+                    if high { "，高位死叉" } else { "" }
+                ),
+            });
+        }
+
+        if kdj.j > 100.0 {
+            signals.push(TechnicalSignal {
+                symbol: symbol.to_string(),
+                price: current.close,
+                indicator_name: "KDJ超买".to_string(),
+                value: kdj.j,
+                signal_strength: 3,
+                description: format!("J值{:.1}超过100，短线超买，注意回调风险", kdj.j),
+            });
+        } else if kdj.j < 0.0 {
+            signals.push(TechnicalSignal {
+                symbol: symbol.to_string(),
+                price: current.close,
+                indicator_name: "KDJ超卖".to_string(),
+                value: kdj.j,
+                signal_strength: 3,
+                description: format!("J值{:.1}低于0，短线超卖，可能出现反弹", kdj.j),
+            });
+        }
+
+        signals
+    }
+
+    /// 量比放大或萎缩
+    fn volume_ratio_signal(
+        &self,
+        symbol: &str,
+        values: &[IndicatorValues],
+    ) -> Option<TechnicalSignal> {
+        let params = &self.indicators;
+        let current = values.last()?;
+        let ratio = current.volume_ratio?;
+
+        let (name, strength, description) = if ratio >= params.volume_ratio_high {
+            (
+                "量比放大",
+                if ratio >= params.volume_ratio_high * 2.0 {
+                    4
+                } else {
+                    3
+                },
+                format!(
+                    "量比{:.2}，成交较前{}日明显放大",
+                    ratio, params.volume_ratio_period
+                ),
+            )
+        } else if ratio <= params.volume_ratio_low {
+            (
+                "量比萎缩",
+                2,
+                format!(
+                    "量比{:.2}，成交较前{}日明显萎缩",
+                    ratio, params.volume_ratio_period
+                ),
+            )
+        } else {
+            return None;
+        };
+
+        Some(TechnicalSignal {
+            symbol: symbol.to_string(),
+            price: current.close,
+            indicator_name: name.to_string(),
+            value: ratio,
+            signal_strength: strength,
+            description,
+        })
+    }
+
+    /// 换手率活跃或清淡
+    fn turnover_signal(&self, symbol: &str, values: &[IndicatorValues]) -> Option<TechnicalSignal> {
+        let params = &self.indicators;
+        let current = values.last()?;
+        let turnover = current.turnover_rate?;
+
+        let (name, strength, description) = if turnover >= params.turnover_high {
+            (
+                "高换手",
+                if turnover >= params.turnover_high * 2.0 {
+                    4
+                } else {
+                    3
+                },
+                format!("换手率{:.2}%，交投活跃", turnover),
+            )
+        } else if turnover <= params.turnover_low {
+            ("低换手", 2, format!("换手率{:.2}%，交投清淡", turnover))
+        } else {
+            return None;
+        };
+
+        Some(TechnicalSignal {
+            symbol: symbol.to_string(),
+            price: current.close,
+            indicator_name: name.to_string(),
+            value: turnover,
+            signal_strength: strength,
+            description,
+        })
+    }
+
+    /// 计算移动平均线
+    fn calculate_moving_averages(&self, prices: &[PriceData]) -> MovingAverages {
+        let closes: Vec<f64> = prices.iter().map(|p| p.close).collect();
+        let sma = |period| Sma::new(period).last(&closes);
+        MovingAverages {
+            ma5: sma(5),
+            ma10: sma(10),
+            ma20: sma(20),
+            ma60: sma(60),
+            ma120: sma(120),
+            ma250: sma(250),
+        }
+    }
+
+    /// 计算平均成交量，数据不足 `period` 根时取全部数据的平均
+    fn calculate_average_volume(&self, prices: &[PriceData], period: usize) -> f64 {
+        let volumes: Vec<f64> = prices.iter().map(|p| p.volume as f64).collect();
+        Sma::new(period.min(volumes.len()))
+            .last(&volumes)
+            .unwrap_or(0.0)
+    }
+
+    /// 保存策略信号，同一股票同类信号（技术指标按信号名称区分）之前的记录置为失效
+    pub async fn save_signal(&self, signal: &StrategySignal) -> Result<i64> {
+        let strategy_type = signal.strategy_type();
+        let symbol = signal.symbol();
+        let signal_strength = signal.signal_strength() as i64;
+        let trigger_price = signal.trigger_price();
+        let description = signal.description();
+        let created_at = Utc::now().naive_utc();
+        let expires_at = created_at + chrono::Duration::days(signal.valid_days());
+
+        let mut limit_up_date = None;
+        let mut limit_up_price = None;
+        let mut support_level = None;
+        let mut resistance_level = None;
+        let mut ma_position = None;
+        let mut indicator_name = None;
+        let mut indicator_value = None;
+        let (volume_ratio, key_levels) = match signal {
+            StrategySignal::LimitUpPullback(s) => {
+                limit_up_date = Some(s.limit_up_date.clone());
+                limit_up_price = Some(s.limit_up_price);
+                support_level = Some(s.support_level);
+                (
+                    Some(s.volume_shrink_ratio),
+                    serde_json::to_string(&s.key_levels)?,
+                )
+            }
+            StrategySignal::BottomBreakout(s) => {
+                resistance_level = Some(s.resistance_level);
+                ma_position = Some(s.ma_position);
+                (Some(s.volume_ratio), serde_json::to_string(&s.key_levels)?)
+            }
+            StrategySignal::TechnicalIndicator(s) => {
+                indicator_name = Some(s.indicator_name.clone());
+                indicator_value = Some(s.value);
+                (None, "[]".to_string())
+            }
+        };
+
+        // 形态信号记录按市场与板块生效的参数，技术指标信号记录指标参数
+        let (param_set, params) = match signal {
+            StrategySignal::TechnicalIndicator(_) => (
+                "indicators".to_string(),
+                serde_json::to_string(&self.indicators)?,
             ),
+            _ => {
+                let set = self.param_set(symbol);
+                (set.name, serde_json::to_string(&set.params)?)
+            }
         };
 
+        let mut tx = self.db.begin().await?;
         sqlx::query!(
             r#"
-            INSERT INTO strategy_signals 
-            (symbol, strategy_type, signal_strength, trigger_price, key_levels, description)
-            VALUES (?, ?, ?, ?, ?, ?)
+            UPDATE strategy_signals SET is_active = 0
+            WHERE symbol = ? AND strategy_type = ? AND indicator_name IS ? AND is_active = 1
+            "#,
+            symbol,
+            strategy_type,
+            indicator_name
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let id = sqlx::query!(
+            r#"
+            INSERT INTO strategy_signals
+            (symbol, strategy_type, signal_strength, trigger_price, key_levels, description,
+             created_at, expires_at, is_active, limit_up_date, limit_up_price, volume_ratio,
+             support_level, resistance_level, ma_position, indicator_name, indicator_value,
+             param_set, params)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 1, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             symbol,
             strategy_type,
             signal_strength,
-            0.0, // trigger_price - 需要根据具体信号类型设置
-            key_levels_json,
-            description
+            trigger_price,
+            key_levels,
+            description,
+            created_at,
+            expires_at,
+            limit_up_date,
+            limit_up_price,
+            volume_ratio,
+            support_level,
+            resistance_level,
+            ma_position,
+            indicator_name,
+            indicator_value,
+            param_set,
+            params
         )
-        .execute(&self.db)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+        tx.commit().await?;
+
+        Ok(id)
+    }
+
+    /// 是否已有相同的有效信号：同一股票、策略类型、指标名称与涨停日期
+    pub async fn is_duplicate_signal(&self, signal: &StrategySignal) -> Result<bool> {
+        let strategy_type = signal.strategy_type();
+        let symbol = signal.symbol();
+        let (indicator_name, limit_up_date) = match signal {
+            StrategySignal::LimitUpPullback(s) => (None, Some(s.limit_up_date.as_str())),
+            StrategySignal::BottomBreakout(_) => (None, None),
+            StrategySignal::TechnicalIndicator(s) => (Some(s.indicator_name.as_str()), None),
+        };
+        let now = Utc::now().naive_utc();
+
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!: i64" FROM strategy_signals
+            WHERE symbol = ? AND strategy_type = ? AND indicator_name IS ?
+              AND limit_up_date IS ? AND is_active = 1 AND expires_at > ?
+            "#,
+            symbol,
+            strategy_type,
+            indicator_name,
+            limit_up_date,
+            now
+        )
+        .fetch_one(&self.db)
         .await?;
 
+        Ok(count > 0)
+    }
+
+    /// 保存综合评分，每只股票每个交易日一行，重复计算时覆盖
+    pub async fn save_analysis(&self, score: &CompositeScore) -> Result<()> {
+        let key_points = serde_json::to_string(&score.key_points)?;
+        let created_at = Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+            INSERT INTO strategy_analysis
+            (symbol, analysis_date, total_signals, avg_signal_strength, composite_score,
+             risk_level, recommendation, key_points, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(symbol, analysis_date) DO UPDATE SET
+                total_signals = excluded.total_signals,
+                avg_signal_strength = excluded.avg_signal_strength,
+                composite_score = excluded.composite_score,
+                risk_level = excluded.risk_level,
+                recommendation = excluded.recommendation,
+                key_points = excluded.key_points,
+                created_at = excluded.created_at
+            "#,
+            score.symbol,
+            score.analysis_date,
+            score.total_signals,
+            score.avg_signal_strength,
+            score.composite_score,
+            score.risk_level,
+            score.recommendation,
+            key_points,
+            created_at
+        )
+        .execute(&self.db)
+        .await?;
         Ok(())
     }
 
-    /// 获取最新的策略信号
-    pub async fn get_recent_signals(&self, limit: i32) -> Result<Vec<(String, StrategySignal)>> {
+    /// 按综合评分从高到低排列；不指定日期时取每只股票最新一次评分
+    pub async fn ranked_analyses(
+        &self,
+        date: Option<NaiveDate>,
+        recommendation: Option<Recommendation>,
+    ) -> Result<Vec<CompositeScore>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT symbol, analysis_date as "analysis_date: NaiveDate",
+                   total_signals as "total_signals!", avg_signal_strength as "avg_signal_strength!",
+                   composite_score as "composite_score!",
+                   risk_level as "risk_level!: RiskLevel",
+                   recommendation as "recommendation!: Recommendation",
+                   key_points
+            FROM strategy_analysis a
+            WHERE risk_level IS NOT NULL AND recommendation IS NOT NULL
+              AND (analysis_date = ? OR (? IS NULL AND analysis_date = (
+                    SELECT MAX(b.analysis_date) FROM strategy_analysis b WHERE b.symbol = a.symbol)))
+              AND (? IS NULL OR recommendation = ?)
+            ORDER BY composite_score DESC, symbol
+            "#,
+            date,
+            date,
+            recommendation,
+            recommendation
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| CompositeScore {
+                symbol: row.symbol,
+                analysis_date: row.analysis_date,
+                total_signals: row.total_signals,
+                avg_signal_strength: row.avg_signal_strength,
+                composite_score: row.composite_score,
+                risk_level: row.risk_level,
+                recommendation: row.recommendation,
+                key_points: row
+                    .key_points
+                    .and_then(|points| serde_json::from_str(&points).ok())
+                    .unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    /// 将已过有效期的信号置为失效
+    pub async fn expire_signals(&self) -> Result<u64> {
+        let now = Utc::now().naive_utc();
+        let result = sqlx::query!(
+            "UPDATE strategy_signals SET is_active = 0 WHERE is_active = 1 AND expires_at <= ?",
+            now
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 获取最新的有效策略信号，按保存时的类型还原
+    pub async fn get_recent_signals(&self, limit: i32) -> Result<Vec<StoredSignal>> {
+        self.expire_signals().await?;
+
         let rows = sqlx::query!(
             r#"
-            SELECT symbol, strategy_type, signal_strength, description, key_levels, created_at
-            FROM strategy_signals 
-            ORDER BY created_at DESC 
+            SELECT id as "id!", symbol, strategy_type, signal_strength, trigger_price, key_levels,
+                   description, created_at, expires_at, is_active, limit_up_date,
+                   limit_up_price, volume_ratio, support_level, resistance_level,
+                   ma_position, indicator_name, indicator_value, param_set, params
+            FROM strategy_signals
+            WHERE is_active = 1
+            ORDER BY created_at DESC, id DESC
             LIMIT ?
             "#,
             limit
@@ -462,19 +1375,476 @@ impl StrategyAnalyzer {
 
         let mut signals = Vec::new();
         for row in rows {
-            // 这里需要根据数据库记录重构信号对象
-            // 为简化实现，先返回技术指标信号
-            let signal = StrategySignal::TechnicalIndicator(TechnicalSignal {
-                symbol: row.symbol.clone(),
-                indicator_name: row.strategy_type,
-                value: row.signal_strength as f64,
-                signal_strength: row.signal_strength as u8,
-                description: row.description.unwrap_or_default(),
-            });
+            let symbol = row.symbol.clone();
+            let signal_strength = row.signal_strength.clamp(0, u8::MAX as i64) as u8;
+            let description = row.description.unwrap_or_default();
+            let key_levels: Vec<f64> = row
+                .key_levels
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok())
+                .unwrap_or_default();
+
+            let signal = match row.strategy_type.as_str() {
+                "limit_up_pullback" => match (
+                    row.limit_up_date,
+                    row.limit_up_price,
+                    row.volume_ratio,
+                    row.support_level,
+                ) {
+                    (Some(limit_up_date), Some(limit_up_price), Some(ratio), Some(support)) => {
+                        Some(StrategySignal::LimitUpPullback(LimitUpPullbackSignal {
+                            symbol: symbol.clone(),
+                            signal_strength,
+                            limit_up_date,
+                            limit_up_price,
+                            pullback_price: row.trigger_price,
+                            volume_shrink_ratio: ratio,
+                            support_level: support,
+                            description,
+                            key_levels,
+                        }))
+                    }
+                    _ => None,
+                },
+                "bottom_breakout" => {
+                    match (row.volume_ratio, row.ma_position, row.resistance_level) {
+                        (Some(volume_ratio), Some(ma_position), Some(resistance_level)) => {
+                            Some(StrategySignal::BottomBreakout(BottomBreakoutSignal {
+                                symbol: symbol.clone(),
+                                signal_strength,
+                                breakout_price: row.trigger_price,
+                                volume_ratio,
+                                ma_position,
+                                resistance_level,
+                                description,
+                                key_levels,
+                            }))
+                        }
+                        _ => None,
+                    }
+                }
+                "technical_indicator" => match (row.indicator_name, row.indicator_value) {
+                    (Some(indicator_name), Some(value)) => {
+                        Some(StrategySignal::TechnicalIndicator(TechnicalSignal {
+                            symbol: symbol.clone(),
+                            indicator_name,
+                            value,
+                            price: row.trigger_price,
+                            signal_strength,
+                            description,
+                        }))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
 
-            signals.push((row.symbol, signal));
+            match signal {
+                Some(signal) => signals.push(StoredSignal {
+                    id: row.id,
+                    symbol,
+                    signal,
+                    param_set: row.param_set,
+                    params: row
+                        .params
+                        .and_then(|params| serde_json::from_str(&params).ok()),
+                    created_at: row.created_at,
+                    expires_at: row.expires_at,
+                    is_active: row.is_active.unwrap_or(true),
+                }),
+                None => warn!(
+                    "策略信号 {} ({} {}) 缺少还原所需的字段，已跳过",
+                    row.id, symbol, row.strategy_type
+                ),
+            }
         }
 
         Ok(signals)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    async fn analyzer() -> StrategyAnalyzer {
+        // 内存数据库每个连接相互独立，测试只使用一个连接
+        let db = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&db).await.unwrap();
+        StrategyAnalyzer::new(db, IndicatorConfig::default())
+    }
+
+    fn technical(name: &str, value: f64) -> StrategySignal {
+        StrategySignal::TechnicalIndicator(TechnicalSignal {
+            symbol: "600000.SH".to_string(),
+            indicator_name: name.to_string(),
+            value,
+            price: 10.5,
+            signal_strength: 3,
+            description: format!("{name} {value}"),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_signal_round_trip_and_expiry() {
+        let analyzer = analyzer().await;
+        let pullback = StrategySignal::LimitUpPullback(LimitUpPullbackSignal {
+            symbol: "000725.SZ".to_string(),
+            signal_strength: 4,
+            limit_up_date: "2025-07-01".to_string(),
+            limit_up_price: 4.4,
+            pullback_price: 4.15,
+            volume_shrink_ratio: 0.35,
+            support_level: 4.05,
+            description: "涨停回踩".to_string(),
+            key_levels: vec![4.05, 4.0, 4.4],
+        });
+        let breakout = StrategySignal::BottomBreakout(BottomBreakoutSignal {
+            symbol: "AAPL".to_string(),
+            signal_strength: 5,
+            breakout_price: 210.0,
+            volume_ratio: 2.3,
+            ma_position: 4.2,
+            resistance_level: 205.0,
+            description: "底部突破".to_string(),
+            key_levels: vec![190.0, 200.0, 205.0],
+        });
+        analyzer.save_signal(&pullback).await.unwrap();
+        analyzer.save_signal(&breakout).await.unwrap();
+        // 同名技术信号只保留最新一条，不同名互不影响
+        analyzer
+            .save_signal(&technical("RSI超卖", 25.0))
+            .await
+            .unwrap();
+        analyzer
+            .save_signal(&technical("KDJ金叉", 30.0))
+            .await
+            .unwrap();
+        analyzer
+            .save_signal(&technical("RSI超卖", 22.0))
+            .await
+            .unwrap();
+
+        let stored = analyzer.get_recent_signals(10).await.unwrap();
+        assert_eq!(stored.len(), 4);
+        assert!(stored
+            .iter()
+            .all(|s| s.is_active && s.expires_at > s.created_at));
+
+        let find = |strategy_type: &str| {
+            stored
+                .iter()
+                .find(|s| s.signal.strategy_type() == strategy_type)
+                .unwrap()
+        };
+        let StrategySignal::LimitUpPullback(s) = &find("limit_up_pullback").signal else {
+            panic!("expected limit-up pullback");
+        };
+        assert_eq!(find("limit_up_pullback").symbol, "000725.SZ");
+        assert_eq!(
+            (s.limit_up_date.as_str(), s.limit_up_price),
+            ("2025-07-01", 4.4)
+        );
+        assert_eq!(
+            (s.pullback_price, s.volume_shrink_ratio, s.support_level),
+            (4.15, 0.35, 4.05)
+        );
+        assert_eq!(s.key_levels, vec![4.05, 4.0, 4.4]);
+
+        let StrategySignal::BottomBreakout(s) = &find("bottom_breakout").signal else {
+            panic!("expected bottom breakout");
+        };
+        assert_eq!(
+            (
+                s.breakout_price,
+                s.volume_ratio,
+                s.ma_position,
+                s.resistance_level
+            ),
+            (210.0, 2.3, 4.2, 205.0)
+        );
+
+        let rsi: Vec<_> = stored
+            .iter()
+            .filter_map(|s| match &s.signal {
+                StrategySignal::TechnicalIndicator(t) if t.indicator_name == "RSI超卖" => Some(t),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rsi.len(), 1);
+        assert_eq!((rsi[0].value, rsi[0].price), (22.0, 10.5));
+
+        // 过期信号不再返回
+        sqlx::query(
+            "UPDATE strategy_signals SET expires_at = '2000-01-01 00:00:00' WHERE symbol = 'AAPL'",
+        )
+        .execute(&analyzer.db)
+        .await
+        .unwrap();
+        let stored = analyzer.get_recent_signals(10).await.unwrap();
+        assert_eq!(stored.len(), 3);
+        assert!(stored.iter().all(|s| s.symbol != "AAPL"));
+    }
+
+    /// 模拟唯一索引之前的价格历史：每次轮询都新增一行
+    async fn insert_polls(db: &SqlitePool, symbol: &str, date: &str, polls: &[(f64, i64)]) {
+        sqlx::query("DROP INDEX IF EXISTS idx_price_history_symbol_date")
+            .execute(db)
+            .await
+            .unwrap();
+        for &(price, volume) in polls {
+            sqlx::query(
+                "INSERT INTO price_history (symbol, date, open_price, high_price, low_price, close_price, volume) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(symbol)
+            .bind(date)
+            .bind(price)
+            .bind(price)
+            .bind(price)
+            .bind(price)
+            .bind(volume)
+            .execute(db)
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_price_history_merges_rows_per_date() {
+        let analyzer = analyzer().await;
+        insert_polls(
+            &analyzer.db,
+            "600000.SH",
+            "2025-07-01",
+            &[(10.0, 100), (11.0, 200), (9.5, 300), (10.5, 400)],
+        )
+        .await;
+        insert_polls(
+            &analyzer.db,
+            "600000.SH",
+            "2025-07-02",
+            &[(10.6, 50), (10.8, 150)],
+        )
+        .await;
+
+        let bars = analyzer
+            .get_price_history("600000.SH", 10, PriceAdjustment::Raw)
+            .await
+            .unwrap();
+        let ohlcv: Vec<_> = bars
+            .iter()
+            .map(|b| (b.date.as_str(), b.open, b.high, b.low, b.close, b.volume))
+            .collect();
+        assert_eq!(
+            ohlcv,
+            vec![
+                ("2025-07-01", 10.0, 11.0, 9.5, 10.5, 400),
+                ("2025-07-02", 10.6, 10.8, 10.6, 10.8, 150),
+            ]
+        );
+
+        // LIMIT 按日线计数
+        let latest = analyzer
+            .get_price_history("600000.SH", 1, PriceAdjustment::Raw)
+            .await
+            .unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].date, "2025-07-02");
+    }
+
+    #[tokio::test]
+    async fn test_indicators_use_one_bar_per_date() {
+        let analyzer = analyzer().await;
+        let start = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let mut expected = Vec::new();
+        for day in 0..80u64 {
+            let date = (start + chrono::Days::new(day))
+                .format("%Y-%m-%d")
+                .to_string();
+            let close = 10.0 + (day as f64 / 5.0).sin() * 2.0 + day as f64 * 0.01;
+            let volume = 1_000 + (day as i64 % 7) * 300;
+            // 每天四次轮询：开盘、冲高、回落、收盘，成交量为当日累计
+            insert_polls(
+                &analyzer.db,
+                "600000.SH",
+                &date,
+                &[
+                    (close * 0.98, volume / 4),
+                    (close * 1.03, volume / 2),
+                    (close * 0.97, volume * 3 / 4),
+                    (close, volume),
+                ],
+            )
+            .await;
+            expected.push(PriceData {
+                date,
+                open: close * 0.98,
+                high: close * 1.03,
+                low: close * 0.97,
+                close,
+                volume: volume as u64,
+                change_percent: None,
+            });
+        }
+
+        let values = analyzer
+            .get_indicator_values("600000.SH", 80, PriceAdjustment::Raw, None)
+            .await
+            .unwrap();
+        assert_eq!(values.len(), 80);
+        let dates: BTreeSet<&str> = values.iter().map(|v| v.date.as_str()).collect();
+        assert_eq!(dates.len(), 80);
+
+        // RSI、MACD、布林带、KDJ、ATR 与量比都与按日线计算的结果一致
+        let daily = analyzer.calculate_indicators(&expected, None);
+        assert!(daily.last().unwrap().kdj.is_some());
+        assert!(daily.last().unwrap().atr.is_some());
+        assert_eq!(
+            serde_json::to_value(&values).unwrap(),
+            serde_json::to_value(&daily).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bar_indicators_use_intraday_bars() {
+        let analyzer = analyzer().await;
+        let store = IntradayStore::new(analyzer.db.clone());
+        let start = Utc::now() - chrono::Duration::minutes(60);
+        for minute in 0..60 {
+            let price = 10.0 + (minute as f64 / 5.0).sin();
+            store
+                .record_tick(
+                    "600000.SH",
+                    price,
+                    1_000 * minute,
+                    "sina",
+                    crate::models::MarketSession::Regular,
+                    start + chrono::Duration::minutes(minute),
+                )
+                .await
+                .unwrap();
+        }
+
+        let values = analyzer
+            .get_bar_indicator_values("600000.SH", BarInterval::MINUTE, 30)
+            .await
+            .unwrap();
+        assert_eq!(values.len(), 30);
+        assert!(values.last().unwrap().rsi.is_some());
+        // 日内成交量为累计值，不计算量比与换手率
+        assert!(values
+            .iter()
+            .all(|v| v.volume_ratio.is_none() && v.turnover_rate.is_none()));
+    }
+
+    #[tokio::test]
+    async fn test_score_uses_one_bar_per_date() {
+        let analyzer = analyzer().await;
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut expected = Vec::new();
+        for day in 0..260u64 {
+            let date = (start + chrono::Days::new(day))
+                .format("%Y-%m-%d")
+                .to_string();
+            let close = 10.0 + day as f64 * 0.02 + (day as f64 / 7.0).sin() * 0.5;
+            // 最后一天放量，量能评分要与前一天收盘比较
+            let volume = if day == 259 { 5_000 } else { 1_000 };
+            // 盘中大幅波动：按行计算会把盘中价当作前收盘并放大波动率
+            insert_polls(
+                &analyzer.db,
+                "600000.SH",
+                &date,
+                &[
+                    (close * 0.95, volume / 3),
+                    (close * 1.06, volume * 2 / 3),
+                    (close, volume),
+                ],
+            )
+            .await;
+            expected.push(PriceData {
+                date,
+                open: close * 0.95,
+                high: close * 1.06,
+                low: close * 0.95,
+                close,
+                volume: volume as u64,
+                change_percent: None,
+            });
+        }
+        let expected = &expected[expected.len() - 250..];
+
+        let scoring = ScoringConfig::default();
+        let (signals, score) = analyzer
+            .analyze_and_score("600000.SH", &scoring)
+            .await
+            .unwrap();
+        let score = score.unwrap();
+        assert_eq!(score.analysis_date.to_string(), expected[249].date);
+
+        // 均线趋势、波动率与量能都按日线计算
+        let daily = composite_score("600000.SH", expected, &signals, &scoring).unwrap();
+        assert_eq!(
+            serde_json::to_value(&score).unwrap(),
+            serde_json::to_value(&daily).unwrap()
+        );
+    }
+
+    fn score(
+        symbol: &str,
+        date: &str,
+        value: f64,
+        recommendation: Recommendation,
+    ) -> CompositeScore {
+        CompositeScore {
+            symbol: symbol.to_string(),
+            analysis_date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            total_signals: 1,
+            avg_signal_strength: 3.0,
+            composite_score: value,
+            risk_level: RiskLevel::Medium,
+            recommendation,
+            key_points: vec![format!("评分{value}")],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analysis_upsert_and_ranking() {
+        let analyzer = analyzer().await;
+        for s in [
+            score("600000.SH", "2025-07-30", 60.0, Recommendation::Hold),
+            score("600000.SH", "2025-07-31", 40.0, Recommendation::Watch),
+            // 同一天重复评分覆盖之前的结果
+            score("600000.SH", "2025-07-31", 75.0, Recommendation::Buy),
+            score("AAPL", "2025-07-30", 80.0, Recommendation::Buy),
+            score("000001.SZ", "2025-07-31", 30.0, Recommendation::Sell),
+        ] {
+            analyzer.save_analysis(&s).await.unwrap();
+        }
+
+        // 默认取每只股票最新一次评分
+        let latest = analyzer.ranked_analyses(None, None).await.unwrap();
+        let ranked: Vec<_> = latest
+            .iter()
+            .map(|s| (s.symbol.as_str(), s.composite_score))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![("AAPL", 80.0), ("600000.SH", 75.0), ("000001.SZ", 30.0)]
+        );
+        assert_eq!(latest[1].key_points, vec!["评分75".to_string()]);
+
+        let date = NaiveDate::from_ymd_opt(2025, 7, 31);
+        let buys = analyzer
+            .ranked_analyses(date, Some(Recommendation::Buy))
+            .await
+            .unwrap();
+        assert_eq!(buys.len(), 1);
+        assert_eq!(buys[0].symbol, "600000.SH");
+    }
+}
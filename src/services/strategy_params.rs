@@ -0,0 +1,410 @@
+use crate::config::{PatternParams, PatternParamsOverride, StrategyParams};
+use crate::models::Market;
+use anyhow::{bail, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::str::FromStr;
+use std::sync::RwLock;
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// A股板块，按交易所与代码前缀判断
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Board {
+    /// 沪深主板
+    Main,
+    /// 创业板（300/301）
+    ChiNext,
+    /// 科创板（688/689）
+    Star,
+    /// 北交所
+    Bse,
+}
+
+impl Board {
+    /// A股代码所属板块，非A股返回 None
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        if Market::from_symbol(symbol) != Market::CN {
+            return None;
+        }
+        let symbol = symbol.to_uppercase();
+        let code = symbol.split('.').next().unwrap_or_default();
+        Some(if symbol.ends_with(".BJ") {
+            Board::Bse
+        } else if code.starts_with("300") || code.starts_with("301") {
+            Board::ChiNext
+        } else if code.starts_with("688") || code.starts_with("689") {
+            Board::Star
+        } else {
+            Board::Main
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Board::Main => "main",
+            Board::ChiNext => "chinext",
+            Board::Star => "star",
+            Board::Bse => "bse",
+        }
+    }
+}
+
+impl FromStr for Board {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "main" => Ok(Board::Main),
+            "chinext" => Ok(Board::ChiNext),
+            "star" => Ok(Board::Star),
+            "bse" => Ok(Board::Bse),
+            _ => Err(()),
+        }
+    }
+}
+
+/// 某只股票生效的一组形态参数
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParamSet {
+    /// 参数组名称：市场，A股附加板块，如 us、cn/chinext
+    pub name: String,
+    pub params: PatternParams,
+}
+
+impl PatternParams {
+    /// 用覆盖中设置了的字段替换当前值
+    pub fn apply(&mut self, o: &PatternParamsOverride) {
+        macro_rules! apply {
+            ($($field:ident),*) => {
+                $(if let Some(value) = o.$field {
+                    self.$field = value;
+                })*
+            };
+        }
+        apply!(
+            limit_up_percent,
+            limit_up_lookback_days,
+            pullback_volume_ratio,
+            deep_shrink_ratio,
+            ma_proximity_percent,
+            volume_average_days,
+            breakout_volume_ratio,
+            strong_volume_ratio,
+            resistance_lookback_days,
+            breakout_percent
+        );
+    }
+}
+
+impl PatternParamsOverride {
+    /// 合并另一组覆盖，双方都设置的字段以 `other` 为准
+    pub fn merge(&mut self, other: &PatternParamsOverride) {
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field;
+                })*
+            };
+        }
+        merge!(
+            limit_up_percent,
+            limit_up_lookback_days,
+            pullback_volume_ratio,
+            deep_shrink_ratio,
+            ma_proximity_percent,
+            volume_average_days,
+            breakout_volume_ratio,
+            strong_volume_ratio,
+            resistance_lookback_days,
+            breakout_percent
+        );
+    }
+
+    /// 百分比与比例须为正数，K线数至少为 1
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("limit_up_percent", self.limit_up_percent),
+            ("pullback_volume_ratio", self.pullback_volume_ratio),
+            ("deep_shrink_ratio", self.deep_shrink_ratio),
+            ("ma_proximity_percent", self.ma_proximity_percent),
+            ("breakout_volume_ratio", self.breakout_volume_ratio),
+            ("strong_volume_ratio", self.strong_volume_ratio),
+            ("breakout_percent", self.breakout_percent),
+        ] {
+            if let Some(value) = value {
+                if !value.is_finite() || value <= 0.0 {
+                    bail!("{} must be a positive number", name);
+                }
+            }
+        }
+        for (name, value) in [
+            ("limit_up_lookback_days", self.limit_up_lookback_days),
+            ("volume_average_days", self.volume_average_days),
+            ("resistance_lookback_days", self.resistance_lookback_days),
+        ] {
+            if value == Some(0) {
+                bail!("{} must be at least 1", name);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StrategyParams {
+    /// 按默认值、市场覆盖、A股板块覆盖的顺序得到股票生效的参数
+    pub fn resolve(&self, symbol: &str) -> ParamSet {
+        let market = Market::from_symbol(symbol);
+        let mut params = self.default.clone();
+        let mut name = market.to_str().to_string();
+        if let Some(o) = self.markets.get(market.to_str()) {
+            params.apply(o);
+        }
+        if let Some(board) = Board::from_symbol(symbol) {
+            name = format!("{}/{}", name, board.as_str());
+            if let Some(o) = self.boards.get(board.as_str()) {
+                params.apply(o);
+            }
+        }
+        ParamSet { name, params }
+    }
+
+    fn apply_override(&mut self, scope: ParamScope, o: &PatternParamsOverride) {
+        match scope {
+            ParamScope::Default => self.default.apply(o),
+            ParamScope::Market(market) => self
+                .markets
+                .entry(market.to_str().to_string())
+                .or_default()
+                .merge(o),
+            ParamScope::Board(board) => self
+                .boards
+                .entry(board.as_str().to_string())
+                .or_default()
+                .merge(o),
+        }
+    }
+}
+
+/// 参数覆盖的作用范围：默认值、某个市场或某个A股板块
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamScope {
+    Default,
+    Market(Market),
+    Board(Board),
+}
+
+impl ParamScope {
+    pub fn key(&self) -> &'static str {
+        match self {
+            ParamScope::Default => "default",
+            ParamScope::Market(market) => market.to_str(),
+            ParamScope::Board(board) => board.as_str(),
+        }
+    }
+}
+
+impl FromStr for ParamScope {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("default") {
+            Ok(ParamScope::Default)
+        } else if let Ok(market) = s.parse::<Market>() {
+            Ok(ParamScope::Market(market))
+        } else {
+            s.parse::<Board>().map(ParamScope::Board)
+        }
+    }
+}
+
+/// 运行时可修改的形态策略参数：以配置文件为基础，API 修改保存在 strategy_param_overrides 表中
+pub struct StrategyParamStore {
+    db: SqlitePool,
+    base: StrategyParams,
+    current: RwLock<StrategyParams>,
+    /// 串行化修改，避免并发更新同一范围时互相覆盖
+    update_lock: Mutex<()>,
+}
+
+impl StrategyParamStore {
+    /// 加载配置文件参数并应用数据库中保存的覆盖
+    pub async fn load(db: SqlitePool, base: StrategyParams) -> Result<Self> {
+        let store = Self {
+            db,
+            current: RwLock::new(base.clone()),
+            base,
+            update_lock: Mutex::new(()),
+        };
+        store.reload().await?;
+        Ok(store)
+    }
+
+    /// 当前生效的全部参数
+    pub fn snapshot(&self) -> StrategyParams {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// 合并保存某个范围的覆盖，返回更新后的全部参数
+    pub async fn update(
+        &self,
+        scope: ParamScope,
+        o: &PatternParamsOverride,
+    ) -> Result<StrategyParams> {
+        o.validate()?;
+        let _guard = self.update_lock.lock().await;
+
+        let key = scope.key();
+        let existing = sqlx::query_scalar!(
+            "SELECT params FROM strategy_param_overrides WHERE scope = ?",
+            key
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        let mut merged: PatternParamsOverride = match existing {
+            Some(json) => serde_json::from_str(&json)?,
+            None => PatternParamsOverride::default(),
+        };
+        merged.merge(o);
+
+        let params = serde_json::to_string(&merged)?;
+        let updated_at = Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+            INSERT INTO strategy_param_overrides (scope, params, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(scope) DO UPDATE SET params = excluded.params, updated_at = excluded.updated_at
+            "#,
+            key,
+            params,
+            updated_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        self.reload().await
+    }
+
+    /// 删除某个范围通过 API 保存的覆盖，恢复为配置文件中的值
+    pub async fn reset(&self, scope: ParamScope) -> Result<StrategyParams> {
+        let _guard = self.update_lock.lock().await;
+        let key = scope.key();
+        sqlx::query!("DELETE FROM strategy_param_overrides WHERE scope = ?", key)
+            .execute(&self.db)
+            .await?;
+        self.reload().await
+    }
+
+    async fn reload(&self) -> Result<StrategyParams> {
+        let rows =
+            sqlx::query!(r#"SELECT scope as "scope!", params FROM strategy_param_overrides"#)
+                .fetch_all(&self.db)
+                .await?;
+
+        let mut params = self.base.clone();
+        // 先应用默认值，再应用市场与板块覆盖
+        let mut overrides: Vec<(ParamScope, PatternParamsOverride)> = Vec::new();
+        for row in rows {
+            let Ok(scope) = row.scope.parse::<ParamScope>() else {
+                warn!("忽略未知范围的策略参数覆盖: {}", row.scope);
+                continue;
+            };
+            match serde_json::from_str(&row.params) {
+                Ok(o) => overrides.push((scope, o)),
+                Err(e) => warn!("策略参数覆盖 {} 无法解析: {}", row.scope, e),
+            }
+        }
+        overrides.sort_by_key(|(scope, _)| !matches!(scope, ParamScope::Default));
+        for (scope, o) in &overrides {
+            params.apply_override(*scope, o);
+        }
+
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = params.clone();
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_board_detection_and_resolution() {
+        assert_eq!(Board::from_symbol("600519.SH"), Some(Board::Main));
+        assert_eq!(Board::from_symbol("300750.SZ"), Some(Board::ChiNext));
+        assert_eq!(Board::from_symbol("688981.SH"), Some(Board::Star));
+        assert_eq!(Board::from_symbol("830799.BJ"), Some(Board::Bse));
+        assert_eq!(Board::from_symbol("AAPL"), None);
+
+        let mut params = StrategyParams::default();
+        params.markets.insert(
+            "cn".to_string(),
+            PatternParamsOverride {
+                breakout_percent: Some(3.0),
+                ..PatternParamsOverride::default()
+            },
+        );
+
+        let chinext = params.resolve("300750.SZ");
+        assert_eq!(chinext.name, "cn/chinext");
+        assert_eq!(chinext.params.limit_up_percent, 19.8);
+        assert_eq!(chinext.params.breakout_percent, 3.0);
+
+        let us = params.resolve("AAPL");
+        assert_eq!(us.name, "us");
+        assert_eq!(us.params, PatternParams::default());
+    }
+
+    #[tokio::test]
+    async fn test_runtime_overrides_persist_and_reset() {
+        // 内存数据库每个连接相互独立，测试只使用一个连接
+        let db = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&db).await.unwrap();
+        let store = StrategyParamStore::load(db.clone(), StrategyParams::default())
+            .await
+            .unwrap();
+
+        let us = ParamScope::Market(Market::US);
+        let limit = PatternParamsOverride {
+            limit_up_percent: Some(8.0),
+            ..PatternParamsOverride::default()
+        };
+        let breakout = PatternParamsOverride {
+            breakout_percent: Some(1.0),
+            ..PatternParamsOverride::default()
+        };
+        store.update(us, &limit).await.unwrap();
+        store.update(us, &breakout).await.unwrap();
+        let invalid = PatternParamsOverride {
+            resistance_lookback_days: Some(0),
+            ..PatternParamsOverride::default()
+        };
+        assert!(store.update(us, &invalid).await.is_err());
+
+        // 重新加载后仍然生效，两次修改合并
+        let reloaded = StrategyParamStore::load(db, StrategyParams::default())
+            .await
+            .unwrap();
+        let resolved = reloaded.snapshot().resolve("TSLA").params;
+        assert_eq!(
+            (resolved.limit_up_percent, resolved.breakout_percent),
+            (8.0, 1.0)
+        );
+
+        let params = reloaded.reset(us).await.unwrap();
+        assert_eq!(params.resolve("TSLA").params, PatternParams::default());
+        assert_eq!(
+            "chinext".parse::<ParamScope>(),
+            Ok(ParamScope::Board(Board::ChiNext))
+        );
+    }
+}
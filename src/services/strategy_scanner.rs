@@ -0,0 +1,532 @@
+use crate::config::{IndicatorConfig, ScanUniverse, ScannerConfig, ScoringConfig, StrategyConfig};
+use crate::models::Market;
+use crate::services::{
+    CompositeScore, StrategyAnalyzer, StrategyParamStore, StrategySignal, TradingCalendars,
+};
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDateTime, NaiveTime, Utc};
+use futures_util::{stream, StreamExt};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+/// 策略扫描运行记录
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanRun {
+    pub id: i64,
+    pub market: String,
+    pub universe: String,
+    /// scheduled 为收盘后自动扫描，manual 为手动触发
+    pub trigger_type: String,
+    /// 对应的收盘时间（UTC），手动扫描为空
+    pub session_close: Option<NaiveDateTime>,
+    /// running / completed / failed
+    pub status: String,
+    pub symbols_total: i64,
+    pub symbols_failed: i64,
+    pub signals_new: i64,
+    pub signals_duplicate: i64,
+    pub signals_expired: i64,
+    /// 保存了综合评分的股票数
+    pub symbols_scored: i64,
+    pub error: Option<String>,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// 单次扫描的统计
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ScanCounts {
+    symbols_failed: i64,
+    signals_new: i64,
+    signals_duplicate: i64,
+    signals_expired: i64,
+    symbols_scored: i64,
+}
+
+/// 收盘后策略扫描器：按市场收盘时间对扫描范围内的股票运行策略分析
+pub struct StrategyScanner {
+    db: SqlitePool,
+    config: ScannerConfig,
+    indicators: IndicatorConfig,
+    scoring: ScoringConfig,
+    params: Arc<StrategyParamStore>,
+    calendars: Arc<TradingCalendars>,
+    /// 同一时间只运行一次扫描，避免手动与定时扫描重复分析
+    scan_lock: Mutex<()>,
+}
+
+impl StrategyScanner {
+    pub fn new(
+        db: SqlitePool,
+        strategy: &StrategyConfig,
+        params: Arc<StrategyParamStore>,
+        calendars: Arc<TradingCalendars>,
+    ) -> Self {
+        Self {
+            db,
+            config: strategy.scanner.clone(),
+            indicators: strategy.indicators.clone(),
+            scoring: strategy.scoring.clone(),
+            params,
+            calendars,
+            scan_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn config(&self) -> &ScannerConfig {
+        &self.config
+    }
+
+    /// 扫描范围内的全部股票代码
+    pub async fn universe_symbols(&self) -> Result<Vec<String>> {
+        let mut symbols = match self.config.universe {
+            ScanUniverse::CnStocks => {
+                sqlx::query_scalar!(
+                    "SELECT symbol FROM cn_stocks WHERE status = 'active' ORDER BY symbol"
+                )
+                .fetch_all(&self.db)
+                .await?
+            }
+            ScanUniverse::Watchlist => self
+                .config
+                .watchlist
+                .iter()
+                .map(|symbol| symbol.trim().to_uppercase())
+                .filter(|symbol| !symbol.is_empty())
+                .collect(),
+            ScanUniverse::Index => self.index_constituents(&self.config.index).await?,
+        };
+        symbols.sort();
+        symbols.dedup();
+        Ok(symbols)
+    }
+
+    /// 扫描范围内属于指定市场的股票
+    pub async fn market_symbols(&self, market: Market) -> Result<Vec<String>> {
+        Ok(self
+            .universe_symbols()
+            .await?
+            .into_iter()
+            .filter(|symbol| Market::from_symbol(symbol) == market)
+            .collect())
+    }
+
+    /// 指数成分股
+    pub async fn index_constituents(&self, index: &str) -> Result<Vec<String>> {
+        let index = index.to_uppercase();
+        let symbols = sqlx::query_scalar!(
+            "SELECT symbol FROM index_constituents WHERE index_symbol = ? ORDER BY symbol",
+            index
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(symbols)
+    }
+
+    /// 整体替换指数成分股，返回保存的成分股数量
+    pub async fn set_index_constituents(&self, index: &str, symbols: &[String]) -> Result<usize> {
+        let index = index.to_uppercase();
+        let mut symbols: Vec<String> = symbols
+            .iter()
+            .map(|symbol| symbol.trim().to_uppercase())
+            .filter(|symbol| !symbol.is_empty())
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+
+        let now = Utc::now().naive_utc();
+        let mut tx = self.db.begin().await?;
+        sqlx::query!(
+            "DELETE FROM index_constituents WHERE index_symbol = ?",
+            index
+        )
+        .execute(&mut *tx)
+        .await?;
+        for symbol in &symbols {
+            sqlx::query!(
+                "INSERT INTO index_constituents (index_symbol, symbol, updated_at) VALUES (?, ?, ?)",
+                index,
+                symbol,
+                now
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(symbols.len())
+    }
+
+    /// 最近的扫描记录，最新的在前
+    pub async fn recent_runs(&self, limit: i64) -> Result<Vec<ScanRun>> {
+        let runs = sqlx::query_as!(
+            ScanRun,
+            r#"
+            SELECT id as "id!", market, universe, trigger_type,
+                   session_close as "session_close: NaiveDateTime", status,
+                   symbols_total, symbols_failed, signals_new, signals_duplicate,
+                   signals_expired, symbols_scored, error,
+                   started_at as "started_at: NaiveDateTime",
+                   finished_at as "finished_at: NaiveDateTime"
+            FROM strategy_scan_runs
+            ORDER BY started_at DESC, id DESC
+            LIMIT ?
+            "#,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?;
+        Ok(runs)
+    }
+
+    /// 市场最近一次已过等待时间的收盘，全天候市场以 UTC 零点作为收盘
+    fn due_close(&self, market: Market, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let cutoff = now - Duration::minutes(self.config.delay_minutes.max(0));
+        let calendar = self.calendars.for_market(&market);
+        if calendar.is_always_open() {
+            Some(cutoff.date_naive().and_time(NaiveTime::MIN).and_utc())
+        } else {
+            calendar.last_day_close(cutoff)
+        }
+    }
+
+    /// 对已收盘且尚未扫描的市场运行扫描，返回本次运行的扫描次数
+    pub async fn run_due_scans(&self) -> Result<usize> {
+        let symbols = self.universe_symbols().await?;
+        let now = Utc::now();
+        let mut runs = 0;
+
+        for market in Market::ALL {
+            let market_symbols: Vec<String> = symbols
+                .iter()
+                .filter(|symbol| Market::from_symbol(symbol) == market)
+                .cloned()
+                .collect();
+            if market_symbols.is_empty() {
+                continue;
+            }
+            let Some(close) = self.due_close(market, now) else {
+                continue;
+            };
+            // 唯一索引保证同一市场同一次收盘只登记一次
+            let Some(run_id) = self
+                .create_run(market, "scheduled", Some(close.naive_utc()))
+                .await?
+            else {
+                continue;
+            };
+
+            info!(
+                "开始收盘后策略扫描: {} 收盘 {}，共 {} 只",
+                market.display_name(),
+                close,
+                market_symbols.len()
+            );
+            self.execute_run(run_id, &market_symbols).await;
+            runs += 1;
+        }
+
+        Ok(runs)
+    }
+
+    /// 手动触发指定市场的扫描，扫描在后台运行；扫描范围内没有该市场的股票时返回 None
+    pub async fn start_manual_scan(self: Arc<Self>, market: Market) -> Result<Option<i64>> {
+        let symbols = self.market_symbols(market).await?;
+        if symbols.is_empty() {
+            return Ok(None);
+        }
+        let Some(run_id) = self.create_run(market, "manual", None).await? else {
+            return Ok(None);
+        };
+
+        tokio::spawn(async move {
+            self.execute_run(run_id, &symbols).await;
+        });
+        Ok(Some(run_id))
+    }
+
+    /// 登记一次扫描，同一收盘已登记过时返回 None
+    async fn create_run(
+        &self,
+        market: Market,
+        trigger_type: &str,
+        session_close: Option<NaiveDateTime>,
+    ) -> Result<Option<i64>> {
+        let market = market.to_str();
+        let universe = self.config.universe.as_str();
+        let started_at = Utc::now().naive_utc();
+        let result = sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO strategy_scan_runs
+            (market, universe, trigger_type, session_close, status, started_at)
+            VALUES (?, ?, ?, ?, 'running', ?)
+            "#,
+            market,
+            universe,
+            trigger_type,
+            session_close,
+            started_at
+        )
+        .execute(&self.db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(result.last_insert_rowid()))
+        }
+    }
+
+    /// 执行扫描并写回运行结果
+    async fn execute_run(&self, run_id: i64, symbols: &[String]) {
+        let _guard = self.scan_lock.lock().await;
+        let symbols_total = symbols.len() as i64;
+        let finished = match self.scan_symbols(symbols).await {
+            Ok(counts) => {
+                info!(
+                    "策略扫描 #{} 完成: {} 只股票，新信号 {}，重复 {}，失败 {}",
+                    run_id,
+                    symbols_total,
+                    counts.signals_new,
+                    counts.signals_duplicate,
+                    counts.symbols_failed
+                );
+                self.finish_run(run_id, "completed", symbols_total, counts, None)
+                    .await
+            }
+            Err(e) => {
+                error!("策略扫描 #{} 失败: {}", run_id, e);
+                self.finish_run(
+                    run_id,
+                    "failed",
+                    symbols_total,
+                    ScanCounts::default(),
+                    Some(e.to_string()),
+                )
+                .await
+            }
+        };
+        if let Err(e) = finished {
+            error!("保存策略扫描 #{} 结果失败: {}", run_id, e);
+        }
+    }
+
+    /// 过期旧信号，有限并发分析股票，保存尚不存在的信号与当日综合评分
+    async fn scan_symbols(&self, symbols: &[String]) -> Result<ScanCounts> {
+        let analyzer = StrategyAnalyzer::new(self.db.clone(), self.indicators.clone())
+            .with_params(self.params.snapshot());
+        let mut counts = ScanCounts {
+            signals_expired: analyzer.expire_signals().await? as i64,
+            ..ScanCounts::default()
+        };
+
+        let results: Vec<(String, AnalysisResult)> = stream::iter(symbols.to_vec())
+            .map(|symbol| analyze(&analyzer, &self.scoring, symbol))
+            .buffer_unordered(self.config.max_concurrency.max(1))
+            .collect()
+            .await;
+
+        // 分析并发进行，写入逐条进行，避免 SQLite 写锁竞争
+        for (symbol, result) in results {
+            match result {
+                Ok((signals, score)) => {
+                    if let Some(score) = score {
+                        analyzer.save_analysis(&score).await?;
+                        counts.symbols_scored += 1;
+                    }
+                    for signal in signals {
+                        if analyzer.is_duplicate_signal(&signal).await? {
+                            counts.signals_duplicate += 1;
+                        } else {
+                            analyzer.save_signal(&signal).await?;
+                            counts.signals_new += 1;
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!("策略扫描分析 {} 失败: {}", symbol, e);
+                    counts.symbols_failed += 1;
+                }
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn finish_run(
+        &self,
+        run_id: i64,
+        status: &str,
+        symbols_total: i64,
+        counts: ScanCounts,
+        error: Option<String>,
+    ) -> Result<()> {
+        let finished_at = Utc::now().naive_utc();
+        sqlx::query!(
+            r#"
+            UPDATE strategy_scan_runs
+            SET status = ?, symbols_total = ?, symbols_failed = ?, signals_new = ?,
+                signals_duplicate = ?, signals_expired = ?, symbols_scored = ?, error = ?,
+                finished_at = ?
+            WHERE id = ?
+            "#,
+            status,
+            symbols_total,
+            counts.symbols_failed,
+            counts.signals_new,
+            counts.signals_duplicate,
+            counts.signals_expired,
+            counts.symbols_scored,
+            error,
+            finished_at,
+            run_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// 服务重启时仍为 running 的扫描已中断，标记为失败
+    async fn fail_interrupted_runs(&self) -> Result<u64> {
+        let finished_at = Utc::now().naive_utc();
+        let result = sqlx::query!(
+            r#"
+            UPDATE strategy_scan_runs
+            SET status = 'failed', error = '服务重启，扫描中断', finished_at = ?
+            WHERE status = 'running'
+            "#,
+            finished_at
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// 定期检查各市场是否已收盘，收盘后扫描一次
+    pub fn start_scan_loop(self: Arc<Self>) {
+        tokio::spawn(async move {
+            match self.fail_interrupted_runs().await {
+                Ok(0) => {}
+                Ok(count) => warn!("{} 次策略扫描因重启中断", count),
+                Err(e) => error!("更新中断的策略扫描失败: {}", e),
+            }
+
+            let check_interval =
+                std::time::Duration::from_secs(self.config.check_interval_secs.max(1));
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                interval.tick().await;
+                match self.run_due_scans().await {
+                    Ok(0) => {}
+                    Ok(count) => info!("完成 {} 个市场的收盘后策略扫描", count),
+                    Err(e) => error!("收盘后策略扫描失败: {}", e),
+                }
+            }
+        });
+    }
+}
+
+type AnalysisResult = Result<(Vec<StrategySignal>, Option<CompositeScore>)>;
+
+async fn analyze(
+    analyzer: &StrategyAnalyzer,
+    scoring: &ScoringConfig,
+    symbol: String,
+) -> (String, AnalysisResult) {
+    let result = analyzer.analyze_and_score(&symbol, scoring).await;
+    (symbol, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::TechnicalSignal;
+
+    async fn scanner(config: ScannerConfig) -> StrategyScanner {
+        // 内存数据库每个连接相互独立，测试只使用一个连接
+        let db = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&db).await.unwrap();
+        let strategy = StrategyConfig {
+            scanner: config,
+            ..StrategyConfig::default()
+        };
+        let params = StrategyParamStore::load(db.clone(), strategy.params.clone())
+            .await
+            .unwrap();
+        StrategyScanner::new(
+            db,
+            &strategy,
+            Arc::new(params),
+            Arc::new(TradingCalendars::builtin()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_scan_runs_once_per_close() {
+        let scanner = scanner(ScannerConfig {
+            universe: ScanUniverse::Watchlist,
+            watchlist: vec!["600000.sh".to_string(), "AAPL".to_string(), "".to_string()],
+            ..ScannerConfig::default()
+        })
+        .await;
+
+        // 每个市场一次，同一收盘再次检查时不重复扫描
+        assert_eq!(scanner.run_due_scans().await.unwrap(), 2);
+        assert_eq!(scanner.run_due_scans().await.unwrap(), 0);
+
+        let runs = scanner.recent_runs(10).await.unwrap();
+        assert_eq!(runs.len(), 2);
+        for run in &runs {
+            assert_eq!(run.status, "completed");
+            assert_eq!(run.trigger_type, "scheduled");
+            assert_eq!(run.symbols_total, 1);
+            assert!(run.session_close.is_some());
+        }
+        assert_eq!(
+            scanner.market_symbols(Market::CN).await.unwrap(),
+            vec!["600000.SH".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_index_universe_and_signal_dedup() {
+        let scanner = scanner(ScannerConfig {
+            universe: ScanUniverse::Index,
+            index: "000300.SH".to_string(),
+            ..ScannerConfig::default()
+        })
+        .await;
+        let saved = scanner
+            .set_index_constituents(
+                "000300.sh",
+                &["600519.SH".to_string(), "600519.sh".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(saved, 1);
+        assert_eq!(
+            scanner.universe_symbols().await.unwrap(),
+            vec!["600519.SH".to_string()]
+        );
+
+        let analyzer = StrategyAnalyzer::new(scanner.db.clone(), IndicatorConfig::default());
+        let signal = StrategySignal::TechnicalIndicator(TechnicalSignal {
+            symbol: "600519.SH".to_string(),
+            indicator_name: "RSI超卖".to_string(),
+            value: 25.0,
+            price: 1500.0,
+            signal_strength: 3,
+            description: "RSI超卖".to_string(),
+        });
+        assert!(!analyzer.is_duplicate_signal(&signal).await.unwrap());
+        analyzer.save_signal(&signal).await.unwrap();
+        assert!(analyzer.is_duplicate_signal(&signal).await.unwrap());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::config::StreamConfig;
+use crate::services::crypto::CryptoVenue;
+use crate::utils::raw_response_hash;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, RwLock};
+use tokio::time::{self, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+/// 重连初始退避时间
+const BASE_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+/// OKX 要求30秒内有消息往来，否则断开连接
+const OKX_PING_INTERVAL: Duration = Duration::from_secs(20);
+
+/// 推送的一笔行情
+#[derive(Debug, Clone)]
+pub struct StreamTick {
+    pub venue_symbol: String,
+    pub price: f64,
+    pub volume: f64,
+    pub timestamp: DateTime<Utc>,
+    /// 推送消息原文摘要
+    pub raw_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceMiniTicker {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c")]
+    close: String,
+    #[serde(rename = "v")]
+    volume: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OkxPush {
+    data: Vec<OkxPushTicker>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OkxPushTicker {
+    #[serde(rename = "instId")]
+    inst_id: String,
+    last: String,
+    #[serde(rename = "vol24h")]
+    vol_24h: String,
+    ts: String,
+}
+
+#[derive(Debug, Default)]
+struct StreamState {
+    connected: bool,
+    last_tick: HashMap<String, Instant>,
+    reconnects: u64,
+}
+
+/// 推送连接状态，在数据源健康API中展示
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamStatus {
+    pub venue: &'static str,
+    pub connected: bool,
+    /// 已发生的重连次数
+    pub reconnects: u64,
+    /// 当前订阅的交易对数
+    pub subscribed: usize,
+}
+
+/// 行情推送连接 - 维护所订阅交易对的 WebSocket 连接
+///
+/// 断线后按指数退避重连并重新订阅；订阅集合变化时增量发送订阅/退订消息。
+/// 收到的行情通过 channel 交给价格服务，走与轮询相同的预警检查路径。
+pub struct PriceStream {
+    venue: CryptoVenue,
+    url: String,
+    stale_after: Duration,
+    max_backoff: Duration,
+    symbols: watch::Sender<BTreeSet<String>>,
+    state: Arc<RwLock<StreamState>>,
+}
+
+impl PriceStream {
+    pub fn new(venue: CryptoVenue, config: &StreamConfig) -> Self {
+        let url = config
+            .url
+            .clone()
+            .unwrap_or_else(|| default_stream_url(venue).to_string());
+        let (symbols, _) = watch::channel(BTreeSet::new());
+
+        Self {
+            venue,
+            url,
+            stale_after: Duration::from_secs(config.stale_after_secs),
+            max_backoff: Duration::from_secs(config.reconnect_max_backoff_secs.max(1)),
+            symbols,
+            state: Arc::new(RwLock::new(StreamState::default())),
+        }
+    }
+
+    /// 更新需要订阅的交易对集合
+    pub fn set_symbols(&self, symbols: BTreeSet<String>) {
+        self.symbols.send_if_modified(|current| {
+            if *current == symbols {
+                return false;
+            }
+            *current = symbols;
+            true
+        });
+    }
+
+    /// 该交易对的推送是否健康（已连接且在 `stale_after_secs` 内收到过行情）
+    pub async fn is_healthy(&self, venue_symbol: &str) -> bool {
+        let state = self.state.read().await;
+        state.connected
+            && state
+                .last_tick
+                .get(venue_symbol)
+                .is_some_and(|at| at.elapsed() < self.stale_after)
+    }
+
+    /// 连接状态与重连次数
+    pub async fn status(&self) -> StreamStatus {
+        let state = self.state.read().await;
+        StreamStatus {
+            venue: self.venue.name(),
+            connected: state.connected,
+            reconnects: state.reconnects,
+            subscribed: self.symbols.borrow().len(),
+        }
+    }
+
+    /// 启动连接任务，行情发送到 `ticks`
+    pub fn spawn(self: &Arc<Self>, ticks: mpsc::Sender<StreamTick>) {
+        let stream = Arc::clone(self);
+        tokio::spawn(async move { stream.run(ticks).await });
+    }
+
+    async fn run(&self, ticks: mpsc::Sender<StreamTick>) {
+        let mut symbols_rx = self.symbols.subscribe();
+        let mut backoff = BASE_RECONNECT_DELAY;
+
+        loop {
+            // 没有需要订阅的交易对时不保持连接
+            if symbols_rx.borrow_and_update().is_empty() {
+                if symbols_rx.changed().await.is_err() {
+                    return;
+                }
+                continue;
+            }
+
+            let received = match self.connect_and_stream(&mut symbols_rx, &ticks).await {
+                Ok(received) => received,
+                Err(e) => {
+                    warn!("{} price stream error: {}", self.venue.name(), e);
+                    false
+                }
+            };
+
+            {
+                let mut state = self.state.write().await;
+                state.connected = false;
+                state.reconnects += 1;
+            }
+            if ticks.is_closed() {
+                return;
+            }
+
+            // 收到过行情说明连接曾经正常，从初始退避重新开始
+            if received {
+                backoff = BASE_RECONNECT_DELAY;
+            }
+            info!(
+                "Reconnecting {} price stream in {}s",
+                self.venue.name(),
+                backoff.as_secs()
+            );
+            time::sleep(backoff).await;
+            backoff = (backoff * 2).min(self.max_backoff);
+        }
+    }
+
+    /// 建立一次连接并持续接收，连接断开时返回是否收到过行情
+    async fn connect_and_stream(
+        &self,
+        symbols_rx: &mut watch::Receiver<BTreeSet<String>>,
+        ticks: &mpsc::Sender<StreamTick>,
+    ) -> Result<bool> {
+        let (mut ws, _) = tokio_tungstenite::connect_async(self.url.as_str()).await?;
+        info!("Connected to {} price stream", self.venue.name());
+
+        let mut subscribed = symbols_rx.borrow_and_update().clone();
+        let all: Vec<String> = subscribed.iter().cloned().collect();
+        ws.send(Message::Text(subscribe_message(self.venue, &all, true)))
+            .await?;
+        self.state.write().await.connected = true;
+
+        let mut received = false;
+        let mut last_message = Instant::now();
+        let mut ping = time::interval(OKX_PING_INTERVAL);
+        ping.tick().await;
+
+        loop {
+            tokio::select! {
+                message = ws.next() => {
+                    let message = match message {
+                        Some(message) => message?,
+                        None => return Ok(received),
+                    };
+                    last_message = Instant::now();
+                    match message {
+                        Message::Text(text) => {
+                            for tick in parse_stream_message(self.venue, &text) {
+                                received = true;
+                                self.state
+                                    .write()
+                                    .await
+                                    .last_tick
+                                    .insert(tick.venue_symbol.clone(), Instant::now());
+                                if ticks.send(tick).await.is_err() {
+                                    return Ok(received);
+                                }
+                            }
+                        }
+                        Message::Close(_) => return Ok(received),
+                        _ => {}
+                    }
+                }
+                changed = symbols_rx.changed() => {
+                    if changed.is_err() {
+                        return Ok(received);
+                    }
+                    let desired = symbols_rx.borrow_and_update().clone();
+                    if desired.is_empty() {
+                        let _ = ws.close(None).await;
+                        return Ok(received);
+                    }
+
+                    let added: Vec<String> = desired.difference(&subscribed).cloned().collect();
+                    let removed: Vec<String> = subscribed.difference(&desired).cloned().collect();
+                    if !added.is_empty() {
+                        info!("Subscribing to {:?} on {} stream", added, self.venue.name());
+                        ws.send(Message::Text(subscribe_message(self.venue, &added, true))).await?;
+                    }
+                    if !removed.is_empty() {
+                        info!("Unsubscribing from {:?} on {} stream", removed, self.venue.name());
+                        ws.send(Message::Text(subscribe_message(self.venue, &removed, false))).await?;
+                        let mut state = self.state.write().await;
+                        for symbol in &removed {
+                            state.last_tick.remove(symbol);
+                        }
+                    }
+                    subscribed = desired;
+                }
+                _ = ping.tick() => {
+                    if last_message.elapsed() >= self.stale_after {
+                        return Err(anyhow::anyhow!(
+                            "No message received for {}s",
+                            last_message.elapsed().as_secs()
+                        ));
+                    }
+                    if self.venue == CryptoVenue::Okx {
+                        ws.send(Message::Text("ping".to_string())).await?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 交易所官方推送地址
+pub fn default_stream_url(venue: CryptoVenue) -> &'static str {
+    match venue {
+        CryptoVenue::Binance => "wss://stream.binance.com:9443/ws",
+        CryptoVenue::Okx => "wss://ws.okx.com:8443/ws/v5/public",
+    }
+}
+
+/// 生成订阅/退订消息
+pub fn subscribe_message(venue: CryptoVenue, venue_symbols: &[String], subscribe: bool) -> String {
+    match venue {
+        CryptoVenue::Binance => serde_json::json!({
+            "method": if subscribe { "SUBSCRIBE" } else { "UNSUBSCRIBE" },
+            "params": venue_symbols
+                .iter()
+                .map(|s| format!("{}@miniTicker", s.to_lowercase()))
+                .collect::<Vec<_>>(),
+            "id": 1
+        }),
+        CryptoVenue::Okx => serde_json::json!({
+            "op": if subscribe { "subscribe" } else { "unsubscribe" },
+            "args": venue_symbols
+                .iter()
+                .map(|s| serde_json::json!({"channel": "tickers", "instId": s}))
+                .collect::<Vec<_>>()
+        }),
+    }
+    .to_string()
+}
+
+/// 解析推送消息，订阅确认、心跳等非行情消息返回空
+pub fn parse_stream_message(venue: CryptoVenue, text: &str) -> Vec<StreamTick> {
+    match venue {
+        CryptoVenue::Binance => serde_json::from_str::<BinanceMiniTicker>(text)
+            .ok()
+            .and_then(|t| {
+                Some(StreamTick {
+                    price: t.close.parse().ok()?,
+                    volume: t.volume.parse().ok()?,
+                    timestamp: DateTime::from_timestamp_millis(t.event_time)?,
+                    raw_hash: raw_response_hash(text),
+                    venue_symbol: t.symbol,
+                })
+            })
+            .into_iter()
+            .collect(),
+        CryptoVenue::Okx => serde_json::from_str::<OkxPush>(text)
+            .map(|push| {
+                push.data
+                    .into_iter()
+                    .filter_map(|t| {
+                        Some(StreamTick {
+                            price: t.last.parse().ok()?,
+                            volume: t.vol_24h.parse().ok()?,
+                            timestamp: DateTime::from_timestamp_millis(t.ts.parse().ok()?)?,
+                            raw_hash: raw_response_hash(text),
+                            venue_symbol: t.inst_id,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
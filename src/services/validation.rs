@@ -0,0 +1,112 @@
+use crate::config::ValidationConfig;
+
+/// 相对参考价的偏离百分比
+pub fn deviation_percent(price: f64, reference: f64) -> f64 {
+    if reference == 0.0 {
+        return f64::INFINITY;
+    }
+    ((price - reference) / reference * 100.0).abs()
+}
+
+/// 价格校验结论
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Accept,
+    /// 需要第二个数据源确认
+    NeedsConfirmation {
+        deviation_percent: f64,
+    },
+}
+
+/// 价格校验器 - 在写入价格历史、检查预警之前拦截异常行情
+#[derive(Debug, Clone)]
+pub struct PriceValidator {
+    config: ValidationConfig,
+}
+
+impl PriceValidator {
+    pub fn new(config: &ValidationConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// A股是否需要新浪与腾讯交叉核对
+    pub fn cn_cross_check(&self) -> bool {
+        self.config.enabled && self.config.cn_cross_check
+    }
+
+    /// 同一数据源重复给出的一致价格是否可以代替第二个数据源确认
+    pub fn repeat_confirms(&self) -> bool {
+        self.config.enabled && self.config.repeat_confirm
+    }
+
+    /// 与上一次有效价格比较，偏离超过 `max_deviation_percent` 时需要确认
+    pub fn check(&self, price: f64, last_good: Option<f64>) -> Verdict {
+        if !self.config.enabled {
+            return Verdict::Accept;
+        }
+        if price <= 0.0 {
+            return Verdict::NeedsConfirmation {
+                deviation_percent: f64::INFINITY,
+            };
+        }
+
+        match last_good {
+            Some(reference) => {
+                let deviation = deviation_percent(price, reference);
+                if deviation > self.config.max_deviation_percent {
+                    Verdict::NeedsConfirmation {
+                        deviation_percent: deviation,
+                    }
+                } else {
+                    Verdict::Accept
+                }
+            }
+            None => Verdict::Accept,
+        }
+    }
+
+    /// 第二个数据源的价格是否在容差内与之一致
+    pub fn confirms(&self, price: f64, confirm_price: f64) -> bool {
+        deviation_percent(price, confirm_price) <= self.config.confirm_tolerance_percent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator() -> PriceValidator {
+        PriceValidator::new(&ValidationConfig {
+            enabled: true,
+            max_deviation_percent: 15.0,
+            confirm_tolerance_percent: 1.0,
+            cn_cross_check: false,
+            repeat_confirm: false,
+        })
+    }
+
+    #[test]
+    fn test_large_moves_need_confirmation() {
+        let validator = validator();
+
+        assert_eq!(validator.check(10.5, Some(10.0)), Verdict::Accept);
+        assert_eq!(validator.check(10.5, None), Verdict::Accept);
+        assert!(matches!(
+            validator.check(1.0, Some(10.0)),
+            Verdict::NeedsConfirmation { deviation_percent } if deviation_percent == 90.0
+        ));
+        assert!(matches!(
+            validator.check(0.0, Some(10.0)),
+            Verdict::NeedsConfirmation { .. }
+        ));
+
+        assert!(validator.confirms(1.0, 1.005));
+        assert!(!validator.confirms(1.0, 10.0));
+    }
+}
@@ -1,2 +1,9 @@
 // Utility functions module
 // This module will contain shared utility functions
+
+use sha2::{Digest, Sha256};
+
+/// 数据源原始响应的 SHA-256 摘要（十六进制），随价格一同保存用于追溯
+pub fn raw_response_hash(body: &str) -> String {
+    format!("{:x}", Sha256::digest(body.as_bytes()))
+}
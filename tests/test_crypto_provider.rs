@@ -0,0 +1,146 @@
+use anyhow::Result;
+use axum::extract::Query;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use trade_alert_rust::config::{CryptoProviderConfig, PriceFetcherConfig};
+use trade_alert_rust::services::rate_limiter::RateLimiter;
+use trade_alert_rust::services::{CryptoExchangeClient, CryptoVenue};
+
+// 2024-01-01 00:00:00 UTC
+const DAY0_MILLIS: i64 = 1_704_067_200_000;
+const DAY_MILLIS: i64 = 86_400_000;
+
+/// 启动本地模拟交易所，返回地址
+async fn start_mock_exchange() -> String {
+    let app = Router::new()
+        .route(
+            "/api/v3/ticker/24hr",
+            get(|Query(params): Query<HashMap<String, String>>| async move {
+                match params.get("symbol").map(String::as_str) {
+                    Some("BTCUSDT") => (
+                        axum::http::StatusCode::OK,
+                        Json(json!({
+                            "symbol": "BTCUSDT",
+                            "lastPrice": "43250.50",
+                            "volume": "12345.678",
+                            "closeTime": DAY0_MILLIS
+                        })),
+                    ),
+                    _ => (
+                        axum::http::StatusCode::BAD_REQUEST,
+                        Json(json!({"code": -1121, "msg": "Invalid symbol."})),
+                    ),
+                }
+            }),
+        )
+        .route(
+            "/api/v3/klines",
+            get(|| async {
+                Json(json!([
+                    [DAY0_MILLIS, "100.0", "110.0", "95.0", "105.0", "10.5", DAY0_MILLIS + DAY_MILLIS - 1],
+                    [DAY0_MILLIS + DAY_MILLIS, "105.0", "120.0", "104.0", "118.0", "20.0", DAY0_MILLIS + 2 * DAY_MILLIS - 1]
+                ]))
+            }),
+        )
+        .route(
+            "/api/v5/market/ticker",
+            get(|| async {
+                Json(json!({
+                    "code": "0",
+                    "msg": "",
+                    "data": [{"instId": "ETH-USDT", "last": "2280.1", "vol24h": "5000.5", "ts": DAY0_MILLIS.to_string()}]
+                }))
+            }),
+        )
+        .route(
+            "/api/v5/market/candles",
+            get(|| async {
+                // OKX 最新的K线在前
+                Json(json!({
+                    "code": "0",
+                    "msg": "",
+                    "data": [
+                        [(DAY0_MILLIS + DAY_MILLIS).to_string(), "2300", "2350", "2250", "2280", "900"],
+                        [DAY0_MILLIS.to_string(), "2200", "2310", "2190", "2300", "800"]
+                    ]
+                }))
+            }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+fn client(venue: CryptoVenue, base_url: String) -> Result<CryptoExchangeClient> {
+    let config = CryptoProviderConfig {
+        venue,
+        base_url: Some(base_url),
+        ..CryptoProviderConfig::default()
+    };
+    let fetcher: PriceFetcherConfig = toml::from_str(
+        r#"
+        update_interval_secs = 30
+        cache_ttl_secs = 30
+        max_retries = 3
+        max_concurrent_requests = 10
+        max_requests_per_hour = 3600
+        request_timeout_secs = 10
+        pool_idle_timeout_secs = 30
+        "#,
+    )?;
+    CryptoExchangeClient::new(&config, Arc::new(RateLimiter::new(&fetcher)))
+}
+
+#[tokio::test]
+async fn test_binance_ticker_and_klines() -> Result<()> {
+    let client = client(CryptoVenue::Binance, start_mock_exchange().await)?;
+
+    let ticker = client.fetch_ticker("BTCUSDT").await?;
+    assert_eq!(ticker.last_price, 43250.50);
+    assert_eq!(ticker.volume, 12345.678);
+
+    let klines = client.fetch_daily_klines("BTCUSDT", 2).await?;
+    assert_eq!(klines.len(), 2);
+    assert_eq!(klines[0].date.to_string(), "2024-01-01");
+    assert_eq!(klines[1].close, 118.0);
+
+    let error = client.fetch_ticker("NOPEUSDT").await.unwrap_err();
+    assert!(error.to_string().contains("Invalid symbol"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_okx_ticker_and_klines_sorted_ascending() -> Result<()> {
+    let client = client(CryptoVenue::Okx, start_mock_exchange().await)?;
+
+    let ticker = client.fetch_ticker("ETH-USDT").await?;
+    assert_eq!(ticker.last_price, 2280.1);
+
+    let klines = client.fetch_daily_klines("ETH-USDT", 2).await?;
+    assert_eq!(klines[0].date.to_string(), "2024-01-01");
+    assert_eq!(klines[1].date.to_string(), "2024-01-02");
+    Ok(())
+}
+
+#[test]
+fn test_default_venue_symbols() {
+    assert_eq!(
+        CryptoVenue::Binance.default_venue_symbol("btc", "USD"),
+        Some("BTCUSDT".to_string())
+    );
+    assert_eq!(
+        CryptoVenue::Okx.default_venue_symbol("ETH", "USD"),
+        Some("ETH-USDT".to_string())
+    );
+    assert_eq!(
+        CryptoVenue::Binance.default_venue_symbol("USDT", "USD"),
+        None
+    );
+}
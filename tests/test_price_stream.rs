@@ -0,0 +1,90 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::routing::get;
+use axum::Router;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use trade_alert_rust::config::StreamConfig;
+use trade_alert_rust::services::stream::{subscribe_message, PriceStream};
+use trade_alert_rust::services::CryptoVenue;
+
+/// 启动本地模拟推送服务：把收到的订阅消息转发给测试，
+/// 收到订阅后推送一笔行情，然后主动断开以触发重连
+async fn start_mock_stream() -> (String, mpsc::UnboundedReceiver<String>) {
+    let (requests_tx, requests_rx) = mpsc::unbounded_channel();
+    let app = Router::new().route(
+        "/ws",
+        get(move |ws: WebSocketUpgrade| {
+            let requests_tx = requests_tx.clone();
+            async move { ws.on_upgrade(move |socket| handle_socket(socket, requests_tx)) }
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (format!("ws://{}/ws", addr), requests_rx)
+}
+
+async fn handle_socket(mut socket: WebSocket, requests_tx: mpsc::UnboundedSender<String>) {
+    while let Some(Ok(message)) = socket.recv().await {
+        if let Message::Text(text) = message {
+            let subscribe = text.contains("\"SUBSCRIBE\"");
+            requests_tx.send(text).unwrap();
+            if subscribe {
+                let tick = r#"{"e":"24hrMiniTicker","E":1704067200000,"s":"BTCUSDT","c":"43000.5","v":"1200.0"}"#;
+                socket.send(Message::Text(tick.to_string())).await.unwrap();
+                return;
+            }
+        }
+    }
+}
+
+async fn next_request(requests: &mut mpsc::UnboundedReceiver<String>) -> String {
+    tokio::time::timeout(Duration::from_secs(5), requests.recv())
+        .await
+        .expect("timed out waiting for subscription")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_stream_delivers_ticks_and_resubscribes_after_reconnect() {
+    let (url, mut requests) = start_mock_stream().await;
+    let config = StreamConfig {
+        url: Some(url),
+        reconnect_max_backoff_secs: 1,
+        ..StreamConfig::default()
+    };
+    let stream = Arc::new(PriceStream::new(CryptoVenue::Binance, &config));
+    let (ticks_tx, mut ticks_rx) = mpsc::channel(16);
+    stream.spawn(ticks_tx);
+    stream.set_symbols(BTreeSet::from(["BTCUSDT".to_string()]));
+
+    let first = next_request(&mut requests).await;
+    assert!(first.contains("btcusdt@miniTicker"));
+
+    let tick = tokio::time::timeout(Duration::from_secs(5), ticks_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tick.venue_symbol, "BTCUSDT");
+    assert_eq!(tick.price, 43000.5);
+
+    // 服务端断开后应重连并重新订阅
+    let resubscribe = next_request(&mut requests).await;
+    assert!(resubscribe.contains("btcusdt@miniTicker"));
+    assert!(stream.status().await.reconnects >= 1);
+}
+
+#[test]
+fn test_subscribe_messages() {
+    let symbols = vec!["BTCUSDT".to_string()];
+    let binance = subscribe_message(CryptoVenue::Binance, &symbols, false);
+    assert!(binance.contains("UNSUBSCRIBE") && binance.contains("btcusdt@miniTicker"));
+
+    let okx = subscribe_message(CryptoVenue::Okx, &["BTC-USDT".to_string()], true);
+    assert!(okx.contains("\"subscribe\"") && okx.contains("BTC-USDT"));
+}